@@ -1,21 +1,72 @@
 //! Command-line interface for runst.
 
+use crate::config::ConfigOverrides;
 use clap::{Parser, Subcommand};
+use std::path::PathBuf;
 
 /// A dead simple notification daemon.
 #[derive(Parser, Debug)]
 #[command(name = "runst", version, about)]
 pub struct Cli {
+    /// Path to the configuration file (overrides the default search).
+    #[arg(short, long, value_name = "PATH")]
+    pub config: Option<PathBuf>,
+
+    /// Override the window origin (e.g. "top-right").
+    #[arg(long)]
+    pub origin: Option<String>,
+
+    /// Override the window geometry (e.g. "300x200+10+10").
+    #[arg(long)]
+    pub geometry: Option<String>,
+
+    /// Override the text font.
+    #[arg(long)]
+    pub font: Option<String>,
+
+    /// Override the maximum number of notifications to display at once.
+    #[arg(long)]
+    pub display_limit: Option<usize>,
+
+    /// Run headless: record and forward notifications without opening a
+    /// window.
+    #[arg(long)]
+    pub headless: bool,
+
+    /// Take over the notification bus name from a running daemon,
+    /// importing a previous runst's unread buffer.
+    #[arg(long)]
+    pub replace: bool,
+
     /// Subcommand to run.
     #[command(subcommand)]
     pub command: Option<Command>,
 }
 
+impl Cli {
+    /// Returns the configuration overrides given on the command line.
+    pub fn overrides(&self) -> ConfigOverrides {
+        ConfigOverrides {
+            config: self.config.clone(),
+            origin: self.origin.clone(),
+            geometry: self.geometry.clone(),
+            font: self.font.clone(),
+            display_limit: self.display_limit,
+            headless: self.headless,
+            replace: self.replace,
+        }
+    }
+}
+
 /// Available subcommands.
 #[derive(Subcommand, Debug)]
 pub enum Command {
     /// Query notification history.
     History {
+        /// Subcommand acting on a single entry.
+        #[command(subcommand)]
+        command: Option<HistoryCommand>,
+
         /// Number of recent notifications to show (default: 10).
         #[arg(short, long, default_value = "10")]
         count: usize,
@@ -24,6 +75,20 @@ pub enum Command {
         #[arg(short, long)]
         search: Option<String>,
 
+        /// Only show notifications from matching applications (glob).
+        #[arg(long)]
+        app: Option<String>,
+
+        /// Only show notifications with this urgency.
+        #[arg(long, value_parser = ["low", "normal", "critical"])]
+        urgency: Option<String>,
+
+        /// Only show notifications newer than this age (humantime format,
+        /// e.g. "2h" or "30d"); reads the monthly archives when the window
+        /// reaches past the live history.
+        #[arg(long)]
+        since: Option<String>,
+
         /// Show all notifications (ignores --count).
         #[arg(short, long)]
         all: bool,
@@ -32,6 +97,22 @@ pub enum Command {
         #[arg(short, long)]
         json: bool,
 
+        /// Stream new entries as they arrive (tail -f semantics);
+        /// combined with --json, prints one JSON object per line.
+        #[arg(short, long)]
+        follow: bool,
+
+        /// Output format: "table", "compact", "tsv", or a custom line
+        /// template with {id}, {time}, {app}, {urgency}, {summary},
+        /// {body} and {count} placeholders.
+        #[arg(long, conflicts_with = "json")]
+        format: Option<String>,
+
+        /// Dump the full history as pretty-printed JSON, regardless of
+        /// the storage backend.
+        #[arg(long)]
+        export: bool,
+
         /// Clear all history.
         #[arg(long)]
         clear: bool,
@@ -40,4 +121,204 @@ pub enum Command {
         #[arg(long)]
         path: bool,
     },
+
+    /// Replay historical notifications through the daemon pipeline.
+    ///
+    /// Useful for load-testing redesigns and reproducing layout bugs
+    /// from real data.
+    Replay {
+        /// Source to replay from (only "history" is supported).
+        #[arg(long, default_value = "history")]
+        from: String,
+
+        /// Playback speed multiplier (e.g. "10x" or "2.5").
+        #[arg(short, long, default_value = "10x")]
+        speed: String,
+
+        /// Limit to the most recent N entries.
+        #[arg(short, long)]
+        count: Option<usize>,
+    },
+
+    /// Restore the most recently dismissed notification (undo-close).
+    ///
+    /// Handy bound to a key, like dunst's history-pop.
+    Pop,
+
+    /// Print notification counts and DND state for status bars.
+    ///
+    /// Outputs displayed/unread/history counts and whether do-not-disturb
+    /// (manual or quiet hours) is active, on a single line.
+    Count {
+        /// Output as a single JSON object.
+        #[arg(short, long)]
+        json: bool,
+
+        /// Keep running and re-print the line whenever it changes.
+        #[arg(short, long)]
+        watch: bool,
+    },
+
+    /// List the currently displayed notifications.
+    ///
+    /// Shows IDs, ages and available action keys, for targeting `ctl`
+    /// commands at a specific entry.
+    List {
+        /// Output the unread buffer as JSON.
+        #[arg(short, long)]
+        json: bool,
+    },
+
+    /// Emit waybar-compatible status JSON.
+    ///
+    /// Prints `{"text", "class", "tooltip"}` for a custom bar module; the
+    /// class is "dnd", "unread" or "empty".
+    Statusbar {
+        /// Keep running, re-emitting whenever the unread count or DND
+        /// state changes.
+        #[arg(long)]
+        watch: bool,
+    },
+
+    /// Control a running daemon.
+    Ctl {
+        /// Control command to run.
+        #[command(subcommand)]
+        command: CtlCommand,
+    },
+
+    /// Render the configured template against a sample notification.
+    ///
+    /// Prints the resulting markup so template iteration doesn't require
+    /// sending real notifications and watching the popup.
+    Template {
+        /// Notification to render as JSON ("-" reads from stdin);
+        /// overrides the field flags below.
+        #[arg(long)]
+        json: Option<String>,
+
+        /// Application name of the sample notification.
+        #[arg(long, default_value = "runst")]
+        app: String,
+
+        /// Summary of the sample notification.
+        #[arg(long, default_value = "sample summary")]
+        summary: String,
+
+        /// Body of the sample notification.
+        #[arg(long, default_value = "sample body")]
+        body: String,
+
+        /// Urgency of the sample notification.
+        #[arg(long, default_value = "normal", value_parser = ["low", "normal", "critical"])]
+        urgency: String,
+
+        /// Template string to render instead of the configured one.
+        #[arg(short, long)]
+        template: Option<String>,
+    },
+
+    /// Preview a configuration file with sample notifications.
+    ///
+    /// Shows a persistent sample notification window rendered with the given
+    /// configuration and reloads it whenever the file is saved.
+    Preview {
+        /// Path to the configuration file to preview.
+        #[arg(short, long)]
+        config: PathBuf,
+    },
+}
+
+/// Commands acting on a single history entry.
+#[derive(Subcommand, Debug)]
+pub enum HistoryCommand {
+    /// Print every stored field of one history entry.
+    ///
+    /// The body is printed with its original line breaks, so multi-line
+    /// notifications stay readable.
+    Show {
+        /// ID of the history entry (as shown in the listing).
+        id: u32,
+    },
+
+    /// Pick an entry with a dmenu-style menu and act on it.
+    ///
+    /// Prints entries one per line for piping into dmenu or rofi; the
+    /// selected line comes back on stdin (or the picker is spawned
+    /// directly via --run). The chosen notification is replayed through
+    /// the daemon, or its default action invoked with --invoke.
+    Menu {
+        /// Picker command to spawn (e.g. "rofi -dmenu"); it receives the
+        /// lines on stdin and must print the chosen one.
+        #[arg(long)]
+        run: Option<String>,
+
+        /// Invoke the chosen notification's default action instead of
+        /// replaying it (works while it is still displayed).
+        #[arg(long)]
+        invoke: bool,
+    },
+}
+
+/// Commands for controlling a running daemon.
+#[derive(Subcommand, Debug)]
+pub enum CtlCommand {
+    /// Temporarily mute notifications from matching applications.
+    ///
+    /// History still records muted notifications; only the display is
+    /// suppressed.
+    Mute {
+        /// Glob pattern matching application names.
+        pattern: String,
+
+        /// How long to mute (humantime format, e.g. "30m"); mutes until
+        /// unmuted when omitted.
+        duration: Option<String>,
+    },
+
+    /// Remove a mute.
+    Unmute {
+        /// Glob pattern of the mute to remove.
+        pattern: String,
+    },
+
+    /// List the active mutes.
+    Mutes,
+
+    /// Print the popup's position, size and per-entry bounds as JSON.
+    ///
+    /// Intended for external tools (screenshot annotators, click
+    /// automation) that need to target specific entries.
+    Layout,
+
+    /// Print the daemon's effective configuration as JSON.
+    ///
+    /// Reflects runtime changes made via `set-option`, which a plain
+    /// config file dump would miss.
+    GetConfig,
+
+    /// Change a runtime-tunable option of the running daemon.
+    ///
+    /// Supported keys: "dnd" (bool), "display_limit" (integer) and
+    /// "origin" (e.g. "top-right"). Changes last until the next
+    /// configuration reload.
+    SetOption {
+        /// Option to change.
+        key: String,
+
+        /// New value.
+        value: String,
+    },
+
+    /// Export the unread notification buffer to a file.
+    ExportUnread {
+        /// File to write the snapshot to.
+        file: PathBuf,
+    },
+
+    /// Import an unread notification snapshot from a file.
+    ImportUnread {
+        /// File to read the snapshot from.
+        file: PathBuf,
+    },
 }
@@ -1,7 +1,9 @@
 use crate::error::{Error, Result};
 use crate::notification::{Notification, NotificationFilter, Urgency};
+use crate::theme::{Theme, ThemeColors};
 use colorsys::Rgb;
 use log::LevelFilter;
+use regex::Regex;
 use rust_embed::RustEmbed;
 use serde::de::{Deserializer, Error as SerdeError};
 use serde::ser::Serializer;
@@ -11,11 +13,11 @@ use std::collections::HashMap;
 use std::env;
 use std::fmt;
 use std::fs;
-use std::path::PathBuf;
-use std::process::Command;
+use std::path::{Path, PathBuf};
 use std::result::Result as StdResult;
 use std::str::{self, FromStr};
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::sync::OnceLock;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use tera::Tera;
 
 /// Window origin/anchor point for positioning.
@@ -57,9 +59,151 @@ impl FromStr for Origin {
     }
 }
 
+/// Eviction policy for the unread ring buffer.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum EvictionPolicy {
+    /// Evict the oldest unread notification (default).
+    #[default]
+    Oldest,
+    /// Evict the lowest-urgency notification, oldest first within the same urgency.
+    LowestUrgency,
+    /// Evict from the application with the most unread notifications, oldest first.
+    LargestGroup,
+}
+
+/// What happens when more notifications arrive than `display_limit`.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum OverflowPolicy {
+    /// Evict unread notifications according to the `evict` policy (default).
+    #[default]
+    Evict,
+    /// Queue the overflow and promote entries in arrival order as visible
+    /// ones expire; queued entries do not tick down while hidden.
+    Fifo,
+    /// Like `fifo`, but the most urgent queued entries are promoted first.
+    Urgency,
+}
+
+/// How entry ages are rendered in the popup.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum AgeFormat {
+    /// Fixed-width "12s" / "3m" / "1h" (default).
+    #[default]
+    Compact,
+    /// Prose-like "just now", "5 min", "2 h", "3 d".
+    Human,
+    /// Arrival time on the clock: "14:05" within a day, "06-12 14:05"
+    /// after that, "now" under a minute.
+    Clock,
+}
+
+/// Base direction for laying out notification text.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum TextDirection {
+    /// Detect from the first strong directional character (default).
+    #[default]
+    Auto,
+    /// Force left-to-right.
+    Ltr,
+    /// Force right-to-left.
+    Rtl,
+}
+
+/// How notifications behave while the focused window is fullscreen
+/// (EWMH `_NET_WM_STATE_FULLSCREEN`).
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum FullscreenMode {
+    /// Display the popup normally (default).
+    #[default]
+    Show,
+    /// Hold the notification back and display it once the focused window
+    /// leaves fullscreen.
+    Delay,
+    /// Drop the popup outright; history still records the notification.
+    Suppress,
+}
+
+/// Show/hide animation configuration.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct AnimationConfig {
+    /// Whether show/hide animations are enabled.
+    #[serde(default)]
+    pub enabled: bool,
+    /// Animation duration in milliseconds.
+    #[serde(default = "default_animation_duration")]
+    pub duration_ms: u64,
+    /// Animation style.
+    #[serde(default)]
+    pub style: AnimationStyle,
+    /// Easing curve applied to the animation progress.
+    #[serde(default)]
+    pub easing: Easing,
+}
+
+impl Default for AnimationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            duration_ms: default_animation_duration(),
+            style: AnimationStyle::default(),
+            easing: Easing::default(),
+        }
+    }
+}
+
+fn default_animation_duration() -> u64 {
+    150
+}
+
+/// Style of the show/hide animation.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum AnimationStyle {
+    /// Slide in from the origin edge of the screen (default).
+    #[default]
+    Slide,
+    /// Fade the window opacity (requires a compositor).
+    Fade,
+}
+
+/// Easing curve for animations.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum Easing {
+    /// Constant speed.
+    Linear,
+    /// Start fast, end slow (default).
+    #[default]
+    EaseOut,
+    /// Start slow, end fast.
+    EaseIn,
+}
+
+impl Easing {
+    /// Applies the easing curve to a progress value in `0.0..=1.0`.
+    pub fn apply(self, t: f64) -> f64 {
+        match self {
+            Self::Linear => t,
+            Self::EaseOut => t * (2.0 - t),
+            Self::EaseIn => t * t,
+        }
+    }
+}
+
 /// Environment variable for the configuration file.
 const CONFIG_ENV: &str = "RUNST_CONFIG";
 
+/// Prefix for environment variable overrides of configuration keys.
+const ENV_OVERRIDE_PREFIX: &str = "RUNST_";
+
+/// Separator between nested keys in override variable names.
+const ENV_KEY_SEPARATOR: &str = "__";
+
 /// Name of the default configuration file.
 const DEFAULT_CONFIG: &str = concat!(env!("CARGO_PKG_NAME"), ".toml");
 
@@ -68,6 +212,11 @@ const DEFAULT_CONFIG: &str = concat!(env!("CARGO_PKG_NAME"), ".toml");
 #[folder = "config/"]
 struct EmbeddedConfig;
 
+/// Embedded configuration decoded and parsed once, on first use; reloads
+/// without a user configuration file clone the cached value instead of
+/// re-parsing the TOML.
+static EMBEDDED_VALUE: OnceLock<Option<toml::Value>> = OnceLock::new();
+
 /// Configuration.
 #[derive(Debug, Deserialize, Serialize)]
 pub struct Config {
@@ -82,12 +231,76 @@ pub struct Config {
     /// Color mapping for specific applications (app_name -> hex color).
     #[serde(default)]
     pub app_colors: HashMap<String, String>,
+    /// Per-application configuration overrides (app_name -> overrides).
+    #[serde(default)]
+    pub app: HashMap<String, AppConfig>,
     /// Notification styling rules based on patterns.
     #[serde(default)]
     pub rules: Vec<NotificationRule>,
+    /// GNTP network listener configuration.
+    #[serde(default)]
+    pub gntp: crate::gntp::GntpConfig,
+    /// Sound configuration.
+    #[serde(default)]
+    pub sound: crate::sound::SoundConfig,
+    /// Console sink configuration.
+    #[serde(default)]
+    pub console: crate::console::ConsoleConfig,
+    /// Custom command executor configuration.
+    #[serde(default)]
+    pub commands: crate::executor::CommandsConfig,
+    /// Show/hide animation configuration.
+    #[serde(default)]
+    pub animation: AnimationConfig,
+    /// History storage configuration.
+    #[serde(default)]
+    pub history: crate::history::HistoryConfig,
+    /// Lowercased `app_colors` keys with their colors, precomputed after
+    /// parse so per-frame lookups avoid re-lowercasing the patterns.
+    #[serde(skip)]
+    app_color_matchers: Vec<(String, String)>,
+    /// Lowercased `app` keys with their original key, precomputed after parse.
+    #[serde(skip)]
+    app_config_matchers: Vec<(String, String)>,
+}
+
+/// Per-application configuration overrides.
+///
+/// Declared as `[app."firefox"]` style sections; keys support glob-style
+/// patterns with `*`. Values set here take precedence over the urgency
+/// section for notifications from the matching application.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct AppConfig {
+    /// Foreground color override (hex).
+    #[serde(default)]
+    pub foreground: Option<String>,
+    /// Background color override (hex).
+    #[serde(default)]
+    pub background: Option<String>,
+    /// Timeout override in seconds.
+    #[serde(default)]
+    pub timeout: Option<u32>,
+    /// Auto clear override.
+    #[serde(default)]
+    pub auto_clear: Option<bool>,
+    /// Template override for the notification message.
+    #[serde(default)]
+    pub template: Option<String>,
+    /// Font override.
+    #[serde(default)]
+    pub font: Option<String>,
+    /// Window origin override.
+    #[serde(default)]
+    pub origin: Option<Origin>,
 }
 
+/// Prefix that marks a rule pattern as a regular expression.
+const REGEX_PATTERN_PREFIX: &str = "regex:";
+
 /// A rule for styling notifications based on patterns.
+///
+/// Patterns are glob-style with `*` by default; prefixing a pattern with
+/// `regex:` makes it a regular expression (e.g. `regex:^Build (failed|errored)`).
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct NotificationRule {
     /// Pattern to match against app_name (glob-style with *).
@@ -105,69 +318,295 @@ pub struct NotificationRule {
     /// Background color to use for matching notifications.
     #[serde(default)]
     pub background: Option<String>,
+    /// Timeout override in seconds for matching notifications (0 disables
+    /// auto-clear). Takes precedence over the sender's expire timeout and
+    /// the urgency section defaults.
+    #[serde(default)]
+    pub timeout: Option<u32>,
+    /// Urgency override for matching notifications, escalating or
+    /// downgrading them regardless of the sender's hints.
+    #[serde(default)]
+    pub urgency: Option<Urgency>,
+    /// Replaces the summary of matching notifications.
+    #[serde(default)]
+    pub set_summary: Option<String>,
+    /// Replaces the body of matching notifications.
+    #[serde(default)]
+    pub set_body: Option<String>,
+    /// Regex substitutions applied to the summary and body of matching
+    /// notifications (e.g. to strip noisy prefixes from CI bot messages).
+    #[serde(default)]
+    pub replace: Option<Vec<TextReplace>>,
+    /// How long matching notifications are kept in history (humantime
+    /// format, e.g. "1d" or "2h 30m"). Unset entries are kept until the
+    /// global history limit evicts them.
+    #[serde(default)]
+    pub history_ttl: Option<String>,
+    /// Maximum number of matching notifications to keep in history; the
+    /// oldest matching entries are pruned first.
+    #[serde(default)]
+    pub history_limit: Option<usize>,
+    /// Overrides the global `downgrade_repeats` setting for matching
+    /// notifications.
+    #[serde(default)]
+    pub downgrade_repeats: Option<bool>,
+    /// Sound override for matching notifications: a theme sound name or a
+    /// file path, taking precedence over the sender's hints and the
+    /// urgency default.
+    #[serde(default)]
+    pub sound: Option<String>,
+    /// Template override for matching notifications, taking precedence
+    /// over the urgency section's template.
+    #[serde(default)]
+    pub template: Option<String>,
+    /// Age display override for matching notifications.
+    #[serde(default)]
+    pub age_format: Option<AgeFormat>,
+    /// Overrides the global `expand_emoji` setting for matching
+    /// notifications.
+    #[serde(default)]
+    pub expand_emoji: Option<bool>,
+    /// Forces the base text direction for matching notifications
+    /// instead of detecting it from the content.
+    #[serde(default)]
+    pub text_direction: Option<TextDirection>,
+    /// Compiled regex for the app_name pattern, if it uses the `regex:` prefix.
+    #[serde(skip)]
+    app_name_regex: Option<Regex>,
+    /// Compiled regex for the summary pattern, if it uses the `regex:` prefix.
+    #[serde(skip)]
+    summary_regex: Option<Regex>,
+    /// Compiled regex for the body pattern, if it uses the `regex:` prefix.
+    #[serde(skip)]
+    body_regex: Option<Regex>,
+    /// Parsed form of `history_ttl`.
+    #[serde(skip)]
+    history_ttl_duration: Option<Duration>,
+    /// Lowercased app_name glob pattern, precomputed by [`NotificationRule::compile`].
+    #[serde(skip)]
+    app_name_lower: Option<String>,
+    /// Lowercased summary glob pattern, precomputed by [`NotificationRule::compile`].
+    #[serde(skip)]
+    summary_lower: Option<String>,
+    /// Lowercased body glob pattern, precomputed by [`NotificationRule::compile`].
+    #[serde(skip)]
+    body_lower: Option<String>,
 }
 
 /// Checks if a value matches a glob-style pattern (case-insensitive).
-/// Supports `*` as wildcard.
+///
+/// Guarantees:
+/// - `*` matches any sequence of characters, including none; consecutive
+///   `*` behave like a single one.
+/// - Literal segments before the first and after the last `*` are
+///   anchored: `a*b*c` only matches values starting with `a` and ending
+///   with `c`, and segments never overlap (`a*a` does not match `a`).
+/// - Both sides are compared after Unicode lowercasing, so matching
+///   follows lowercase folding.
+/// - An empty pattern matches only the empty value.
 pub fn glob_match(pattern: &str, value: &str) -> bool {
-    let pattern_lower = pattern.to_lowercase();
-    let value_lower = value.to_lowercase();
+    glob_match_lower(&pattern.to_lowercase(), &value.to_lowercase())
+}
 
+/// [`glob_match`] over already-lowercased inputs.
+///
+/// Hot paths (rule matching per notification per frame) lowercase the
+/// pattern once at parse time and the value once per notification, so the
+/// comparison itself allocates nothing.
+fn glob_match_lower(pattern_lower: &str, value_lower: &str) -> bool {
     if !pattern_lower.contains('*') {
         return pattern_lower == value_lower;
     }
 
+    // The pattern contains at least one `*`, so there are at least two
+    // parts: anchor the first at the start and the last at the end, then
+    // consume the middle parts left to right without overlapping.
     let parts: Vec<&str> = pattern_lower.split('*').collect();
+    let (first, rest) = parts.split_first().expect("split yields at least one part");
+    let (last, middle) = rest.split_last().expect("pattern contains a wildcard");
 
-    match parts.as_slice() {
-        ["", suffix] => value_lower.ends_with(suffix),
-        [prefix, ""] => value_lower.starts_with(prefix),
-        [prefix, suffix] => value_lower.starts_with(prefix) && value_lower.ends_with(suffix),
-        ["", middle, ""] => value_lower.contains(middle),
-        _ => {
-            let mut remaining = value_lower.as_str();
-            for part in &parts {
-                if part.is_empty() {
-                    continue;
-                }
-                if let Some(pos) = remaining.find(part) {
-                    remaining = &remaining[pos + part.len()..];
-                } else {
-                    return false;
-                }
-            }
-            true
+    let mut remaining = value_lower;
+    if !remaining.starts_with(first) {
+        return false;
+    }
+    remaining = &remaining[first.len()..];
+    if !remaining.ends_with(last) {
+        return false;
+    }
+    remaining = &remaining[..remaining.len() - last.len()];
+
+    for part in middle {
+        if part.is_empty() {
+            continue;
+        }
+        match remaining.find(part) {
+            Some(pos) => remaining = &remaining[pos + part.len()..],
+            None => return false,
         }
     }
+    true
+}
+
+/// A single regex substitution applied by a rule.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct TextReplace {
+    /// Regular expression to search for.
+    pub pattern: String,
+    /// Replacement text (supports capture group references like `$1`).
+    pub with: String,
+    /// Compiled form of the pattern.
+    #[serde(skip)]
+    regex: Option<Regex>,
+}
+
+/// Checks a single rule field: a compiled regex takes precedence, otherwise
+/// the pattern is treated as a glob. A missing pattern always matches.
+///
+/// The lowercased pattern and value are used when available so compiled
+/// rules avoid re-lowercasing in hot paths.
+fn field_matches(
+    pattern: &Option<String>,
+    pattern_lower: &Option<String>,
+    regex: &Option<Regex>,
+    value: &str,
+    value_lower: &str,
+) -> bool {
+    match (pattern, regex) {
+        (_, Some(regex)) => regex.is_match(value),
+        (Some(pattern), None) => match pattern_lower {
+            Some(pattern_lower) => glob_match_lower(pattern_lower, value_lower),
+            None => glob_match(pattern, value),
+        },
+        (None, None) => true,
+    }
 }
 
 impl NotificationRule {
-    /// Checks if this rule matches the given notification.
-    pub fn matches(&self, app_name: &str, summary: &str, body: &str) -> bool {
-        // All specified patterns must match
-        if let Some(ref pattern) = self.app_name
-            && !glob_match(pattern, app_name)
-        {
-            return false;
+    /// Compiles any `regex:` patterns in this rule, caching the result.
+    pub fn compile(&mut self) -> Result<()> {
+        fn compile_pattern(pattern: &Option<String>) -> Result<Option<Regex>> {
+            match pattern
+                .as_deref()
+                .and_then(|p| p.strip_prefix(REGEX_PATTERN_PREFIX))
+            {
+                Some(raw) => Regex::new(raw)
+                    .map(Some)
+                    .map_err(|e| Error::Config(format!("invalid rule regex `{raw}`: {e}"))),
+                None => Ok(None),
+            }
         }
-        if let Some(ref pattern) = self.summary
-            && !glob_match(pattern, summary)
-        {
-            return false;
+        self.app_name_regex = compile_pattern(&self.app_name)?;
+        self.summary_regex = compile_pattern(&self.summary)?;
+        self.body_regex = compile_pattern(&self.body)?;
+        if let Some(replacements) = &mut self.replace {
+            for replacement in replacements {
+                replacement.regex = Some(Regex::new(&replacement.pattern).map_err(|e| {
+                    Error::Config(format!(
+                        "invalid replace regex `{}`: {}",
+                        replacement.pattern, e
+                    ))
+                })?);
+            }
         }
-        if let Some(ref pattern) = self.body
-            && !glob_match(pattern, body)
-        {
-            return false;
+        self.history_ttl_duration = match &self.history_ttl {
+            Some(raw) => Some(
+                humantime::parse_duration(raw)
+                    .map_err(|e| Error::Config(format!("invalid history_ttl `{raw}`: {e}")))?,
+            ),
+            None => None,
+        };
+        // Precompute the lowercased glob patterns (regex patterns match
+        // through their compiled form instead)
+        fn lower_pattern(pattern: &Option<String>, regex: &Option<Regex>) -> Option<String> {
+            match (pattern, regex) {
+                (Some(pattern), None) => Some(pattern.to_lowercase()),
+                _ => None,
+            }
+        }
+        self.app_name_lower = lower_pattern(&self.app_name, &self.app_name_regex);
+        self.summary_lower = lower_pattern(&self.summary, &self.summary_regex);
+        self.body_lower = lower_pattern(&self.body, &self.body_regex);
+        Ok(())
+    }
+
+    /// Returns the parsed history retention period, if configured.
+    pub fn history_ttl(&self) -> Option<Duration> {
+        self.history_ttl_duration
+    }
+
+    /// Applies this rule's text transforms to the notification fields.
+    pub fn apply_transforms(&self, summary: &mut String, body: &mut String) {
+        if let Some(new_summary) = &self.set_summary {
+            *summary = new_summary.clone();
+        }
+        if let Some(new_body) = &self.set_body {
+            *body = new_body.clone();
+        }
+        if let Some(replacements) = &self.replace {
+            for replacement in replacements {
+                if let Some(regex) = &replacement.regex {
+                    *summary = regex
+                        .replace_all(summary, replacement.with.as_str())
+                        .into_owned();
+                    *body = regex
+                        .replace_all(body, replacement.with.as_str())
+                        .into_owned();
+                }
+            }
         }
-        true
+    }
+
+    /// Checks if this rule matches the given notification.
+    pub fn matches(&self, app_name: &str, summary: &str, body: &str) -> bool {
+        self.matches_lower(
+            app_name,
+            &app_name.to_lowercase(),
+            summary,
+            &summary.to_lowercase(),
+            body,
+            &body.to_lowercase(),
+        )
+    }
+
+    /// Like [`NotificationRule::matches`], with caller-provided lowercased
+    /// values so hot paths lowercase once per notification rather than
+    /// once per rule.
+    fn matches_lower(
+        &self,
+        app_name: &str,
+        app_name_lower: &str,
+        summary: &str,
+        summary_lower: &str,
+        body: &str,
+        body_lower: &str,
+    ) -> bool {
+        // All specified patterns must match
+        field_matches(
+            &self.app_name,
+            &self.app_name_lower,
+            &self.app_name_regex,
+            app_name,
+            app_name_lower,
+        ) && field_matches(
+            &self.summary,
+            &self.summary_lower,
+            &self.summary_regex,
+            summary,
+            summary_lower,
+        ) && field_matches(
+            &self.body,
+            &self.body_lower,
+            &self.body_regex,
+            body,
+            body_lower,
+        )
     }
 }
 
 impl Config {
-    /// Parses the configuration file.
-    pub fn parse() -> Result<Self> {
-        for config_path in [
+    /// Returns the path of the configuration file that would be loaded, if any.
+    pub fn path() -> Option<PathBuf> {
+        [
             env::var(CONFIG_ENV).ok().map(PathBuf::from),
             dirs::config_dir().map(|p| p.join(env!("CARGO_PKG_NAME")).join(DEFAULT_CONFIG)),
             dirs::home_dir().map(|p| {
@@ -175,32 +614,240 @@ impl Config {
                     .join(DEFAULT_CONFIG)
             }),
         ]
-        .iter()
+        .into_iter()
         .flatten()
-        {
-            if config_path.exists() {
-                let contents = fs::read_to_string(config_path)?;
-                let config = toml::from_str(&contents)?;
-                return Ok(config);
-            }
+        .find(|config_path| config_path.exists())
+    }
+
+    /// Parses the configuration file.
+    pub fn parse() -> Result<Self> {
+        if let Some(config_path) = Self::path() {
+            return Self::load(&config_path);
         }
-        if let Some(embedded_config) = EmbeddedConfig::get(DEFAULT_CONFIG)
-            .and_then(|v| String::from_utf8(v.data.as_ref().to_vec()).ok())
-        {
-            let config = toml::from_str(&embedded_config)?;
+        let embedded = EMBEDDED_VALUE.get_or_init(|| {
+            EmbeddedConfig::get(DEFAULT_CONFIG)
+                .and_then(|v| String::from_utf8(v.data.as_ref().to_vec()).ok())
+                .and_then(|raw| toml::from_str(&raw).ok())
+        });
+        if let Some(value) = embedded {
+            let mut value = value.clone();
+            apply_env_overrides(&mut value);
+            let mut config: Self = value.try_into()?;
+            config.finalize()?;
             Ok(config)
         } else {
             Err(Error::Config(String::from("configuration file not found")))
         }
     }
 
-    /// Returns the appropriate urgency configuration.
-    pub fn get_urgency_config(&self, urgency: &Urgency) -> UrgencyConfig {
-        match urgency {
+    /// Loads the configuration from the given file, layering it over any
+    /// files listed in its `include` key and merging drop-in fragments from
+    /// a sibling `<file>.d/` directory.
+    pub fn load(path: &Path) -> Result<Self> {
+        let mut visited = Vec::new();
+        let mut value = Self::load_value(path, &mut visited)?;
+        Self::merge_drop_ins(&mut value, path)?;
+        apply_env_overrides(&mut value);
+        let mut config: Self = value.try_into()?;
+        config.finalize()?;
+        Ok(config)
+    }
+
+    /// Loads a TOML file, resolving its `include = [...]` key.
+    ///
+    /// Included files are merged first (in listed order) with the including
+    /// file taking precedence, so shared theme and rule files can be layered
+    /// under machine-specific settings. Includes may nest; cycles are
+    /// rejected with an error naming the offending file.
+    fn load_value(path: &Path, visited: &mut Vec<PathBuf>) -> Result<toml::Value> {
+        let canonical = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        if visited.contains(&canonical) {
+            return Err(Error::Config(format!(
+                "include cycle detected at {}",
+                path.display()
+            )));
+        }
+        visited.push(canonical);
+        let contents = fs::read_to_string(path)
+            .map_err(|e| Error::Config(format!("failed to read {}: {}", path.display(), e)))?;
+        let mut value: toml::Value = toml::from_str(&contents)
+            .map_err(|e| Error::Config(format!("failed to parse {}: {}", path.display(), e)))?;
+        if let Some(includes) = value.as_table_mut().and_then(|t| t.remove("include")) {
+            let toml::Value::Array(includes) = includes else {
+                return Err(Error::Config(format!(
+                    "`include` must be an array of paths in {}",
+                    path.display()
+                )));
+            };
+            let mut base = toml::Value::Table(Default::default());
+            for include in includes {
+                let Some(include_path) = include.as_str() else {
+                    return Err(Error::Config(format!(
+                        "invalid `include` entry in {}",
+                        path.display()
+                    )));
+                };
+                // Relative includes resolve against the including file
+                let include_path = PathBuf::from(include_path);
+                let include_path = if include_path.is_absolute() {
+                    include_path
+                } else {
+                    path.parent()
+                        .unwrap_or_else(|| Path::new("."))
+                        .join(include_path)
+                };
+                log::debug!("including config file {}", include_path.display());
+                let included = Self::load_value(&include_path, visited)?;
+                merge_toml(&mut base, included);
+            }
+            merge_toml(&mut base, value);
+            value = base;
+        }
+        visited.pop();
+        Ok(value)
+    }
+
+    /// Finalizes a freshly parsed configuration (e.g. compiles rule patterns
+    /// and applies the selected color theme).
+    fn finalize(&mut self) -> Result<()> {
+        for rule in &mut self.rules {
+            rule.compile()?;
+        }
+        self.app_color_matchers = self
+            .app_colors
+            .iter()
+            .map(|(pattern, color)| (pattern.to_lowercase(), color.clone()))
+            .collect();
+        self.app_config_matchers = self
+            .app
+            .keys()
+            .map(|pattern| (pattern.to_lowercase(), pattern.clone()))
+            .collect();
+        if let Some(theme_name) = &self.global.theme {
+            let theme = Theme::load(theme_name)?;
+            fn apply(target: &mut UrgencyConfig, colors: &ThemeColors) -> Result<()> {
+                target.background = Rgb::from_hex_str(&colors.background).map_err(|e| {
+                    Error::Config(format!(
+                        "invalid theme color `{}`: {}",
+                        colors.background, e
+                    ))
+                })?;
+                target.foreground = Rgb::from_hex_str(&colors.foreground).map_err(|e| {
+                    Error::Config(format!(
+                        "invalid theme color `{}`: {}",
+                        colors.foreground, e
+                    ))
+                })?;
+                Ok(())
+            }
+            apply(&mut self.urgency_low, &theme.urgency_low)?;
+            apply(&mut self.urgency_normal, &theme.urgency_normal)?;
+            apply(&mut self.urgency_critical, &theme.urgency_critical)?;
+        }
+        if let Some(border_color) = &self.global.border_color {
+            Rgb::from_hex_str(border_color).map_err(|e| {
+                Error::Config(format!("invalid border_color `{border_color}`: {e}"))
+            })?;
+        }
+        for urgency_config in [
+            &self.urgency_low,
+            &self.urgency_normal,
+            &self.urgency_critical,
+        ] {
+            if let Some(countdown_color) = &urgency_config.countdown_color {
+                Rgb::from_hex_str(countdown_color).map_err(|e| {
+                    Error::Config(format!("invalid countdown_color `{countdown_color}`: {e}"))
+                })?;
+            }
+        }
+        for (app_name, app_config) in &self.app {
+            for color in [&app_config.foreground, &app_config.background]
+                .into_iter()
+                .flatten()
+            {
+                Rgb::from_hex_str(color).map_err(|e| {
+                    Error::Config(format!(
+                        "invalid color `{color}` for app `{app_name}`: {e}"
+                    ))
+                })?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Merges drop-in fragments from `<file>.d/` over the given value in
+    /// lexical order, so rules and app_colors can be managed as separate
+    /// generated files.
+    fn merge_drop_ins(value: &mut toml::Value, path: &Path) -> Result<()> {
+        let file_name = path
+            .file_name()
+            .and_then(|v| v.to_str())
+            .unwrap_or(DEFAULT_CONFIG);
+        let drop_in_dir = path.with_file_name(format!("{file_name}.d"));
+        if !drop_in_dir.is_dir() {
+            return Ok(());
+        }
+        let mut fragments: Vec<PathBuf> = fs::read_dir(&drop_in_dir)?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|p| p.extension().is_some_and(|ext| ext == "toml"))
+            .collect();
+        fragments.sort();
+        for fragment in fragments {
+            let contents = fs::read_to_string(&fragment)?;
+            let overlay: toml::Value = toml::from_str(&contents).map_err(|e| {
+                Error::Config(format!("failed to parse {}: {}", fragment.display(), e))
+            })?;
+            log::debug!("merging config fragment {}", fragment.display());
+            merge_toml(value, overlay);
+        }
+        Ok(())
+    }
+
+    /// Returns the appropriate urgency configuration with any per-application
+    /// overrides merged over it.
+    pub fn get_urgency_config(&self, urgency: &Urgency, app_name: &str) -> UrgencyConfig {
+        let mut urgency_config = match urgency {
             Urgency::Low => self.urgency_low.clone(),
             Urgency::Normal => self.urgency_normal.clone(),
             Urgency::Critical => self.urgency_critical.clone(),
+        };
+        if let Some(app_config) = self.get_app_config(app_name) {
+            if let Some(background) = app_config
+                .background
+                .as_deref()
+                .and_then(|v| Rgb::from_hex_str(v).ok())
+            {
+                urgency_config.background = background;
+            }
+            if let Some(foreground) = app_config
+                .foreground
+                .as_deref()
+                .and_then(|v| Rgb::from_hex_str(v).ok())
+            {
+                urgency_config.foreground = foreground;
+            }
+            if let Some(timeout) = app_config.timeout {
+                urgency_config.timeout = timeout;
+            }
+            if app_config.auto_clear.is_some() {
+                urgency_config.auto_clear = app_config.auto_clear;
+            }
         }
+        urgency_config
+    }
+
+    /// Returns the configuration overrides for a specific application, if any.
+    /// Keys support glob-style patterns with `*` as a wildcard.
+    pub fn get_app_config(&self, app_name: &str) -> Option<&AppConfig> {
+        if let Some(app_config) = self.app.get(app_name) {
+            return Some(app_config);
+        }
+        let app_name_lower = app_name.to_lowercase();
+        self.app_config_matchers
+            .iter()
+            .find(|(pattern_lower, _)| glob_match_lower(pattern_lower, &app_name_lower))
+            .and_then(|(_, key)| self.app.get(key))
     }
 
     /// Returns the color for a specific application, if configured.
@@ -212,14 +859,12 @@ impl Config {
             return Some(color);
         }
 
-        // Then try pattern matching
-        for (pattern, color) in &self.app_colors {
-            if glob_match(pattern, app_name) {
-                return Some(color);
-            }
-        }
-
-        None
+        // Then try the precompiled pattern matchers
+        let app_name_lower = app_name.to_lowercase();
+        self.app_color_matchers
+            .iter()
+            .find(|(pattern_lower, _)| glob_match_lower(pattern_lower, &app_name_lower))
+            .map(|(_, color)| color)
     }
 
     /// Returns the first matching rule for a notification, if any.
@@ -229,9 +874,154 @@ impl Config {
         summary: &str,
         body: &str,
     ) -> Option<&NotificationRule> {
-        self.rules
-            .iter()
-            .find(|rule| rule.matches(app_name, summary, body))
+        // Lowercase once here instead of once per rule
+        let app_name_lower = app_name.to_lowercase();
+        let summary_lower = summary.to_lowercase();
+        let body_lower = body.to_lowercase();
+        self.rules.iter().find(|rule| {
+            rule.matches_lower(
+                app_name,
+                &app_name_lower,
+                summary,
+                &summary_lower,
+                body,
+                &body_lower,
+            )
+        })
+    }
+}
+
+/// Command-line overrides applied on top of the configuration file.
+///
+/// Useful for quickly testing layouts or running a second instance on
+/// another screen without editing the file.
+#[derive(Clone, Debug, Default)]
+pub struct ConfigOverrides {
+    /// Configuration file path override.
+    pub config: Option<PathBuf>,
+    /// Window origin override.
+    pub origin: Option<String>,
+    /// Window geometry override.
+    pub geometry: Option<String>,
+    /// Font override.
+    pub font: Option<String>,
+    /// Display limit override.
+    pub display_limit: Option<usize>,
+    /// Headless mode override.
+    pub headless: bool,
+    /// Bus name replacement override.
+    pub replace: bool,
+}
+
+impl ConfigOverrides {
+    /// Parses the configuration, honoring the path override and applying
+    /// the remaining overrides on top of the file values.
+    pub fn load(&self) -> Result<Config> {
+        let mut config = match &self.config {
+            Some(path) => Config::load(path),
+            None => Config::parse(),
+        }?;
+        self.apply(&mut config)?;
+        Ok(config)
+    }
+
+    /// Applies the overrides to a parsed configuration.
+    pub fn apply(&self, config: &mut Config) -> Result<()> {
+        if let Some(origin) = &self.origin {
+            config.global.origin = Origin::from_str(origin)?;
+        }
+        if let Some(geometry) = &self.geometry {
+            config.global.geometry = Geometry::from_str(geometry)?;
+        }
+        if let Some(font) = &self.font {
+            config.global.font = font.clone();
+        }
+        if let Some(display_limit) = self.display_limit {
+            config.global.display_limit = display_limit;
+        }
+        if self.headless {
+            config.global.headless = true;
+        }
+        if self.replace {
+            config.global.replace = true;
+        }
+        Ok(())
+    }
+}
+
+/// Applies `RUNST_GLOBAL__FONT` style environment variable overrides.
+///
+/// Variable names map to configuration keys by stripping the `RUNST_`
+/// prefix, splitting on `__` and lowercasing each segment (e.g.
+/// `RUNST_URGENCY_CRITICAL__TIMEOUT` sets `urgency_critical.timeout`), so
+/// containerized or scripted sessions can tweak settings without writing
+/// files. Values are parsed as TOML scalars and fall back to plain strings.
+fn apply_env_overrides(value: &mut toml::Value) {
+    for (name, raw) in env::vars() {
+        let Some(key_path) = name.strip_prefix(ENV_OVERRIDE_PREFIX) else {
+            continue;
+        };
+        if name == CONFIG_ENV || !key_path.contains(ENV_KEY_SEPARATOR) {
+            continue;
+        }
+        let keys: Vec<String> = key_path
+            .split(ENV_KEY_SEPARATOR)
+            .map(str::to_lowercase)
+            .collect();
+        let Some((last, rest)) = keys.split_last() else {
+            continue;
+        };
+        let mut target = &mut *value;
+        let mut reachable = true;
+        for key in rest {
+            let toml::Value::Table(table) = target else {
+                log::warn!("ignoring override {}: {} is not a table", name, key);
+                reachable = false;
+                break;
+            };
+            target = table
+                .entry(key.clone())
+                .or_insert_with(|| toml::Value::Table(Default::default()));
+        }
+        if !reachable {
+            continue;
+        }
+        if let toml::Value::Table(table) = target {
+            log::debug!("applying environment override {}", name);
+            table.insert(last.clone(), parse_env_value(&raw));
+        } else {
+            log::warn!("ignoring override {}: parent is not a table", name);
+        }
+    }
+}
+
+/// Parses an override value, keeping TOML scalar types where possible.
+fn parse_env_value(raw: &str) -> toml::Value {
+    // Parse through a tiny document so numbers and booleans keep their type
+    toml::from_str::<toml::Value>(&format!("v = {raw}"))
+        .ok()
+        .and_then(|value| value.get("v").cloned())
+        .unwrap_or_else(|| toml::Value::String(raw.to_string()))
+}
+
+/// Recursively merges `overlay` into `base`: tables merge by key, arrays are
+/// appended, and scalars are replaced.
+fn merge_toml(base: &mut toml::Value, overlay: toml::Value) {
+    match (base, overlay) {
+        (toml::Value::Table(base_table), toml::Value::Table(overlay_table)) => {
+            for (key, overlay_value) in overlay_table {
+                match base_table.get_mut(&key) {
+                    Some(base_value) => merge_toml(base_value, overlay_value),
+                    None => {
+                        base_table.insert(key, overlay_value);
+                    }
+                }
+            }
+        }
+        (toml::Value::Array(base_array), toml::Value::Array(overlay_array)) => {
+            base_array.extend(overlay_array);
+        }
+        (base, overlay) => *base = overlay,
     }
 }
 
@@ -241,6 +1031,10 @@ pub struct GlobalConfig {
     /// Log verbosity.
     #[serde(deserialize_with = "deserialize_level_from_string", skip_serializing)]
     pub log_verbosity: LevelFilter,
+    /// Color theme overriding the urgency foreground/background colors
+    /// (built-in: "gruvbox", "nord", "catppuccin", "solarized").
+    #[serde(default)]
+    pub theme: Option<String>,
     /// Whether if a startup notification should be shown.
     pub startup_notification: bool,
     /// Geometry of the notification window.
@@ -250,17 +1044,131 @@ pub struct GlobalConfig {
     /// The geometry x,y become offsets from this origin.
     #[serde(default)]
     pub origin: Origin,
+    /// Monitor to place the notification window on, by RandR output name
+    /// (e.g. "DP-1") or index. Defaults to the whole virtual screen.
+    #[serde(default)]
+    pub monitor: Option<String>,
     /// Whether if the window will be resized to wrap the content.
     pub wrap_content: bool,
+    /// Whether notifications are anchored near the sending application's
+    /// window (looked up via EWMH) instead of the global origin. Falls back
+    /// to the origin when the application has no visible window. Requires
+    /// `wrap_content`.
+    #[serde(default)]
+    pub anchor_to_app: bool,
     /// Text font.
     pub font: String,
     /// Template for the notification message.
     pub template: String,
+    /// Template for a header line drawn above the stack. The context
+    /// carries `unread_count`, `displayed_count`, `hidden_older` and
+    /// `hidden_newer`; rendering to whitespace omits the line.
+    #[serde(default)]
+    pub header_template: Option<String>,
+    /// Template replacing the built-in "... and N more" footer, with the
+    /// same context as `header_template`.
+    #[serde(default)]
+    pub footer_template: Option<String>,
     /// Maximum number of notifications to display at once (ring buffer).
     /// When exceeded, oldest notifications are automatically dismissed.
     /// Set to 0 for unlimited.
     #[serde(default)]
     pub display_limit: usize,
+    /// Maximum number of notifications (read and unread) kept in memory.
+    /// Past the cap, the oldest already-read entries are evicted first;
+    /// unread entries are never evicted. Set to 0 for unlimited.
+    /// Default is 1000.
+    #[serde(default = "default_buffer_limit")]
+    pub buffer_limit: usize,
+    /// Maximum body size in bytes; longer bodies are truncated with an
+    /// ellipsis before storage and rendering, as some applications send
+    /// multi-megabyte bodies. Set to 0 for unlimited. Default is 8192.
+    #[serde(default = "default_max_body_length")]
+    pub max_body_length: usize,
+    /// Which notification to evict when the display limit is exceeded.
+    #[serde(default)]
+    pub evict: EvictionPolicy,
+    /// How overflow past the display limit is handled: evicted (default)
+    /// or queued and promoted as visible notifications expire.
+    #[serde(default)]
+    pub overflow: OverflowPolicy,
+    /// Width of the window border in pixels (0 disables the border).
+    #[serde(default)]
+    pub border_width: u32,
+    /// Border color (hex). Defaults to the urgency foreground color.
+    #[serde(default)]
+    pub border_color: Option<String>,
+    /// Corner radius of the window in pixels (0 keeps sharp corners).
+    #[serde(default)]
+    pub corner_radius: u32,
+    /// Whether to draw a thin shrinking bar on each entry showing the
+    /// remaining time before auto-clear.
+    #[serde(default)]
+    pub show_countdown: bool,
+    /// Whether bodies containing only whitespace collapse to the compact
+    /// single-line layout, like genuinely empty ones. Default is true.
+    #[serde(default = "default_collapse_empty_body")]
+    pub collapse_empty_body: bool,
+    /// Whether bodies are interpreted as a small Markdown subset (bold,
+    /// italic, code, links, lists) instead of plain text, for bots that
+    /// send Markdown.
+    #[serde(default)]
+    pub render_markdown: bool,
+    /// Whether `:rocket:`-style shortcodes in summaries and bodies are
+    /// expanded to Unicode emoji, for GitHub/Slack webhook-style senders.
+    /// Rules can override this per match.
+    #[serde(default)]
+    pub expand_emoji: bool,
+    /// Whether each entry shows a colored rounded square with the app's
+    /// initials (color picked deterministically per app).
+    #[serde(default)]
+    pub show_app_badge: bool,
+    /// Do not disturb: suppress popups while notifications keep being
+    /// recorded in history. Toggleable at runtime via the control interface.
+    #[serde(default)]
+    pub dnd: bool,
+    /// Scheduled quiet hours during which non-critical notifications are
+    /// recorded to history but not displayed. Entries are daily time
+    /// ranges ("22:00-08:00", wrapping past midnight is fine) or weekday
+    /// names ("Sat", "Sunday"); any matching entry activates quiet hours.
+    #[serde(default)]
+    pub quiet_hours: Vec<String>,
+    /// Headless mode: claim the D-Bus name, record history and run rules
+    /// and hooks, but never open an X11 window. For servers and kiosks
+    /// that only want the history and forwarding features. Read once at
+    /// startup; a configuration reload cannot toggle it.
+    #[serde(default)]
+    pub headless: bool,
+    /// Take over `org.freedesktop.Notifications` from a daemon that
+    /// already owns it instead of failing to start; a previous runst
+    /// additionally hands over its unread buffer. Usually set with
+    /// `--replace`.
+    #[serde(default)]
+    pub replace: bool,
+    /// Seconds of user inactivity after which notification timers pause,
+    /// so messages received while away are still up on return. Requires
+    /// the X11 screensaver extension; 0 disables the check (default).
+    #[serde(default)]
+    pub idle_threshold: u64,
+    /// Whether low-urgency notifications collapse into a single one-line
+    /// strip ("3 low priority messages") that expands on click.
+    #[serde(default)]
+    pub collapse_low: bool,
+    /// Groups displayed notifications by application: apps with several
+    /// unread entries collapse into one line ("slack (4)") showing the
+    /// newest body, expandable with a click.
+    #[serde(default)]
+    pub group_by_app: bool,
+    /// Stacks incoming duplicates of an already-displayed notification
+    /// onto the existing entry with a "×N" counter instead of adding a
+    /// second entry (defaults to true).
+    #[serde(default = "default_stack_duplicates")]
+    pub stack_duplicates: bool,
+    /// Whether critical notifications require explicit acknowledgment:
+    /// they never auto-clear and stay pinned at the top of the stack
+    /// until clicked away.
+    #[serde(default)]
+    pub critical_require_ack: bool,
     /// Minimum window width in pixels. If not set, window sizes to content.
     #[serde(default)]
     pub min_width: Option<u32>,
@@ -268,12 +1176,103 @@ pub struct GlobalConfig {
     /// Set to 0 to disable periodic refresh. Default is 1000 (1 second).
     #[serde(default = "default_refresh_interval")]
     pub refresh_interval_ms: u64,
+    /// How entry ages are displayed (`compact`, `human` or `clock`).
+    /// Rules can override this per match.
+    #[serde(default)]
+    pub age_format: AgeFormat,
+    /// Whether repeated identical critical notifications are downgraded to
+    /// normal urgency with a "(repeated)" marker, to counter alarm fatigue.
+    /// Rules can override this per match via `downgrade_repeats`.
+    #[serde(default)]
+    pub downgrade_repeats: bool,
+    /// Number of identical critical notifications within `repeat_window`
+    /// after which subsequent ones are downgraded. Default is 3.
+    #[serde(default = "default_repeat_threshold")]
+    pub repeat_threshold: usize,
+    /// Time window in seconds for counting identical critical notifications.
+    /// Default is 60.
+    #[serde(default = "default_repeat_window")]
+    pub repeat_window: u64,
+}
+
+impl GlobalConfig {
+    /// Whether the configured quiet hours cover the current local time.
+    pub fn quiet_hours_active(&self) -> bool {
+        let now = chrono::Local::now();
+        self.quiet_hours
+            .iter()
+            .any(|spec| quiet_hours_match(spec, &now))
+    }
+}
+
+/// Checks one quiet-hours entry against a point in time.
+///
+/// Invalid entries never match; they are warned about instead of failing
+/// the whole configuration, since quiet hours are best-effort.
+fn quiet_hours_match(spec: &str, now: &chrono::DateTime<chrono::Local>) -> bool {
+    use chrono::{Datelike, Timelike, Weekday};
+    let spec = spec.trim();
+    if let Some((start, end)) = spec.split_once('-') {
+        let parse = |s: &str| -> Option<u32> {
+            let (hour, minute) = s.trim().split_once(':')?;
+            let hour: u32 = hour.parse().ok()?;
+            let minute: u32 = minute.parse().ok()?;
+            (hour < 24 && minute < 60).then_some(hour * 60 + minute)
+        };
+        let (Some(start), Some(end)) = (parse(start), parse(end)) else {
+            log::warn!("invalid quiet hours range: {}", spec);
+            return false;
+        };
+        let minute = now.hour() * 60 + now.minute();
+        if start <= end {
+            (start..end).contains(&minute)
+        } else {
+            // The range wraps past midnight
+            minute >= start || minute < end
+        }
+    } else {
+        let weekday = now.weekday();
+        let full = match weekday {
+            Weekday::Mon => "Monday",
+            Weekday::Tue => "Tuesday",
+            Weekday::Wed => "Wednesday",
+            Weekday::Thu => "Thursday",
+            Weekday::Fri => "Friday",
+            Weekday::Sat => "Saturday",
+            Weekday::Sun => "Sunday",
+        };
+        spec.eq_ignore_ascii_case(&weekday.to_string()) || spec.eq_ignore_ascii_case(full)
+    }
 }
 
 fn default_refresh_interval() -> u64 {
     1000
 }
 
+fn default_buffer_limit() -> usize {
+    1000
+}
+
+fn default_max_body_length() -> usize {
+    8192
+}
+
+fn default_collapse_empty_body() -> bool {
+    true
+}
+
+fn default_stack_duplicates() -> bool {
+    true
+}
+
+fn default_repeat_threshold() -> usize {
+    3
+}
+
+fn default_repeat_window() -> u64 {
+    60
+}
+
 /// Custom deserializer implementation for converting `String` to [`LevelFilter`]
 fn deserialize_level_from_string<'de, D>(deserializer: D) -> StdResult<LevelFilter, D::Error>
 where
@@ -340,6 +1339,26 @@ pub struct UrgencyConfig {
     pub auto_clear: Option<bool>,
     /// Text.
     pub text: Option<String>,
+    /// Template override for entries of this urgency, so criticals can
+    /// use a more detailed layout than chatter. Unset entries use the
+    /// built-in layout.
+    #[serde(default)]
+    pub template: Option<String>,
+    /// Default sound for this urgency: a theme sound name or a file path,
+    /// played unless the sender provides its own sound hints.
+    #[serde(default)]
+    pub sound: Option<String>,
+    /// Sound volume override for this urgency (0.0 to 1.0).
+    #[serde(default)]
+    pub sound_volume: Option<f32>,
+    /// Color of the countdown indicator bar (hex string); falls back to
+    /// the foreground color when unset.
+    #[serde(default)]
+    pub countdown_color: Option<String>,
+    /// What to do with the popup while the focused window is fullscreen
+    /// (`show`, `delay`, or `suppress`; defaults to `show`).
+    #[serde(default)]
+    pub fullscreen: Option<FullscreenMode>,
     /// Custom OS commands to run.
     pub custom_commands: Option<Vec<CustomCommand>>,
 }
@@ -378,17 +1397,18 @@ impl UrgencyConfig {
                     continue;
                 }
                 log::trace!("running command: {:#?}", command);
+                // No HTML autoescaping here; the context is shell-escaped instead
                 let command = Tera::one_off(
                     &command.command,
-                    &notification.into_context(
+                    &notification.into_command_context(
                         self.text
                             .clone()
                             .unwrap_or_else(|| notification.urgency.to_string()),
                         0,
                     )?,
-                    true,
+                    false,
                 )?;
-                Command::new("sh").args(["-c", &command]).spawn()?;
+                crate::executor::CommandExecutor::global().submit(command);
             }
         }
         Ok(())
@@ -418,3 +1438,109 @@ where
         None => Ok(None),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    /// Builds a rule matching the given summary pattern.
+    fn summary_rule(pattern: &str) -> NotificationRule {
+        let mut rule: NotificationRule =
+            toml::from_str(&format!("summary = \"{pattern}\"")).expect("valid rule");
+        rule.compile().expect("rule compiles");
+        rule
+    }
+
+    #[test]
+    fn test_quiet_hours_match() {
+        use chrono::TimeZone;
+        let saturday_night = chrono::Local
+            .with_ymd_and_hms(2025, 1, 4, 23, 30, 0)
+            .unwrap();
+        assert!(quiet_hours_match("22:00-08:00", &saturday_night));
+        assert!(quiet_hours_match("Sat", &saturday_night));
+        assert!(quiet_hours_match("saturday", &saturday_night));
+        assert!(!quiet_hours_match("Mon", &saturday_night));
+        assert!(!quiet_hours_match("09:00-17:00", &saturday_night));
+        // Invalid entries never match
+        assert!(!quiet_hours_match("25:00-99:00", &saturday_night));
+        // A wrapping range still covers the morning side
+        let monday_morning = chrono::Local.with_ymd_and_hms(2025, 1, 6, 7, 0, 0).unwrap();
+        assert!(quiet_hours_match("22:00-08:00", &monday_morning));
+    }
+
+    #[test]
+    fn test_glob_match_anchoring() {
+        // Literal segments around the wildcards are anchored
+        assert!(glob_match("a*b*c", "abc"));
+        assert!(glob_match("a*b*c", "axxbyyc"));
+        assert!(!glob_match("a*b*c", "xaybzcw"));
+        // Segments must not overlap
+        assert!(!glob_match("a*a", "a"));
+        assert!(glob_match("a*a", "aa"));
+        // Consecutive wildcards behave like a single one
+        assert!(glob_match("a**c", "abc"));
+        assert!(glob_match("a**c", "ac"));
+        // An empty pattern only matches the empty value
+        assert!(glob_match("", ""));
+        assert!(!glob_match("", "a"));
+    }
+
+    #[test]
+    fn test_rule_precedence_first_match_wins() {
+        let config = Config {
+            rules: vec![summary_rule("Build*"), summary_rule("*")],
+            ..Config::parse().expect("embedded config parses")
+        };
+        let rule = config
+            .get_matching_rule("ci", "Build failed", "")
+            .expect("a rule matches");
+        assert_eq!(rule.summary.as_deref(), Some("Build*"));
+        let rule = config
+            .get_matching_rule("ci", "deploy done", "")
+            .expect("a rule matches");
+        assert_eq!(rule.summary.as_deref(), Some("*"));
+    }
+
+    proptest! {
+        #[test]
+        fn prop_star_matches_everything(value in ".*") {
+            prop_assert!(glob_match("*", &value));
+        }
+
+        #[test]
+        fn prop_value_matches_itself(value in "[^*]*") {
+            prop_assert!(glob_match(&value, &value));
+        }
+
+        #[test]
+        fn prop_case_insensitive(value in "[a-zA-Z0-9 ]*") {
+            prop_assert!(glob_match(&value.to_uppercase(), &value.to_lowercase()));
+        }
+
+        #[test]
+        fn prop_splice_matches(prefix in "[^*]{0,8}", middle in "[^*]{0,8}", suffix in "[^*]{0,8}") {
+            // A pattern built from the value with wildcards spliced in
+            // always matches the value it was built from
+            let value = format!("{prefix}{middle}{suffix}");
+            let pattern = format!("{prefix}*{suffix}");
+            prop_assert!(glob_match(&pattern, &value));
+        }
+
+        #[test]
+        fn prop_anchored_segments(
+            first in "[a-m]{1,4}",
+            gap in "[a-m]{0,4}",
+            last in "[a-m]{1,4}",
+        ) {
+            let pattern = format!("{first}*{last}");
+            let value = format!("{first}{gap}{last}");
+            prop_assert!(glob_match(&pattern, &value));
+            // Padding outside the anchored segments breaks the match;
+            // `z` never occurs in the segment alphabet
+            prop_assert!(!glob_match(&pattern, &format!("z{value}")));
+            prop_assert!(!glob_match(&pattern, &format!("{value}z")));
+        }
+    }
+}
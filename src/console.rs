@@ -0,0 +1,125 @@
+//! Console sink for notifications.
+//!
+//! When enabled, every incoming notification is rendered through a dedicated
+//! Tera template (with ANSI color helpers) and printed to stdout as a single
+//! line, so tmux/status-pipe consumers get exactly the format they want.
+
+use crate::error::{Error, Result};
+use crate::notification::Notification;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::error::Error as StdError;
+use tera::{Result as TeraResult, Tera, Value};
+
+/// Name of the template for rendering console output.
+const CONSOLE_TEMPLATE: &str = "console_template";
+
+/// Console sink configuration.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ConsoleConfig {
+    /// Whether the console sink is enabled.
+    #[serde(default)]
+    pub enabled: bool,
+    /// Template for each printed line.
+    #[serde(default = "default_template")]
+    pub template: String,
+}
+
+impl Default for ConsoleConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            template: default_template(),
+        }
+    }
+}
+
+/// Default console line template.
+fn default_template() -> String {
+    String::from(
+        "{{ urgency | ansi(color=\"yellow\") }} \
+         {{ app_name | ansi(color=\"cyan\") }} \
+         {{ summary | ansi(style=\"bold\") }}\
+         {% if body %} {{ body }}{% endif %}",
+    )
+}
+
+/// Renders notifications to stdout.
+pub struct ConsoleSink {
+    /// Compiled console template.
+    template: Tera,
+}
+
+impl ConsoleSink {
+    /// Creates a console sink from the given configuration.
+    pub fn new(config: &ConsoleConfig) -> Result<Self> {
+        let mut template = Tera::default();
+        if let Err(e) = template.add_raw_template(CONSOLE_TEMPLATE, config.template.trim()) {
+            return if let Some(error_source) = e.source() {
+                Err(Error::TemplateParse(error_source.to_string()))
+            } else {
+                Err(Error::Template(e))
+            };
+        }
+        template.register_filter("ansi", ansi_filter);
+        crate::notification::register_template_extensions(&mut template);
+        Ok(Self { template })
+    }
+
+    /// Renders and prints a single notification line.
+    pub fn print(&self, notification: &Notification) {
+        let rendered = notification
+            .into_context(notification.urgency.to_string(), 0)
+            .and_then(|context| {
+                self.template
+                    .render(CONSOLE_TEMPLATE, &context)
+                    .map_err(Error::Template)
+            });
+        match rendered {
+            Ok(line) => println!("{}", line),
+            Err(e) => log::warn!("failed to render console template: {}", e),
+        }
+    }
+}
+
+/// Tera filter wrapping a value in ANSI escape codes.
+///
+/// Supports `color` (standard terminal color names) and `style`
+/// (`bold`, `dim`, `italic`, `underline`) arguments.
+fn ansi_filter(value: &Value, args: &HashMap<String, Value>) -> TeraResult<Value> {
+    let text = value
+        .as_str()
+        .map(str::to_string)
+        .unwrap_or_else(|| value.to_string());
+    let mut codes = Vec::new();
+    if let Some(style) = args.get("style").and_then(Value::as_str) {
+        codes.push(match style {
+            "bold" => "1",
+            "dim" => "2",
+            "italic" => "3",
+            "underline" => "4",
+            _ => "0",
+        });
+    }
+    if let Some(color) = args.get("color").and_then(Value::as_str) {
+        codes.push(match color {
+            "black" => "30",
+            "red" => "31",
+            "green" => "32",
+            "yellow" => "33",
+            "blue" => "34",
+            "magenta" => "35",
+            "cyan" => "36",
+            "white" => "37",
+            _ => "39",
+        });
+    }
+    if codes.is_empty() {
+        return Ok(tera::to_value(text)?);
+    }
+    Ok(tera::to_value(format!(
+        "\x1b[{}m{}\x1b[0m",
+        codes.join(";"),
+        text
+    ))?)
+}
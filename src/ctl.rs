@@ -0,0 +1,558 @@
+//! Client-side helpers for controlling a running daemon over D-Bus.
+
+use crate::error::{Error, Result};
+use crate::history::HistoryEntry;
+use crate::notification::{Mute, Notification};
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::thread;
+use std::time::Duration;
+use zbus::blocking::Connection;
+use zbus::zvariant;
+
+/// Well-known bus name of the notification daemon.
+const BUS_NAME: &str = "org.freedesktop.Notifications";
+
+/// Object path of the notification interface.
+const NOTIFICATIONS_PATH: &str = "/org/freedesktop/Notifications";
+
+/// Name of the notification interface.
+const NOTIFICATIONS_INTERFACE: &str = "org.freedesktop.Notifications";
+
+/// Object path of the control interface.
+const CONTROL_PATH: &str = "/org/freedesktop/Notifications/ctl";
+
+/// Name of the control interface.
+const CONTROL_INTERFACE: &str = "org.freedesktop.NotificationControl";
+
+/// Connects to the session bus.
+fn connect() -> Result<Connection> {
+    Ok(Connection::session()?)
+}
+
+/// Sends a notification through the daemon's `Notify` method.
+pub fn send_notification(
+    connection: &Connection,
+    app_name: &str,
+    summary: &str,
+    body: &str,
+    urgency: u8,
+) -> Result<u32> {
+    let mut hints: HashMap<&str, zvariant::Value> = HashMap::new();
+    hints.insert("urgency", zvariant::Value::from(urgency));
+    let reply = connection.call_method(
+        Some(BUS_NAME),
+        NOTIFICATIONS_PATH,
+        Some(NOTIFICATIONS_INTERFACE),
+        "Notify",
+        &(
+            app_name,
+            0u32,
+            "",
+            summary,
+            body,
+            Vec::<String>::new(),
+            hints,
+            -1i32,
+        ),
+    )?;
+    Ok(reply.body().deserialize()?)
+}
+
+/// Parses a playback speed multiplier like "10x" or "2.5".
+pub fn parse_speed(s: &str) -> Result<f64> {
+    let speed = s
+        .trim()
+        .trim_end_matches(['x', 'X'])
+        .parse::<f64>()
+        .map_err(|e| Error::Config(format!("invalid speed `{s}`: {e}")))?;
+    if speed > 0.0 {
+        Ok(speed)
+    } else {
+        Err(Error::Config(format!("speed must be positive: `{s}`")))
+    }
+}
+
+/// Re-injects historical notifications through the daemon's full
+/// rule/render pipeline, scaling the original inter-arrival times.
+pub fn replay(entries: &[HistoryEntry], speed: f64) -> Result<()> {
+    let connection = connect()?;
+    println!(
+        "Replaying {} notification{} at {}x",
+        entries.len(),
+        if entries.len() == 1 { "" } else { "s" },
+        speed
+    );
+    let mut previous_timestamp: Option<u64> = None;
+    for entry in entries {
+        if let Some(previous) = previous_timestamp {
+            // Cap pathological gaps so replay always makes progress
+            let gap = (entry.timestamp.saturating_sub(previous) as f64 / speed).min(30.0);
+            if gap > 0.0 {
+                thread::sleep(Duration::from_secs_f64(gap));
+            }
+        }
+        previous_timestamp = Some(entry.timestamp);
+        let urgency = match entry.urgency.as_str() {
+            "low" => 0u8,
+            "critical" => 2,
+            _ => 1,
+        };
+        send_notification(
+            &connection,
+            &entry.app_name,
+            &entry.summary,
+            &entry.body,
+            urgency,
+        )?;
+    }
+    Ok(())
+}
+
+/// Streams new history entries to stdout as the daemon records them.
+///
+/// Subscribes to the control interface's `HistoryAdded` signal and prints
+/// one line per entry — raw JSON when `json` is set — until interrupted.
+pub fn follow(json: bool) -> Result<()> {
+    let connection = connect()?;
+    let proxy = zbus::blocking::Proxy::new(&connection, BUS_NAME, CONTROL_PATH, CONTROL_INTERFACE)?;
+    let signals = proxy.receive_signal("HistoryAdded")?;
+    for message in signals {
+        let payload: String = message.body().deserialize()?;
+        if json {
+            println!("{payload}");
+        } else {
+            let entry: HistoryEntry = serde_json::from_str(&payload)?;
+            let mut line = format!(
+                "[{}] {} ({}): {}",
+                entry.datetime, entry.app_name, entry.urgency, entry.summary
+            );
+            if !entry.body.is_empty() {
+                line.push_str(" — ");
+                line.push_str(&entry.body.replace('\n', " "));
+            }
+            println!("{line}");
+        }
+    }
+    Ok(())
+}
+
+/// Temporarily mutes applications matching the glob pattern.
+pub fn mute(pattern: &str, duration: Option<&str>) -> Result<()> {
+    // Validate the duration locally for a friendlier error message
+    if let Some(duration) = duration {
+        humantime::parse_duration(duration)
+            .map_err(|e| Error::Config(format!("invalid duration `{duration}`: {e}")))?;
+    }
+    let connection = connect()?;
+    connection.call_method(
+        Some(BUS_NAME),
+        CONTROL_PATH,
+        Some(CONTROL_INTERFACE),
+        "Mute",
+        &(pattern, duration.unwrap_or_default()),
+    )?;
+    match duration {
+        Some(duration) => println!("Muted `{pattern}` for {duration}."),
+        None => println!("Muted `{pattern}` until unmuted."),
+    }
+    Ok(())
+}
+
+/// Removes a mute from the running daemon.
+pub fn unmute(pattern: &str) -> Result<()> {
+    let connection = connect()?;
+    let reply = connection.call_method(
+        Some(BUS_NAME),
+        CONTROL_PATH,
+        Some(CONTROL_INTERFACE),
+        "Unmute",
+        &(pattern,),
+    )?;
+    let existed: bool = reply.body().deserialize()?;
+    if existed {
+        println!("Unmuted `{pattern}`.");
+    } else {
+        println!("No mute found for `{pattern}`.");
+    }
+    Ok(())
+}
+
+/// Lists the active mutes of the running daemon.
+pub fn mutes() -> Result<()> {
+    let connection = connect()?;
+    let reply = connection.call_method(
+        Some(BUS_NAME),
+        CONTROL_PATH,
+        Some(CONTROL_INTERFACE),
+        "Mutes",
+        &(),
+    )?;
+    let json: String = reply.body().deserialize()?;
+    let mutes: Vec<Mute> = serde_json::from_str(&json)?;
+    if mutes.is_empty() {
+        println!("No active mutes.");
+        return Ok(());
+    }
+    for mute in mutes {
+        match mute.expires_at {
+            Some(expires_at) => {
+                let expiry = DateTime::from_timestamp(expires_at as i64, 0)
+                    .unwrap_or_else(Utc::now)
+                    .format("%Y-%m-%d %H:%M:%S UTC");
+                println!("{} (until {})", mute.pattern, expiry);
+            }
+            None => println!("{} (until unmuted)", mute.pattern),
+        }
+    }
+    Ok(())
+}
+
+/// Invokes an action on a notification displayed by the running daemon.
+pub fn invoke(id: u32, action_key: &str) -> Result<()> {
+    let connection = connect()?;
+    connection.call_method(
+        Some(BUS_NAME),
+        CONTROL_PATH,
+        Some(CONTROL_INTERFACE),
+        "InvokeAction",
+        &(id, action_key),
+    )?;
+    Ok(())
+}
+
+/// Restores the most recently dismissed notification of the running daemon.
+pub fn pop() -> Result<()> {
+    let connection = connect()?;
+    connection.call_method(
+        Some(BUS_NAME),
+        CONTROL_PATH,
+        Some(CONTROL_INTERFACE),
+        "Pop",
+        &(),
+    )?;
+    Ok(())
+}
+
+/// Prints notification counts and DND state on a single line.
+///
+/// Queries the running daemon for displayed/unread counts and DND state,
+/// adds the history count from storage, and prints one status-bar
+/// friendly line. With `watch`, polls once a second and re-prints
+/// whenever the line changes; the history count is loaded once and
+/// tracked through `HistoryAdded` signals after that.
+pub fn count(json: bool, watch: bool) -> Result<()> {
+    let connection = connect()?;
+    // The store is opened exactly once; in watch mode the daemon's
+    // `HistoryAdded` signals keep the count current instead of a full
+    // reload from disk every second
+    let mut history_count = crate::history::History::new(crate::history::DEFAULT_HISTORY_LIMIT)
+        .map(|history| history.all().len())
+        .unwrap_or(0);
+    let (tx, rx) = std::sync::mpsc::channel::<()>();
+    if watch {
+        spawn_signal_pump(
+            connection.clone(),
+            CONTROL_PATH,
+            CONTROL_INTERFACE,
+            "HistoryAdded",
+            tx,
+        );
+    }
+    let mut last: Option<String> = None;
+    loop {
+        let reply = connection.call_method(
+            Some(BUS_NAME),
+            CONTROL_PATH,
+            Some(CONTROL_INTERFACE),
+            "Stats",
+            &(),
+        )?;
+        let stats: String = reply.body().deserialize()?;
+        let mut stats: serde_json::Value = serde_json::from_str(&stats)?;
+        stats["history"] = history_count.into();
+
+        let line = if json {
+            stats.to_string()
+        } else {
+            format!(
+                "displayed:{} unread:{} history:{} dnd:{}",
+                stats["displayed"],
+                stats["unread"],
+                stats["history"],
+                if stats["dnd"].as_bool().unwrap_or(false) {
+                    "on"
+                } else {
+                    "off"
+                }
+            )
+        };
+        if last.as_deref() != Some(&line) {
+            println!("{line}");
+            last = Some(line);
+        }
+        if !watch {
+            return Ok(());
+        }
+        thread::sleep(Duration::from_secs(1));
+        // Entries the daemon recorded while we slept
+        while rx.try_recv().is_ok() {
+            history_count += 1;
+        }
+    }
+}
+
+/// Formats an age in seconds compactly ("42s", "5m", "2h", "3d").
+fn format_age(age_secs: u64) -> String {
+    match age_secs {
+        0..60 => format!("{}s", age_secs),
+        60..3600 => format!("{}m", age_secs / 60),
+        3600..86400 => format!("{}h", age_secs / 3600),
+        _ => format!("{}d", age_secs / 86400),
+    }
+}
+
+/// Lists the currently displayed/unread notifications.
+///
+/// Shows IDs, ages and available action keys — everything needed to
+/// target `ctl` commands and action invocations at a specific entry.
+pub fn list(json: bool) -> Result<()> {
+    let connection = connect()?;
+    let reply = connection.call_method(
+        Some(BUS_NAME),
+        CONTROL_PATH,
+        Some(CONTROL_INTERFACE),
+        "ExportUnread",
+        &(),
+    )?;
+    let data: String = reply.body().deserialize()?;
+    let notifications: Vec<Notification> = crate::schema::from_json(&data)?;
+    if json {
+        println!("{data}");
+        return Ok(());
+    }
+    if notifications.is_empty() {
+        println!("No notifications displayed.");
+        return Ok(());
+    }
+
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    println!("{:<6} {:<6} {:<16} {:<9} SUMMARY (ACTIONS)", "ID", "AGE", "APP", "URGENCY");
+    for notification in &notifications {
+        // Action keys sit at the even indices of the [key, label, ...] pairs
+        let actions: Vec<&str> = notification
+            .actions
+            .iter()
+            .step_by(2)
+            .map(String::as_str)
+            .collect();
+        let actions = if actions.is_empty() {
+            String::new()
+        } else {
+            format!(" ({})", actions.join(", "))
+        };
+        println!(
+            "{:<6} {:<6} {:<16} {:<9} {}{}",
+            notification.id,
+            format_age(now.saturating_sub(notification.timestamp)),
+            notification.app_name,
+            notification.urgency.to_string(),
+            notification.summary,
+            actions
+        );
+    }
+    Ok(())
+}
+
+/// Forwards every occurrence of a D-Bus signal as a wake-up on the channel.
+fn spawn_signal_pump(
+    connection: Connection,
+    path: &'static str,
+    interface: &'static str,
+    signal: &'static str,
+    tx: std::sync::mpsc::Sender<()>,
+) {
+    thread::spawn(move || {
+        let Ok(proxy) = zbus::blocking::Proxy::new(&connection, BUS_NAME, path, interface) else {
+            return;
+        };
+        let Ok(signals) = proxy.receive_signal(signal) else {
+            return;
+        };
+        for _ in signals {
+            if tx.send(()).is_err() {
+                break;
+            }
+        }
+    });
+}
+
+/// Emits waybar-compatible status JSON for a custom status bar module.
+///
+/// Prints `{"text", "class", "tooltip"}` on one line. With `watch`, the
+/// daemon's `HistoryAdded` and `NotificationClosed` signals trigger a
+/// re-emit whenever the unread count changes, with a slow poll as
+/// fallback for changes that emit no signal (e.g. a DND toggle).
+pub fn statusbar(watch: bool) -> Result<()> {
+    use std::io::Write;
+
+    let connection = connect()?;
+    let (tx, rx) = std::sync::mpsc::channel::<()>();
+    if watch {
+        spawn_signal_pump(
+            connection.clone(),
+            CONTROL_PATH,
+            CONTROL_INTERFACE,
+            "HistoryAdded",
+            tx.clone(),
+        );
+        spawn_signal_pump(
+            connection.clone(),
+            NOTIFICATIONS_PATH,
+            NOTIFICATIONS_INTERFACE,
+            "NotificationClosed",
+            tx,
+        );
+    }
+
+    let mut last: Option<String> = None;
+    loop {
+        let reply = connection.call_method(
+            Some(BUS_NAME),
+            CONTROL_PATH,
+            Some(CONTROL_INTERFACE),
+            "Stats",
+            &(),
+        )?;
+        let stats: String = reply.body().deserialize()?;
+        let stats: serde_json::Value = serde_json::from_str(&stats)?;
+        let unread = stats["unread"].as_u64().unwrap_or(0);
+        let dnd = stats["dnd"].as_bool().unwrap_or(false);
+
+        let text = if unread > 0 {
+            unread.to_string()
+        } else {
+            String::new()
+        };
+        let class = if dnd {
+            "dnd"
+        } else if unread > 0 {
+            "unread"
+        } else {
+            "empty"
+        };
+        let tooltip = format!(
+            "{} unread notification{}{}",
+            unread,
+            if unread == 1 { "" } else { "s" },
+            if dnd { " — do not disturb" } else { "" }
+        );
+        let line =
+            serde_json::json!({ "text": text, "class": class, "tooltip": tooltip }).to_string();
+        if last.as_deref() != Some(&line) {
+            println!("{line}");
+            // Status bars read a pipe, which is not line-buffered
+            std::io::stdout().flush()?;
+            last = Some(line);
+        }
+        if !watch {
+            return Ok(());
+        }
+        let _ = rx.recv_timeout(Duration::from_secs(5));
+    }
+}
+
+/// Prints the daemon's effective configuration as JSON.
+pub fn get_config() -> Result<()> {
+    let connection = connect()?;
+    let reply = connection.call_method(
+        Some(BUS_NAME),
+        CONTROL_PATH,
+        Some(CONTROL_INTERFACE),
+        "GetConfig",
+        &(),
+    )?;
+    let json: String = reply.body().deserialize()?;
+    println!("{json}");
+    Ok(())
+}
+
+/// Changes a runtime-tunable option of the running daemon.
+pub fn set_option(key: &str, value: &str) -> Result<()> {
+    let connection = connect()?;
+    connection.call_method(
+        Some(BUS_NAME),
+        CONTROL_PATH,
+        Some(CONTROL_INTERFACE),
+        "SetOption",
+        &(key, value),
+    )?;
+    println!("Set {key} = {value}.");
+    Ok(())
+}
+
+/// Prints the popup's current position, size and per-entry bounds as JSON.
+pub fn layout() -> Result<()> {
+    let connection = connect()?;
+    let reply = connection.call_method(
+        Some(BUS_NAME),
+        CONTROL_PATH,
+        Some(CONTROL_INTERFACE),
+        "Layout",
+        &(),
+    )?;
+    let json: String = reply.body().deserialize()?;
+    println!("{json}");
+    Ok(())
+}
+
+/// Exports the unread notification buffer of the running daemon to a file.
+pub fn export_unread(file: &Path) -> Result<()> {
+    let connection = connect()?;
+    let reply = connection.call_method(
+        Some(BUS_NAME),
+        CONTROL_PATH,
+        Some(CONTROL_INTERFACE),
+        "ExportUnread",
+        &(),
+    )?;
+    let json: String = reply.body().deserialize()?;
+    fs::write(file, &json)?;
+    let count = crate::schema::from_json::<Vec<Notification>>(&json)
+        .map(|v| v.len())
+        .unwrap_or(0);
+    println!(
+        "Exported {} unread notification{} to {}",
+        count,
+        if count == 1 { "" } else { "s" },
+        file.display()
+    );
+    Ok(())
+}
+
+/// Imports unread notifications from a file into the running daemon.
+pub fn import_unread(file: &Path) -> Result<()> {
+    let json = fs::read_to_string(file)?;
+    // Validate the snapshot locally before handing it to the daemon
+    let notifications: Vec<Notification> = crate::schema::from_json(&json)?;
+    let connection = connect()?;
+    connection.call_method(
+        Some(BUS_NAME),
+        CONTROL_PATH,
+        Some(CONTROL_INTERFACE),
+        "ImportUnread",
+        &(json.as_str(),),
+    )?;
+    println!(
+        "Imported {} notification{} from {}",
+        notifications.len(),
+        if notifications.len() == 1 { "" } else { "s" },
+        file.display()
+    );
+    Ok(())
+}
@@ -46,6 +46,12 @@ pub enum Error {
     Init(String),
     #[error("JSON error: `{0}`")]
     Json(#[from] serde_json::Error),
+    #[error("File watcher error: `{0}`")]
+    Notify(#[from] notify::Error),
+    #[error("SQLite error: `{0}`")]
+    Sqlite(#[from] rusqlite::Error),
+    #[error("Binary encoding error: `{0}`")]
+    Bincode(#[from] bincode::Error),
 }
 
 /// Type alias for the standard [`Result`] type.
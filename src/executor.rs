@@ -0,0 +1,257 @@
+//! Bounded executor for custom notification commands.
+//!
+//! Custom commands used to spawn unbounded `sh -c` children, so a
+//! notification storm could fork-bomb the session. Commands now go through
+//! a fixed pool of worker threads fed by a bounded queue, with a
+//! per-command timeout, zombie reaping, and exit status logging. Repeated
+//! failures of the same command are surfaced as a self-notification so
+//! broken hook scripts aren't silent.
+
+use crate::notification::{Action, Notification, Urgency};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::Read;
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, Receiver, Sender, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Poll interval while waiting for a running command.
+const WAIT_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Number of consecutive failures after which a self-notification is sent.
+const FAILURE_NOTIFY_THRESHOLD: u32 = 3;
+
+/// Maximum number of stderr bytes included in the failure log.
+const STDERR_LOG_LIMIT: usize = 500;
+
+/// Configuration for the command executor.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct CommandsConfig {
+    /// Maximum number of commands running at once.
+    pub max_concurrent: usize,
+    /// Maximum number of queued commands; further commands are dropped.
+    pub queue_size: usize,
+    /// Seconds a command may run before it is killed (0 disables).
+    pub timeout: u64,
+}
+
+impl Default for CommandsConfig {
+    fn default() -> Self {
+        Self {
+            max_concurrent: 4,
+            queue_size: 64,
+            timeout: 30,
+        }
+    }
+}
+
+/// Global command executor.
+static EXECUTOR: OnceLock<CommandExecutor> = OnceLock::new();
+
+/// State shared between the executor handle and its worker threads.
+struct ExecutorState {
+    /// Seconds a command may run before it is killed (0 disables).
+    timeout: u64,
+    /// Consecutive failure count per command.
+    failures: Mutex<HashMap<String, u32>>,
+    /// Channel used to send self-notifications about failing commands.
+    notifier: Mutex<Option<Sender<Action>>>,
+}
+
+impl ExecutorState {
+    /// Records a failed run and notifies once the failure threshold is hit.
+    fn record_failure(&self, command: &str) {
+        let mut failures = self.failures.lock().expect("failed to lock failure map");
+        let count = failures.entry(command.to_string()).or_insert(0);
+        *count += 1;
+        if *count != FAILURE_NOTIFY_THRESHOLD {
+            return;
+        }
+        log::warn!(
+            "command `{}` failed {} times in a row",
+            command,
+            FAILURE_NOTIFY_THRESHOLD
+        );
+        if let Some(sender) = &*self.notifier.lock().expect("failed to lock notifier") {
+            let notification = Notification {
+                id: 0,
+                app_name: env!("CARGO_PKG_NAME").to_string(),
+                summary: "custom command keeps failing".to_string(),
+                body: format!(
+                    "`{}` failed {} times in a row; check the logs",
+                    command, FAILURE_NOTIFY_THRESHOLD
+                ),
+                expire_timeout: None,
+                urgency: Urgency::Normal,
+                is_read: false,
+                timestamp: SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|d| d.as_secs())
+                    .unwrap_or(0),
+                actions: Vec::new(),
+                deadline: None,
+                repeats: 0,
+                sound: None,
+                suppress_sound: false,
+                icon: String::new(),
+                category: None,
+                value: None,
+                hints: HashMap::new(),
+                image: None,
+            };
+            let _ = sender.send(Action::Show(notification));
+        }
+    }
+
+    /// Resets the failure counter after a successful run.
+    fn record_success(&self, command: &str) {
+        self.failures
+            .lock()
+            .expect("failed to lock failure map")
+            .remove(command);
+    }
+}
+
+/// A bounded pool of worker threads running `sh -c` commands.
+pub struct CommandExecutor {
+    /// Bounded queue feeding the worker threads.
+    queue: SyncSender<String>,
+    /// State shared with the workers.
+    state: Arc<ExecutorState>,
+}
+
+impl CommandExecutor {
+    /// Initializes the global executor with the given configuration.
+    ///
+    /// Only the first call takes effect; the executor keeps its pool size
+    /// across configuration reloads.
+    pub fn init(config: &CommandsConfig) {
+        let _ = EXECUTOR.set(Self::new(config));
+    }
+
+    /// Returns the global executor, initializing it with defaults if needed.
+    pub fn global() -> &'static CommandExecutor {
+        EXECUTOR.get_or_init(|| Self::new(&CommandsConfig::default()))
+    }
+
+    /// Sets the channel used for self-notifications about failing commands.
+    pub fn set_notifier(&self, sender: Sender<Action>) {
+        *self.state.notifier.lock().expect("failed to lock notifier") = Some(sender);
+    }
+
+    /// Creates a new executor and spawns its worker threads.
+    fn new(config: &CommandsConfig) -> Self {
+        let (queue, receiver) = mpsc::sync_channel::<String>(config.queue_size.max(1));
+        let receiver = Arc::new(Mutex::new(receiver));
+        let state = Arc::new(ExecutorState {
+            timeout: config.timeout,
+            failures: Mutex::new(HashMap::new()),
+            notifier: Mutex::new(None),
+        });
+        for worker in 0..config.max_concurrent.max(1) {
+            let receiver: Arc<Mutex<Receiver<String>>> = Arc::clone(&receiver);
+            let state = Arc::clone(&state);
+            thread::Builder::new()
+                .name(format!("runst-cmd-{worker}"))
+                .spawn(move || {
+                    loop {
+                        let command = receiver
+                            .lock()
+                            .expect("failed to lock command queue")
+                            .recv();
+                        match command {
+                            Ok(command) => Self::run(&command, &state),
+                            Err(_) => break,
+                        }
+                    }
+                })
+                .expect("failed to spawn command worker");
+        }
+        Self { queue, state }
+    }
+
+    /// Submits a command for execution, dropping it if the queue is full.
+    pub fn submit(&self, command: String) {
+        match self.queue.try_send(command) {
+            Ok(()) => {}
+            Err(TrySendError::Full(command)) => {
+                log::warn!("command queue full, dropping command: {}", command);
+            }
+            Err(TrySendError::Disconnected(_)) => {
+                log::warn!("command executor is not running");
+            }
+        }
+    }
+
+    /// Runs a single command, enforcing the timeout and logging the result.
+    fn run(command: &str, state: &ExecutorState) {
+        log::trace!("running command: {}", command);
+        let mut child = match Command::new("sh")
+            .args(["-c", command])
+            .stderr(Stdio::piped())
+            .spawn()
+        {
+            Ok(child) => child,
+            Err(e) => {
+                log::warn!("failed to spawn command `{}`: {}", command, e);
+                state.record_failure(command);
+                return;
+            }
+        };
+        let timeout = state.timeout;
+        let deadline = (timeout > 0).then(|| Instant::now() + Duration::from_secs(timeout));
+        loop {
+            match child.try_wait() {
+                Ok(Some(status)) => {
+                    if status.success() {
+                        log::trace!("command `{}` exited successfully", command);
+                        state.record_success(command);
+                    } else {
+                        let stderr_output = Self::read_stderr(&mut child);
+                        log::warn!(
+                            "command `{}` exited with {}{}",
+                            command,
+                            status,
+                            stderr_output
+                        );
+                        state.record_failure(command);
+                    }
+                    return;
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    log::warn!("failed to wait for command `{}`: {}", command, e);
+                    return;
+                }
+            }
+            if let Some(deadline) = deadline
+                && Instant::now() >= deadline
+            {
+                log::warn!("command `{}` timed out after {}s, killing", command, timeout);
+                let _ = child.kill();
+                // Reap the killed child to avoid leaving a zombie behind
+                let _ = child.wait();
+                state.record_failure(command);
+                return;
+            }
+            thread::sleep(WAIT_POLL_INTERVAL);
+        }
+    }
+
+    /// Reads the captured stderr of a finished child, truncated for logging.
+    fn read_stderr(child: &mut std::process::Child) -> String {
+        let mut output = String::new();
+        if let Some(mut stderr) = child.stderr.take() {
+            let _ = stderr.read_to_string(&mut output);
+        }
+        let output = output.trim();
+        if output.is_empty() {
+            return String::new();
+        }
+        let snippet: String = output.chars().take(STDERR_LOG_LIMIT).collect();
+        format!(": {}", snippet)
+    }
+}
@@ -0,0 +1,195 @@
+//! C interface for embedding the daemon in-process.
+//!
+//! Non-Rust compositors and bars link against this to post notifications
+//! through runst's rendering pipeline without a D-Bus round trip. The
+//! surface is deliberately small: start the daemon, post and dismiss
+//! notifications, and observe lifecycle events through one callback.
+//!
+//! All functions may be called from any thread once [`runst_start`] has
+//! returned a handle. String arguments are NUL-terminated; invalid UTF-8
+//! bytes are replaced rather than rejected.
+
+use crate::Daemon;
+use crate::notification::{Action, Manager, Notification, NotificationEvent, Urgency};
+use std::ffi::CStr;
+use std::os::raw::{c_char, c_void};
+use std::sync::mpsc::Sender;
+use std::thread;
+use std::time::Duration;
+
+/// Callback event: a notification entered the buffer.
+pub const RUNST_EVENT_RECEIVED: u32 = 0;
+/// Callback event: a notification was shown on screen.
+pub const RUNST_EVENT_DISPLAYED: u32 = 1;
+/// Callback event: a notification was closed or dismissed.
+pub const RUNST_EVENT_CLOSED: u32 = 2;
+/// Callback event: an action was invoked on a notification.
+pub const RUNST_EVENT_ACTION_INVOKED: u32 = 3;
+
+/// Lifecycle callback: receives one of the `RUNST_EVENT_*` kinds, the
+/// notification ID and the pointer registered alongside it.
+pub type RunstCallback = extern "C" fn(event: u32, id: u32, user_data: *mut c_void);
+
+/// Opaque handle to an in-process daemon.
+pub struct RunstHandle {
+    /// Action channel into the daemon's event loop.
+    sender: Sender<Action>,
+    /// The daemon's notification buffer, for event subscriptions.
+    manager: Manager,
+}
+
+/// A registered user data pointer, made sendable for the callback thread.
+///
+/// The caller guarantees the pointer stays valid, and usable from another
+/// thread, for as long as the callback is registered.
+struct UserData(*mut c_void);
+
+unsafe impl Send for UserData {}
+
+/// Reads a C string argument; NULL reads as the empty string.
+unsafe fn string_arg(ptr: *const c_char) -> String {
+    if ptr.is_null() {
+        return String::new();
+    }
+    unsafe { CStr::from_ptr(ptr) }.to_string_lossy().into_owned()
+}
+
+/// Starts the daemon on a background thread and returns a handle to it.
+///
+/// The daemon reads the global configuration files exactly like the
+/// `runst` binary. Startup failures (no X11 display, no D-Bus session)
+/// surface asynchronously in the log, not as a NULL return. Release the
+/// handle with [`runst_free`].
+#[unsafe(no_mangle)]
+pub extern "C" fn runst_start() -> *mut RunstHandle {
+    let daemon = Daemon::builder().build();
+    let handle = RunstHandle {
+        sender: daemon.sender(),
+        manager: daemon.manager(),
+    };
+    thread::Builder::new()
+        .name("runst-daemon".to_string())
+        .spawn(move || {
+            if let Err(e) = daemon.run() {
+                log::error!("daemon exited: {}", e);
+            }
+        })
+        .expect("failed to spawn daemon");
+    Box::into_raw(Box::new(handle))
+}
+
+/// Posts a notification and returns its allocated ID (0 on failure).
+///
+/// `urgency` is 0 low, 1 normal, 2 critical (anything else reads as
+/// normal); a `timeout_ms` of 0 disables the auto-clear timeout.
+///
+/// # Safety
+///
+/// `handle` must be a live pointer from [`runst_start`]; the string
+/// pointers must each be NULL or point to a NUL-terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn runst_notify(
+    handle: *const RunstHandle,
+    app_name: *const c_char,
+    summary: *const c_char,
+    body: *const c_char,
+    urgency: u32,
+    timeout_ms: u32,
+) -> u32 {
+    let Some(handle) = (unsafe { handle.as_ref() }) else {
+        return 0;
+    };
+    let mut builder = Notification::builder()
+        .app_name(unsafe { string_arg(app_name) })
+        .summary(unsafe { string_arg(summary) })
+        .body(unsafe { string_arg(body) })
+        .urgency(Urgency::from(u64::from(urgency)));
+    if timeout_ms > 0 {
+        builder = builder.expire_timeout(Duration::from_millis(u64::from(timeout_ms)));
+    }
+    let notification = builder.build();
+    let id = notification.id;
+    if handle.sender.send(Action::Show(notification)).is_err() {
+        return 0;
+    }
+    id
+}
+
+/// Dismisses the notification with the given ID.
+///
+/// # Safety
+///
+/// `handle` must be a live pointer from [`runst_start`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn runst_dismiss(handle: *const RunstHandle, id: u32) {
+    if let Some(handle) = unsafe { handle.as_ref() } {
+        let _ = handle.sender.send(Action::Close(Some(id)));
+    }
+}
+
+/// Dismisses every displayed notification.
+///
+/// # Safety
+///
+/// `handle` must be a live pointer from [`runst_start`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn runst_dismiss_all(handle: *const RunstHandle) {
+    if let Some(handle) = unsafe { handle.as_ref() } {
+        let _ = handle.sender.send(Action::CloseAll);
+    }
+}
+
+/// Registers a lifecycle callback.
+///
+/// The callback is invoked from a dedicated thread for every buffer
+/// event. Each call adds an independent subscription for the lifetime of
+/// the daemon — there is no unregistration — so `user_data` must stay
+/// valid until the process exits.
+///
+/// # Safety
+///
+/// `handle` must be a live pointer from [`runst_start`], and `user_data`
+/// must remain valid (and safe to use from another thread) for the rest
+/// of the process lifetime.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn runst_set_callback(
+    handle: *const RunstHandle,
+    callback: RunstCallback,
+    user_data: *mut c_void,
+) {
+    let Some(handle) = (unsafe { handle.as_ref() }) else {
+        return;
+    };
+    let events = handle.manager.subscribe();
+    let data = UserData(user_data);
+    thread::Builder::new()
+        .name("runst-ffi-events".to_string())
+        .spawn(move || {
+            while let Ok(event) = events.recv() {
+                let (kind, id) = match event {
+                    NotificationEvent::Received(n) => (RUNST_EVENT_RECEIVED, n.id),
+                    NotificationEvent::Displayed(id) => (RUNST_EVENT_DISPLAYED, id),
+                    NotificationEvent::Closed(id) => (RUNST_EVENT_CLOSED, id),
+                    NotificationEvent::ActionInvoked(id, _) => (RUNST_EVENT_ACTION_INVOKED, id),
+                };
+                callback(kind, id, data.0);
+            }
+        })
+        .expect("failed to spawn callback thread");
+}
+
+/// Releases a daemon handle.
+///
+/// The daemon itself keeps running for the rest of the process lifetime;
+/// only the handle is freed.
+///
+/// # Safety
+///
+/// `handle` must be a pointer from [`runst_start`] that has not already
+/// been freed, or NULL.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn runst_free(handle: *mut RunstHandle) {
+    if !handle.is_null() {
+        drop(unsafe { Box::from_raw(handle) });
+    }
+}
@@ -0,0 +1,198 @@
+//! Opt-in GNTP (Growl Notification Transport Protocol) listener.
+//!
+//! Accepts `REGISTER` and `NOTIFY` requests from legacy Growl clients on the
+//! LAN and forwards them into the regular notification pipeline. Only
+//! unencrypted (`NONE`) messages are supported. The listener and each
+//! client run as tasks on the shared [runtime](crate::runtime) rather
+//! than spawning a thread per connection.
+
+use crate::notification::{Action, Notification, Urgency};
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::mpsc::Sender;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::tcp::OwnedWriteHalf;
+use tokio::net::{TcpListener, TcpStream};
+
+/// Counter for GNTP notification IDs, offset to avoid colliding with D-Bus IDs.
+static NEXT_ID: AtomicU32 = AtomicU32::new(0x8000_0000);
+
+/// GNTP listener configuration.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct GntpConfig {
+    /// Whether the listener is enabled.
+    #[serde(default)]
+    pub enabled: bool,
+    /// Address to bind to.
+    #[serde(default = "default_bind")]
+    pub bind: String,
+}
+
+impl Default for GntpConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            bind: default_bind(),
+        }
+    }
+}
+
+/// Default address the GNTP listener binds to.
+fn default_bind() -> String {
+    String::from("0.0.0.0:23053")
+}
+
+/// Spawns the GNTP listener task if it is enabled.
+pub fn spawn(config: GntpConfig, sender: Sender<Action>) {
+    if !config.enabled {
+        return;
+    }
+    crate::runtime::spawn(async move {
+        let listener = match TcpListener::bind(&config.bind).await {
+            Ok(listener) => listener,
+            Err(e) => {
+                warn!("failed to bind GNTP listener on {}: {}", config.bind, e);
+                return;
+            }
+        };
+        info!("GNTP listener running on {}", config.bind);
+        loop {
+            match listener.accept().await {
+                Ok((stream, _)) => {
+                    let sender = sender.clone();
+                    tokio::spawn(async move {
+                        if let Err(e) = handle_client(stream, sender).await {
+                            debug!("GNTP client error: {}", e);
+                        }
+                    });
+                }
+                Err(e) => warn!("GNTP accept failed: {}", e),
+            }
+        }
+    });
+}
+
+/// Handles a single GNTP request.
+async fn handle_client(stream: TcpStream, sender: Sender<Action>) -> std::io::Result<()> {
+    let peer = stream.peer_addr()?;
+    let (read_half, mut write_half) = stream.into_split();
+    let mut reader = BufReader::new(read_half);
+
+    // e.g. "GNTP/1.0 NOTIFY NONE"
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).await?;
+    let mut parts = request_line.trim().split_whitespace();
+    let version = parts.next().unwrap_or_default();
+    let message_type = parts.next().unwrap_or_default().to_uppercase();
+    if version != "GNTP/1.0" {
+        return respond_error(&mut write_half, "unsupported protocol version").await;
+    }
+
+    let mut headers = HashMap::new();
+    let mut line = String::new();
+    loop {
+        line.clear();
+        if reader.read_line(&mut line).await? == 0 {
+            break;
+        }
+        let trimmed = line.trim_end();
+        if trimmed.is_empty() {
+            break;
+        }
+        if let Some((key, value)) = trimmed.split_once(':') {
+            headers.insert(key.trim().to_lowercase(), value.trim().to_string());
+        }
+    }
+
+    match message_type.as_str() {
+        "REGISTER" => {
+            debug!(
+                "GNTP REGISTER from {} for {:?}",
+                peer,
+                headers.get("application-name")
+            );
+            respond_ok(&mut write_half, "REGISTER").await
+        }
+        "NOTIFY" => {
+            let notification = Notification {
+                id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+                app_name: headers
+                    .get("application-name")
+                    .cloned()
+                    .unwrap_or_else(|| String::from("growl")),
+                summary: headers
+                    .get("notification-title")
+                    .cloned()
+                    .unwrap_or_default(),
+                body: headers
+                    .get("notification-text")
+                    .cloned()
+                    .unwrap_or_default(),
+                expire_timeout: None,
+                urgency: headers
+                    .get("notification-priority")
+                    .and_then(|v| v.parse::<i8>().ok())
+                    .map(urgency_from_priority)
+                    .unwrap_or_default(),
+                is_read: false,
+                timestamp: SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|d| d.as_secs())
+                    .unwrap_or(0),
+                actions: Vec::new(),
+                deadline: None,
+                repeats: 0,
+                sound: None,
+                suppress_sound: false,
+                icon: String::new(),
+                category: None,
+                value: None,
+                hints: HashMap::new(),
+                image: None,
+            };
+            info!(
+                "GNTP notification from {}: app=\"{}\" summary=\"{}\"",
+                peer, notification.app_name, notification.summary
+            );
+            if sender.send(Action::Show(notification)).is_err() {
+                warn!("failed to forward GNTP notification");
+            }
+            respond_ok(&mut write_half, "NOTIFY").await
+        }
+        _ => {
+            respond_error(
+                &mut write_half,
+                &format!("unsupported message type {message_type}"),
+            )
+            .await
+        }
+    }
+}
+
+/// Maps a GNTP priority (-2..2) onto an urgency level.
+fn urgency_from_priority(priority: i8) -> Urgency {
+    match priority {
+        2 => Urgency::Critical,
+        i8::MIN..=-1 => Urgency::Low,
+        _ => Urgency::Normal,
+    }
+}
+
+/// Writes a GNTP success response.
+async fn respond_ok(stream: &mut OwnedWriteHalf, action: &str) -> std::io::Result<()> {
+    stream
+        .write_all(format!("GNTP/1.0 -OK NONE\r\nResponse-Action: {action}\r\n\r\n").as_bytes())
+        .await
+}
+
+/// Writes a GNTP error response.
+async fn respond_error(stream: &mut OwnedWriteHalf, description: &str) -> std::io::Result<()> {
+    stream
+        .write_all(
+            format!("GNTP/1.0 -ERROR NONE\r\nError-Description: {description}\r\n\r\n").as_bytes(),
+        )
+        .await
+}
@@ -6,14 +6,29 @@
 use crate::error::{Error, Result};
 use crate::notification::Urgency;
 use chrono::{DateTime, Utc};
+use flate2::Compression;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
 use serde::{Deserialize, Serialize};
-use std::collections::VecDeque;
+use std::collections::{BTreeMap, HashMap, VecDeque};
 use std::fs;
 use std::path::PathBuf;
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 /// Default maximum number of notifications to store in history.
 pub const DEFAULT_HISTORY_LIMIT: usize = 10_000;
 
+/// Number of queued entries after which the writer flushes to disk.
+const WRITER_BATCH_SIZE: usize = 32;
+
+/// Maximum time queued entries wait before the writer flushes to disk.
+const WRITER_FLUSH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Interval between retention prunes while the writer is idle.
+const WRITER_PRUNE_INTERVAL: Duration = Duration::from_secs(600);
+
 /// A serializable notification entry for history storage.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct HistoryEntry {
@@ -31,6 +46,27 @@ pub struct HistoryEntry {
     pub timestamp: u64,
     /// ISO 8601 formatted timestamp for human readability.
     pub datetime: String,
+    /// Unix timestamp after which this entry is pruned from history
+    /// (set by rules with a `history_ttl`).
+    #[serde(default)]
+    pub expires_at: Option<u64>,
+    /// Stable content hash of the notification, for dedup and "seen
+    /// before" checks across daemon restarts.
+    #[serde(default)]
+    pub content_hash: u64,
+    /// How many consecutive identical notifications this entry stands
+    /// for; repeats coalesce instead of adding rows.
+    #[serde(default = "default_count")]
+    pub count: u64,
+    /// Unix timestamp of the most recent repetition.
+    #[serde(default)]
+    pub last_seen: u64,
+}
+
+/// Returns the default repetition count for entries saved before
+/// coalescing existed.
+fn default_count() -> u64 {
+    1
 }
 
 impl HistoryEntry {
@@ -47,6 +83,7 @@ impl HistoryEntry {
             .unwrap_or_else(Utc::now)
             .format("%Y-%m-%d %H:%M:%S UTC")
             .to_string();
+        let content_hash = crate::notification::content_hash(&app_name, &summary, &body, urgency);
 
         Self {
             id,
@@ -56,6 +93,341 @@ impl HistoryEntry {
             urgency: urgency.to_string(),
             timestamp,
             datetime,
+            expires_at: None,
+            content_hash,
+            count: 1,
+            last_seen: timestamp,
+        }
+    }
+}
+
+/// History storage configuration.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct HistoryConfig {
+    /// Storage backend.
+    #[serde(default)]
+    pub backend: HistoryBackend,
+    /// Per-app retention periods: application name glob patterns mapped
+    /// to humantime durations (e.g. `"spotify" = "1d"`). Matching entries
+    /// older than their period are purged early; unmatched apps keep the
+    /// full history limit.
+    #[serde(default)]
+    pub retention: HashMap<String, String>,
+}
+
+/// Storage backend for the history.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum HistoryBackend {
+    /// Whole-file JSON rewrite on every change (the original format).
+    #[default]
+    Json,
+    /// SQLite database with O(1) appends.
+    Sqlite,
+    /// Compact binary file: a fraction of the JSON size and much faster
+    /// to load at startup. `runst history --export` dumps it as JSON.
+    Binary,
+    /// Volatile in-memory store; nothing survives a restart.
+    Memory,
+}
+
+/// Persistence behind the in-memory history buffer.
+///
+/// [`History`] owns the entries and their ordering; a store only mirrors
+/// them to a backing medium. The built-in implementations cover the
+/// serde-selectable [`HistoryBackend`]s, and a new medium (a remote
+/// service, say) only needs this trait plus [`History::with_store`] — no
+/// call site in the daemon names a concrete store.
+pub trait HistoryStore: std::fmt::Debug + Send {
+    /// Loads the persisted entries, oldest first.
+    fn load(&mut self) -> Result<VecDeque<HistoryEntry>>;
+
+    /// Rewrites the persisted history in full.
+    fn save(&mut self, entries: &VecDeque<HistoryEntry>) -> Result<()>;
+
+    /// Returns true when appends persist immediately, making the full
+    /// [`HistoryStore::save`] after a deferred batch unnecessary.
+    fn write_through(&self) -> bool {
+        false
+    }
+
+    /// Persists one appended entry.
+    ///
+    /// Write-through stores only; the rest wait for the next save.
+    fn append(&mut self, _entry: &HistoryEntry) -> Result<()> {
+        Ok(())
+    }
+
+    /// Updates the newest persisted entry after a repeat coalesced into it.
+    fn coalesce_newest(&mut self, _count: u64, _last_seen: u64) -> Result<()> {
+        Ok(())
+    }
+
+    /// Mirrors the in-memory expiry and limit pruning.
+    fn prune(&mut self, _now: u64, _limit: usize) -> Result<()> {
+        Ok(())
+    }
+
+    /// Runs a backend-accelerated search, or returns `None` to fall back
+    /// to the linear scan over the in-memory entries.
+    fn search(&self, _query: &str) -> Option<Vec<HistoryEntry>> {
+        None
+    }
+}
+
+/// JSON file store, rewritten in full on every save.
+#[derive(Debug)]
+struct JsonStore {
+    /// Path to the history file.
+    path: PathBuf,
+}
+
+impl HistoryStore for JsonStore {
+    fn load(&mut self) -> Result<VecDeque<HistoryEntry>> {
+        History::load_from_json(&self.path)
+    }
+
+    fn save(&mut self, entries: &VecDeque<HistoryEntry>) -> Result<()> {
+        let entries: Vec<&HistoryEntry> = entries.iter().collect();
+        fs::write(&self.path, serde_json::to_string_pretty(&entries)?)?;
+        Ok(())
+    }
+}
+
+/// Bincode file store, rewritten in full on every save (cheaply, as the
+/// encoding is a fixed-layout dump without field names).
+#[derive(Debug)]
+struct BinaryStore {
+    /// Path to the history file.
+    path: PathBuf,
+}
+
+impl BinaryStore {
+    /// Loads history entries from a binary file path.
+    fn read(path: &PathBuf) -> Result<VecDeque<HistoryEntry>> {
+        if !path.exists() {
+            return Ok(VecDeque::new());
+        }
+        let entries: Vec<HistoryEntry> = bincode::deserialize(&fs::read(path)?)?;
+        Ok(VecDeque::from(entries))
+    }
+}
+
+impl HistoryStore for BinaryStore {
+    fn load(&mut self) -> Result<VecDeque<HistoryEntry>> {
+        Self::read(&self.path)
+    }
+
+    fn save(&mut self, entries: &VecDeque<HistoryEntry>) -> Result<()> {
+        let entries: Vec<&HistoryEntry> = entries.iter().collect();
+        fs::write(&self.path, bincode::serialize(&entries)?)?;
+        Ok(())
+    }
+}
+
+/// Volatile store that never touches disk.
+///
+/// Nothing survives a restart; tests and embedders that do not want
+/// history files use it through [`HistoryBackend::Memory`].
+#[derive(Debug, Default)]
+pub struct MemoryStore {
+    /// The "persisted" entries.
+    entries: VecDeque<HistoryEntry>,
+}
+
+impl HistoryStore for MemoryStore {
+    fn load(&mut self) -> Result<VecDeque<HistoryEntry>> {
+        Ok(self.entries.clone())
+    }
+
+    fn save(&mut self, entries: &VecDeque<HistoryEntry>) -> Result<()> {
+        self.entries = entries.clone();
+        Ok(())
+    }
+}
+
+/// SQLite store with write-through appends and full-text search.
+#[derive(Debug)]
+struct SqliteStore {
+    /// Open database connection.
+    connection: rusqlite::Connection,
+}
+
+impl SqliteStore {
+    /// Opens (and initializes) the database at the given path.
+    fn open(path: &PathBuf) -> Result<Self> {
+        let connection = rusqlite::Connection::open(path)?;
+        connection.execute(
+            "CREATE TABLE IF NOT EXISTS history (
+                id INTEGER NOT NULL,
+                app_name TEXT NOT NULL,
+                summary TEXT NOT NULL,
+                body TEXT NOT NULL,
+                urgency TEXT NOT NULL,
+                timestamp INTEGER NOT NULL,
+                datetime TEXT NOT NULL,
+                expires_at INTEGER,
+                content_hash INTEGER NOT NULL,
+                \"count\" INTEGER NOT NULL DEFAULT 1,
+                last_seen INTEGER NOT NULL DEFAULT 0
+            )",
+            (),
+        )?;
+        // Databases created before coalescing lack these columns; the
+        // duplicate-column error on newer databases is expected
+        let _ = connection.execute(
+            "ALTER TABLE history ADD COLUMN \"count\" INTEGER NOT NULL DEFAULT 1",
+            (),
+        );
+        let _ = connection.execute(
+            "ALTER TABLE history ADD COLUMN last_seen INTEGER NOT NULL DEFAULT 0",
+            (),
+        );
+        // Full-text index over the searchable columns, kept in sync with
+        // triggers; the rebuild picks up databases created before the
+        // index existed
+        connection.execute_batch(
+            "CREATE VIRTUAL TABLE IF NOT EXISTS history_fts USING fts5(
+                app_name, summary, body,
+                content='history', content_rowid='rowid'
+            );
+            CREATE TRIGGER IF NOT EXISTS history_fts_insert
+            AFTER INSERT ON history BEGIN
+                INSERT INTO history_fts(rowid, app_name, summary, body)
+                VALUES (new.rowid, new.app_name, new.summary, new.body);
+            END;
+            CREATE TRIGGER IF NOT EXISTS history_fts_delete
+            AFTER DELETE ON history BEGIN
+                INSERT INTO history_fts(history_fts, rowid, app_name, summary, body)
+                VALUES ('delete', old.rowid, old.app_name, old.summary, old.body);
+            END;
+            INSERT INTO history_fts(history_fts) VALUES ('rebuild');",
+        )?;
+        Ok(Self { connection })
+    }
+
+    /// Runs a full-text query against the database, best matches first.
+    fn search_fts(&self, query: &str) -> Result<Vec<HistoryEntry>> {
+        let mut statement = self.connection.prepare(
+            "SELECT h.id, h.app_name, h.summary, h.body, h.urgency, h.timestamp,
+                    h.datetime, h.expires_at, h.content_hash, h.\"count\", h.last_seen
+             FROM history h
+             JOIN history_fts f ON h.rowid = f.rowid
+             WHERE history_fts MATCH ?1
+             ORDER BY bm25(history_fts)",
+        )?;
+        let entries = statement
+            .query_map((query,), Self::row_to_entry)?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(entries)
+    }
+
+    /// Maps a full history row to an entry.
+    fn row_to_entry(row: &rusqlite::Row<'_>) -> rusqlite::Result<HistoryEntry> {
+        Ok(HistoryEntry {
+            id: row.get(0)?,
+            app_name: row.get(1)?,
+            summary: row.get(2)?,
+            body: row.get(3)?,
+            urgency: row.get(4)?,
+            timestamp: row.get::<_, i64>(5)? as u64,
+            datetime: row.get(6)?,
+            expires_at: row.get::<_, Option<i64>>(7)?.map(|v| v as u64),
+            content_hash: row.get::<_, i64>(8)? as u64,
+            count: row.get::<_, i64>(9)? as u64,
+            last_seen: row.get::<_, i64>(10)? as u64,
+        })
+    }
+
+    /// Appends a single entry via the given connection (or transaction).
+    fn insert(connection: &rusqlite::Connection, entry: &HistoryEntry) -> Result<()> {
+        connection.execute(
+            "INSERT INTO history (id, app_name, summary, body, urgency, timestamp,
+                                  datetime, expires_at, content_hash, \"count\", last_seen)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+            (
+                entry.id,
+                &entry.app_name,
+                &entry.summary,
+                &entry.body,
+                &entry.urgency,
+                entry.timestamp as i64,
+                &entry.datetime,
+                entry.expires_at.map(|v| v as i64),
+                entry.content_hash as i64,
+                entry.count as i64,
+                entry.last_seen as i64,
+            ),
+        )?;
+        Ok(())
+    }
+}
+
+impl HistoryStore for SqliteStore {
+    fn load(&mut self) -> Result<VecDeque<HistoryEntry>> {
+        let mut statement = self.connection.prepare(
+            "SELECT id, app_name, summary, body, urgency, timestamp, datetime,
+                    expires_at, content_hash, \"count\", last_seen
+             FROM history ORDER BY rowid",
+        )?;
+        let entries = statement
+            .query_map((), Self::row_to_entry)?
+            .collect::<std::result::Result<VecDeque<_>, _>>()?;
+        Ok(entries)
+    }
+
+    fn save(&mut self, entries: &VecDeque<HistoryEntry>) -> Result<()> {
+        // One transaction, so a crash mid-save keeps the old rows and
+        // the FTS triggers pay a single commit instead of one per row
+        let tx = self.connection.transaction()?;
+        tx.execute("DELETE FROM history", ())?;
+        for entry in entries {
+            Self::insert(&tx, entry)?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn write_through(&self) -> bool {
+        true
+    }
+
+    fn append(&mut self, entry: &HistoryEntry) -> Result<()> {
+        Self::insert(&self.connection, entry)
+    }
+
+    fn coalesce_newest(&mut self, count: u64, last_seen: u64) -> Result<()> {
+        self.connection.execute(
+            "UPDATE history SET \"count\" = ?1, last_seen = ?2
+             WHERE rowid = (SELECT MAX(rowid) FROM history)",
+            (count as i64, last_seen as i64),
+        )?;
+        Ok(())
+    }
+
+    fn prune(&mut self, now: u64, limit: usize) -> Result<()> {
+        // Mirror the in-memory pruning with cheap indexed deletes
+        self.connection.execute(
+            "DELETE FROM history
+             WHERE expires_at IS NOT NULL AND expires_at <= ?1",
+            (now as i64,),
+        )?;
+        self.connection.execute(
+            "DELETE FROM history
+             WHERE rowid NOT IN
+                (SELECT rowid FROM history ORDER BY rowid DESC LIMIT ?1)",
+            (limit,),
+        )?;
+        Ok(())
+    }
+
+    fn search(&self, query: &str) -> Option<Vec<HistoryEntry>> {
+        match self.search_fts(query) {
+            Ok(entries) => Some(entries),
+            Err(e) => {
+                log::debug!("full-text search failed, scanning linearly: {}", e);
+                None
+            }
         }
     }
 }
@@ -63,29 +435,106 @@ impl HistoryEntry {
 /// Persistent notification history manager.
 #[derive(Debug)]
 pub struct History {
-    /// Path to the history file.
+    /// Path to the history file or database.
     path: PathBuf,
+    /// Store the entries are persisted with.
+    store: Box<dyn HistoryStore>,
     /// In-memory buffer of history entries.
     entries: VecDeque<HistoryEntry>,
+    /// Parsed per-app retention policies (glob pattern, maximum age).
+    retention: Vec<(String, Duration)>,
     /// Maximum number of entries to store.
     limit: usize,
 }
 
 impl History {
     /// Creates a new history manager, loading existing history from disk.
+    ///
+    /// The backend is detected from the files on disk: an existing SQLite
+    /// database wins, then a binary file, otherwise the JSON file is
+    /// used. Pass an explicit backend with [`History::with_backend`].
     pub fn new(limit: usize) -> Result<Self> {
+        let json_path = Self::default_path()?;
+        let backend = if Self::sqlite_path(&json_path).exists() {
+            HistoryBackend::Sqlite
+        } else if Self::binary_path(&json_path).exists() {
+            HistoryBackend::Binary
+        } else {
+            HistoryBackend::Json
+        };
+        Self::with_backend(limit, backend)
+    }
+
+    /// Creates a new history manager with the given storage backend.
+    ///
+    /// Opening the SQLite or binary backend for the first time imports
+    /// any existing `history.json`, so switching backends keeps the
+    /// history.
+    pub fn with_backend(limit: usize, backend: HistoryBackend) -> Result<Self> {
+        let json_path = Self::default_path()?;
+        let (path, store): (PathBuf, Box<dyn HistoryStore>) = match backend {
+            HistoryBackend::Json => {
+                let store = JsonStore {
+                    path: json_path.clone(),
+                };
+                (json_path.clone(), Box::new(store))
+            }
+            HistoryBackend::Sqlite => {
+                let path = Self::sqlite_path(&json_path);
+                (path.clone(), Box::new(SqliteStore::open(&path)?))
+            }
+            HistoryBackend::Binary => {
+                let path = Self::binary_path(&json_path);
+                (path.clone(), Box::new(BinaryStore { path: path.clone() }))
+            }
+            HistoryBackend::Memory => (json_path.clone(), Box::new(MemoryStore::default())),
+        };
+
+        let mut history = Self::load_store(limit, store, path)?;
+
+        // Opening a file-backed store for the first time imports the
+        // original JSON file, so switching backends keeps the history
+        if history.is_empty()
+            && matches!(backend, HistoryBackend::Sqlite | HistoryBackend::Binary)
+            && json_path.exists()
+        {
+            history.entries = Self::load_from_json(&json_path)?;
+            if !history.is_empty() {
+                history.save()?;
+                log::info!(
+                    "migrated {} history entries from {} to {}",
+                    history.len(),
+                    json_path.display(),
+                    history.path.display()
+                );
+            }
+        }
+        Ok(history)
+    }
+
+    /// Creates a new history manager around a custom store.
+    ///
+    /// This is the extension point for stores the [`HistoryBackend`] enum
+    /// does not cover; the path only labels log lines and archive
+    /// placement, and defaults to the JSON file location.
+    pub fn with_store(limit: usize, store: Box<dyn HistoryStore>) -> Result<Self> {
         let path = Self::default_path()?;
-        let entries = Self::load_from_path(&path)?;
+        Self::load_store(limit, store, path)
+    }
 
+    /// Creates the manager by loading the store's persisted entries.
+    fn load_store(limit: usize, mut store: Box<dyn HistoryStore>, path: PathBuf) -> Result<Self> {
+        let entries = store.load()?;
         log::debug!(
             "loaded {} history entries from {}",
             entries.len(),
             path.display()
         );
-
         Ok(Self {
             path,
+            store,
             entries,
+            retention: Vec::new(),
             limit,
         })
     }
@@ -103,8 +552,18 @@ impl History {
         Ok(path)
     }
 
-    /// Loads history entries from a file path.
-    fn load_from_path(path: &PathBuf) -> Result<VecDeque<HistoryEntry>> {
+    /// Returns the SQLite database path next to the JSON file.
+    fn sqlite_path(json_path: &PathBuf) -> PathBuf {
+        json_path.with_file_name("history.sqlite3")
+    }
+
+    /// Returns the binary history path next to the JSON file.
+    fn binary_path(json_path: &PathBuf) -> PathBuf {
+        json_path.with_file_name("history.bin")
+    }
+
+    /// Loads history entries from a JSON file path.
+    fn load_from_json(path: &PathBuf) -> Result<VecDeque<HistoryEntry>> {
         if !path.exists() {
             return Ok(VecDeque::new());
         }
@@ -119,22 +578,151 @@ impl History {
     }
 
     /// Adds a notification to history and persists to disk.
+    ///
+    /// On the SQLite backend this is a single O(1) insert plus cheap
+    /// pruning deletes; the JSON backend rewrites the whole file.
     pub fn add(&mut self, entry: HistoryEntry) -> Result<()> {
+        self.add_deferred(entry)?;
+        self.flush()
+    }
+
+    /// Adds an entry without rewriting deferred stores.
+    ///
+    /// Write-through stores persist immediately either way, so deferral
+    /// only postpones a whole-file rewrite until [`History::flush`]; the
+    /// [`HistoryWriter`] uses this to batch saves.
+    fn add_deferred(&mut self, entry: HistoryEntry) -> Result<()> {
+        // Consecutive identical notifications coalesce into one entry with
+        // a bumped repetition count instead of a new row
+        if let Some(last) = self.entries.back_mut()
+            && last.content_hash == entry.content_hash
+        {
+            last.count += 1;
+            last.last_seen = entry.timestamp;
+            let (count, last_seen) = (last.count, last.last_seen);
+            return self.store.coalesce_newest(count, last_seen);
+        }
+        self.store.append(&entry)?;
         self.entries.push_back(entry);
+        self.prune_expired();
+        self.prune_retained();
 
         // Enforce limit by removing oldest entries
         while self.entries.len() > self.limit {
             self.entries.pop_front();
         }
 
+        self.store.prune(Self::now(), self.limit)
+    }
+
+    /// Persists additions deferred by [`History::add_deferred`].
+    ///
+    /// A no-op on write-through stores, which persist on every add.
+    fn flush(&mut self) -> Result<()> {
+        if self.store.write_through() {
+            Ok(())
+        } else {
+            self.save()
+        }
+    }
+
+    /// Sets the per-app retention policies from the configuration map.
+    ///
+    /// Patterns are glob-matched against application names; the first
+    /// matching pattern wins, with ties broken alphabetically since the
+    /// configuration map is unordered.
+    pub fn set_retention(&mut self, retention: &HashMap<String, String>) -> Result<()> {
+        let mut parsed = Vec::new();
+        for (pattern, raw) in retention {
+            let duration = humantime::parse_duration(raw).map_err(|e| {
+                Error::Config(format!("invalid retention for `{}`: {}", pattern, e))
+            })?;
+            parsed.push((pattern.clone(), duration));
+        }
+        parsed.sort();
+        self.retention = parsed;
+        Ok(())
+    }
+
+    /// Removes entries older than their app's retention period.
+    ///
+    /// In-memory only; the SQLite rows catch up on the next full save,
+    /// which the writer's periodic prune triggers.
+    fn prune_retained(&mut self) -> usize {
+        if self.retention.is_empty() {
+            return 0;
+        }
+        let now = Self::now();
+        let retention = self.retention.clone();
+        let before = self.entries.len();
+        self.entries.retain(|e| {
+            match retention
+                .iter()
+                .find(|(pattern, _)| crate::config::glob_match(pattern, &e.app_name))
+            {
+                Some((_, max_age)) => e.timestamp + max_age.as_secs() > now,
+                None => true,
+            }
+        });
+        let pruned = before - self.entries.len();
+        if pruned > 0 {
+            log::debug!("pruned {} history entries past their retention", pruned);
+        }
+        pruned
+    }
+
+    /// Prunes retention-expired entries and persists if any were removed.
+    fn prune_retention_and_save(&mut self) -> Result<()> {
+        if self.prune_retained() > 0 {
+            self.save()
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Removes entries whose retention period has expired.
+    fn prune_expired(&mut self) {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        let before = self.entries.len();
+        self.entries.retain(|e| e.expires_at.is_none_or(|t| t > now));
+        let pruned = before - self.entries.len();
+        if pruned > 0 {
+            log::debug!("pruned {} expired history entries", pruned);
+        }
+    }
+
+    /// Caps the number of entries matching a predicate, removing the oldest
+    /// matching entries first, and persists the result.
+    pub fn enforce_limit_where(
+        &mut self,
+        limit: usize,
+        matches: impl Fn(&HistoryEntry) -> bool,
+    ) -> Result<()> {
+        let matching = self.entries.iter().filter(|e| matches(e)).count();
+        if matching <= limit {
+            return Ok(());
+        }
+        let mut excess = matching - limit;
+        self.entries.retain(|e| {
+            if excess > 0 && matches(e) {
+                excess -= 1;
+                false
+            } else {
+                true
+            }
+        });
         self.save()
     }
 
-    /// Saves the current history to disk.
-    fn save(&self) -> Result<()> {
-        let entries: Vec<&HistoryEntry> = self.entries.iter().collect();
-        let json = serde_json::to_string_pretty(&entries)?;
-        fs::write(&self.path, json)?;
+    /// Saves the current history to the store in full.
+    ///
+    /// Only [`History::add`] has a store-specific fast path; the rare
+    /// bulk mutations rewrite any store completely.
+    fn save(&mut self) -> Result<()> {
+        self.store.save(&self.entries)?;
         log::trace!(
             "saved {} history entries to {}",
             self.entries.len(),
@@ -164,7 +752,14 @@ impl History {
     }
 
     /// Searches history entries by app name, summary, or body.
-    pub fn search(&self, query: &str) -> Vec<&HistoryEntry> {
+    ///
+    /// On the SQLite backend this runs an FTS5 query (phrases, `prefix*`,
+    /// AND/OR) ranked by bm25; queries the FTS parser rejects, and the
+    /// file backends, fall back to a case-insensitive substring scan.
+    pub fn search(&self, query: &str) -> Vec<HistoryEntry> {
+        if let Some(entries) = self.store.search(query) {
+            return entries;
+        }
         let query_lower = query.to_lowercase();
         self.entries
             .iter()
@@ -173,9 +768,20 @@ impl History {
                     || e.summary.to_lowercase().contains(&query_lower)
                     || e.body.to_lowercase().contains(&query_lower)
             })
+            .cloned()
             .collect()
     }
 
+    /// Returns the full history as a versioned JSON document, oldest
+    /// first, following the [schema](crate::schema) guarantees.
+    ///
+    /// This is how the binary backend's contents get back into a
+    /// greppable form (`runst history --export`).
+    pub fn export_json(&self) -> Result<String> {
+        let entries: Vec<&HistoryEntry> = self.entries.iter().collect();
+        crate::schema::to_json(&entries)
+    }
+
     /// Clears all history entries and saves.
     pub fn clear(&mut self) -> Result<()> {
         self.entries.clear();
@@ -186,6 +792,285 @@ impl History {
     pub fn path(&self) -> &PathBuf {
         &self.path
     }
+
+    /// Rolls entries from past months out of the live history into
+    /// monthly gzip archives (`history-YYYY-MM.json.gz` next to the
+    /// history file) and returns how many were moved.
+    ///
+    /// Keeps the live file small without losing data; archived entries
+    /// stay reachable through [`History::since`].
+    pub fn compact(&mut self) -> Result<usize> {
+        let current = Self::month_key(Self::now());
+        let mut archived: BTreeMap<String, Vec<HistoryEntry>> = BTreeMap::new();
+        let mut live = VecDeque::new();
+        for entry in self.entries.drain(..) {
+            let month = Self::month_key(entry.timestamp);
+            if month < current {
+                archived.entry(month).or_default().push(entry);
+            } else {
+                live.push_back(entry);
+            }
+        }
+        self.entries = live;
+
+        let moved = archived.values().map(Vec::len).sum();
+        if moved == 0 {
+            return Ok(0);
+        }
+        for (month, entries) in &archived {
+            Self::append_archive(&self.archive_path(month), entries)?;
+        }
+        self.save()?;
+        log::info!("compacted {} history entries into monthly archives", moved);
+        Ok(moved)
+    }
+
+    /// Returns all entries newer than `cutoff` (Unix seconds), oldest
+    /// first, transparently reading monthly archives when the cutoff
+    /// reaches back before the live history.
+    pub fn since(&self, cutoff: u64) -> Result<Vec<HistoryEntry>> {
+        let mut entries = Vec::new();
+        let oldest_live = self.entries.front().map(|e| e.timestamp);
+        if oldest_live.is_none_or(|t| t > cutoff) {
+            for path in self.archive_paths()? {
+                entries.extend(
+                    Self::read_archive(&path)?
+                        .into_iter()
+                        .filter(|e| e.timestamp >= cutoff),
+                );
+            }
+        }
+        entries.extend(
+            self.entries
+                .iter()
+                .filter(|e| e.timestamp >= cutoff)
+                .cloned(),
+        );
+        Ok(entries)
+    }
+
+    /// Returns the current Unix timestamp.
+    fn now() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0)
+    }
+
+    /// Returns the "YYYY-MM" month key for a timestamp.
+    fn month_key(timestamp: u64) -> String {
+        DateTime::from_timestamp(timestamp as i64, 0)
+            .unwrap_or_else(Utc::now)
+            .format("%Y-%m")
+            .to_string()
+    }
+
+    /// Returns the archive path for a month key.
+    fn archive_path(&self, month: &str) -> PathBuf {
+        self.path
+            .with_file_name(format!("history-{}.json.gz", month))
+    }
+
+    /// Lists the existing monthly archives, oldest month first.
+    fn archive_paths(&self) -> Result<Vec<PathBuf>> {
+        let Some(dir) = self.path.parent() else {
+            return Ok(Vec::new());
+        };
+        let mut paths: Vec<PathBuf> = fs::read_dir(dir)?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| {
+                path.file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(|name| {
+                        name.starts_with("history-") && name.ends_with(".json.gz")
+                    })
+            })
+            .collect();
+        // The "YYYY-MM" month keys sort chronologically as strings
+        paths.sort();
+        Ok(paths)
+    }
+
+    /// Reads the entries of a gzip archive, oldest first.
+    fn read_archive(path: &PathBuf) -> Result<Vec<HistoryEntry>> {
+        let file = fs::File::open(path)?;
+        let entries = serde_json::from_reader(GzDecoder::new(file))?;
+        Ok(entries)
+    }
+
+    /// Appends entries to a gzip archive, creating it if needed.
+    fn append_archive(path: &PathBuf, entries: &[HistoryEntry]) -> Result<()> {
+        let mut all = if path.exists() {
+            Self::read_archive(path)?
+        } else {
+            Vec::new()
+        };
+        all.extend_from_slice(entries);
+        let file = fs::File::create(path)?;
+        serde_json::to_writer(GzEncoder::new(file, Compression::default()), &all)?;
+        Ok(())
+    }
+}
+
+/// A request handled by the history writer thread.
+enum WriterCommand {
+    /// Add an entry to history.
+    Add(Box<HistoryEntry>),
+    /// Cap entries matching a predicate (see [`History::enforce_limit_where`]).
+    EnforceLimit(usize, Box<dyn Fn(&HistoryEntry) -> bool + Send>),
+}
+
+/// Asynchronous, batched history persistence.
+///
+/// Owns the [`History`] on a background thread fed by a channel, so the
+/// notification hot path only enqueues. Writes are flushed after
+/// [`WRITER_BATCH_SIZE`] queued entries or [`WRITER_FLUSH_INTERVAL`],
+/// whichever comes first, and once more when the writer is dropped —
+/// which keeps the JSON backend's whole-file rewrite out of the main loop.
+pub struct HistoryWriter {
+    /// Channel feeding the writer thread.
+    sender: Option<mpsc::Sender<WriterCommand>>,
+    /// Writer thread handle, joined on drop for the final flush.
+    handle: Option<thread::JoinHandle<()>>,
+}
+
+impl HistoryWriter {
+    /// Spawns the writer thread around the given history.
+    pub fn new(history: History) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let handle = thread::Builder::new()
+            .name("runst-history".to_string())
+            .spawn(move || Self::run(history, receiver))
+            .expect("failed to spawn history writer");
+        Self {
+            sender: Some(sender),
+            handle: Some(handle),
+        }
+    }
+
+    /// Spawns the writer thread and loads the history on it.
+    ///
+    /// The caller only pays for the channel and the thread spawn; backend
+    /// detection, retention setup and archive compaction all happen on the
+    /// writer thread before the first queued command is applied. If loading
+    /// fails the thread exits and queued commands are dropped with a warning.
+    pub fn new_deferred(limit: usize, config: HistoryConfig) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let handle = thread::Builder::new()
+            .name("runst-history".to_string())
+            .spawn(move || {
+                let mut history = match History::with_backend(limit, config.backend) {
+                    Ok(history) => history,
+                    Err(e) => {
+                        log::error!("failed to load history: {}", e);
+                        return;
+                    }
+                };
+                if let Err(e) = history.set_retention(&config.retention) {
+                    log::warn!("failed to apply history retention: {}", e);
+                }
+                // Roll past months into gzip archives before taking new entries
+                if let Err(e) = history.compact() {
+                    log::warn!("failed to compact history: {}", e);
+                }
+                log::info!("history storage initialized with {} entries", history.len());
+                Self::run(history, receiver)
+            })
+            .expect("failed to spawn history writer");
+        Self {
+            sender: Some(sender),
+            handle: Some(handle),
+        }
+    }
+
+    /// Queues a notification entry for persistence.
+    pub fn add(&self, entry: HistoryEntry) {
+        self.send(WriterCommand::Add(Box::new(entry)));
+    }
+
+    /// Queues a rule history-limit enforcement pass.
+    pub fn enforce_limit_where(
+        &self,
+        limit: usize,
+        matches: impl Fn(&HistoryEntry) -> bool + Send + 'static,
+    ) {
+        self.send(WriterCommand::EnforceLimit(limit, Box::new(matches)));
+    }
+
+    /// Sends a command to the writer thread, logging if it is gone.
+    fn send(&self, command: WriterCommand) {
+        if let Some(sender) = &self.sender
+            && sender.send(command).is_err()
+        {
+            log::warn!("history writer is not running");
+        }
+    }
+
+    /// Writer thread body: applies commands and flushes in batches.
+    fn run(mut history: History, receiver: mpsc::Receiver<WriterCommand>) {
+        let mut pending = 0usize;
+        loop {
+            // A short timer while entries are pending, a long one for the
+            // periodic retention prune otherwise
+            let timeout = if pending > 0 {
+                WRITER_FLUSH_INTERVAL
+            } else {
+                WRITER_PRUNE_INTERVAL
+            };
+            let command = match receiver.recv_timeout(timeout) {
+                Ok(command) => Some(command),
+                Err(mpsc::RecvTimeoutError::Timeout) => None,
+                Err(mpsc::RecvTimeoutError::Disconnected) => break,
+            };
+            match command {
+                Some(WriterCommand::Add(entry)) => {
+                    if let Err(e) = history.add_deferred(*entry) {
+                        log::warn!("failed to save notification to history: {}", e);
+                    }
+                    pending += 1;
+                    if pending >= WRITER_BATCH_SIZE {
+                        Self::flush(&mut history, &mut pending);
+                    }
+                }
+                Some(WriterCommand::EnforceLimit(limit, matches)) => {
+                    if let Err(e) = history.enforce_limit_where(limit, matches) {
+                        log::warn!("failed to enforce rule history limit: {}", e);
+                    }
+                    Self::flush(&mut history, &mut pending);
+                }
+                None => {
+                    Self::flush(&mut history, &mut pending);
+                    if let Err(e) = history.prune_retention_and_save() {
+                        log::warn!("failed to prune history retention: {}", e);
+                    }
+                }
+            }
+        }
+        Self::flush(&mut history, &mut pending);
+    }
+
+    /// Flushes deferred entries, if any, resetting the pending counter.
+    fn flush(history: &mut History, pending: &mut usize) {
+        if *pending == 0 {
+            return;
+        }
+        if let Err(e) = history.flush() {
+            log::warn!("failed to flush history: {}", e);
+        }
+        *pending = 0;
+    }
+}
+
+impl Drop for HistoryWriter {
+    fn drop(&mut self) {
+        // Disconnect the channel and wait for the final flush
+        drop(self.sender.take());
+        if let Some(handle) = self.handle.take()
+            && handle.join().is_err()
+        {
+            log::warn!("history writer thread panicked");
+        }
+    }
 }
 
 #[cfg(test)]
@@ -219,8 +1104,10 @@ mod tests {
         let path = dir.path().join("history.json");
 
         let mut history = History {
-            path,
+            path: path.clone(),
+            store: Box::new(JsonStore { path }),
             entries: VecDeque::new(),
+            retention: Vec::new(),
             limit: 3,
         };
 
@@ -237,14 +1124,67 @@ mod tests {
         assert_eq!(entries[2].id, 4);
     }
 
+    #[test]
+    fn test_history_expiry() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("history.json");
+
+        let mut history = History {
+            path: path.clone(),
+            store: Box::new(JsonStore { path }),
+            entries: VecDeque::new(),
+            retention: Vec::new(),
+            limit: 100,
+        };
+
+        let mut expired = create_test_entry(1, "ci", "build finished");
+        expired.expires_at = Some(1); // long past
+        history.add(expired).unwrap();
+        history.add(create_test_entry(2, "calendar", "meeting")).unwrap();
+
+        assert_eq!(history.len(), 1);
+        assert_eq!(history.all()[0].id, 2);
+    }
+
+    #[test]
+    fn test_enforce_limit_where() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("history.json");
+
+        let mut history = History {
+            path: path.clone(),
+            store: Box::new(JsonStore { path }),
+            entries: VecDeque::new(),
+            retention: Vec::new(),
+            limit: 100,
+        };
+
+        for i in 0..5 {
+            history
+                .add(create_test_entry(i, "ci", &format!("build {}", i)))
+                .unwrap();
+        }
+        history.add(create_test_entry(5, "slack", "message")).unwrap();
+
+        history
+            .enforce_limit_where(2, |e| e.app_name == "ci")
+            .unwrap();
+
+        assert_eq!(history.len(), 3);
+        let ids: Vec<_> = history.all().iter().map(|e| e.id).collect();
+        assert_eq!(ids, vec![3, 4, 5]);
+    }
+
     #[test]
     fn test_history_search() {
         let dir = tempdir().unwrap();
         let path = dir.path().join("history.json");
 
         let mut history = History {
-            path,
+            path: path.clone(),
+            store: Box::new(JsonStore { path }),
             entries: VecDeque::new(),
+            retention: Vec::new(),
             limit: 100,
         };
 
@@ -272,8 +1212,10 @@ mod tests {
         let path = dir.path().join("history.json");
 
         let mut history = History {
-            path,
+            path: path.clone(),
+            store: Box::new(JsonStore { path }),
             entries: VecDeque::new(),
+            retention: Vec::new(),
             limit: 100,
         };
 
@@ -289,4 +1231,248 @@ mod tests {
         assert_eq!(recent[1].id, 8);
         assert_eq!(recent[2].id, 7);
     }
+
+    #[test]
+    fn test_coalesce_duplicates() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("history.json");
+
+        let mut history = History {
+            path: path.clone(),
+            store: Box::new(JsonStore { path }),
+            entries: VecDeque::new(),
+            retention: Vec::new(),
+            limit: 100,
+        };
+
+        for i in 0..3 {
+            let mut entry = create_test_entry(1, "slack", "reconnecting");
+            entry.timestamp += i;
+            entry.last_seen = entry.timestamp;
+            history.add(entry).unwrap();
+        }
+        history.add(create_test_entry(2, "slack", "connected")).unwrap();
+
+        assert_eq!(history.len(), 2);
+        let entries = history.all();
+        assert_eq!(entries[0].count, 3);
+        assert_eq!(entries[0].timestamp, 1234567890);
+        assert_eq!(entries[0].last_seen, 1234567892);
+        assert_eq!(entries[1].count, 1);
+    }
+
+    #[test]
+    fn test_retention_prune() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("history.json");
+
+        let mut history = History {
+            path: path.clone(),
+            store: Box::new(JsonStore { path }),
+            entries: VecDeque::new(),
+            retention: Vec::new(),
+            limit: 100,
+        };
+        history
+            .set_retention(&HashMap::from([("spot*".to_string(), "1h".to_string())]))
+            .unwrap();
+
+        // create_test_entry timestamps are far older than one hour
+        history.add(create_test_entry(1, "spotify", "now playing")).unwrap();
+        history.add(create_test_entry(2, "slack", "message")).unwrap();
+        let mut fresh = create_test_entry(3, "spotify", "next track");
+        fresh.timestamp = History::now();
+        history.add(fresh).unwrap();
+
+        let ids: Vec<_> = history.all().iter().map(|e| e.id).collect();
+        assert_eq!(ids, vec![2, 3]);
+
+        let bad = HashMap::from([("spotify".to_string(), "not a duration".to_string())]);
+        assert!(history.set_retention(&bad).is_err());
+    }
+
+    #[test]
+    fn test_compact_and_since() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("history.json");
+
+        let mut history = History {
+            path: path.clone(),
+            store: Box::new(JsonStore { path: path.clone() }),
+            entries: VecDeque::new(),
+            retention: Vec::new(),
+            limit: 100,
+        };
+
+        // create_test_entry timestamps land in February 2009
+        history.add(create_test_entry(1, "ci", "old build")).unwrap();
+        let mut fresh = create_test_entry(2, "slack", "fresh message");
+        fresh.timestamp = History::now();
+        history.add(fresh).unwrap();
+
+        let moved = history.compact().unwrap();
+        assert_eq!(moved, 1);
+        assert_eq!(history.len(), 1);
+        assert!(path.with_file_name("history-2009-02.json.gz").exists());
+
+        // A wide window transparently reads the archive back
+        let entries = history.since(0).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].id, 1);
+        assert_eq!(entries[1].id, 2);
+
+        // A narrow window stays within the live buffer
+        let entries = history.since(History::now() - 60).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].id, 2);
+    }
+
+    #[test]
+    fn test_writer_flushes_on_drop() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("history.json");
+
+        let writer = HistoryWriter::new(History {
+            path: path.clone(),
+            store: Box::new(JsonStore { path: path.clone() }),
+            entries: VecDeque::new(),
+            retention: Vec::new(),
+            limit: 100,
+        });
+        for i in 0..3 {
+            writer.add(create_test_entry(i, "app", &format!("summary {}", i)));
+        }
+        // Fewer entries than a batch: only the drop flush writes them out
+        drop(writer);
+
+        let entries = History::load_from_json(&path).unwrap();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].id, 0);
+        assert_eq!(entries[2].id, 2);
+    }
+
+    #[test]
+    fn test_sqlite_round_trip() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("history.sqlite3");
+
+        let mut history = History {
+            store: Box::new(SqliteStore::open(&path).unwrap()),
+            path: path.clone(),
+            entries: VecDeque::new(),
+            retention: Vec::new(),
+            limit: 3,
+        };
+
+        for i in 0..5 {
+            history
+                .add(create_test_entry(i, "app", &format!("summary {}", i)))
+                .unwrap();
+        }
+        assert_eq!(history.len(), 3);
+
+        // The limit-pruned rows survive a reopen
+        let mut store = SqliteStore::open(&path).unwrap();
+        let entries = store.load().unwrap();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].id, 2);
+        assert_eq!(entries[2].id, 4);
+        assert_eq!(entries[0].content_hash, history.all()[0].content_hash);
+    }
+
+    #[test]
+    fn test_binary_round_trip() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("history.bin");
+
+        let mut history = History {
+            path: path.clone(),
+            store: Box::new(BinaryStore { path: path.clone() }),
+            entries: VecDeque::new(),
+            retention: Vec::new(),
+            limit: 3,
+        };
+
+        for i in 0..5 {
+            history
+                .add(create_test_entry(i, "app", &format!("summary {}", i)))
+                .unwrap();
+        }
+        assert_eq!(history.len(), 3);
+
+        let entries = BinaryStore::read(&path).unwrap();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].id, 2);
+        assert_eq!(entries[2].id, 4);
+        assert_eq!(entries[0].content_hash, history.all()[0].content_hash);
+
+        // The export escape hatch round-trips through the schema module
+        let exported: Vec<HistoryEntry> =
+            crate::schema::from_json(&history.export_json().unwrap()).unwrap();
+        assert_eq!(exported.len(), 3);
+        assert_eq!(exported[0].summary, "summary 2");
+    }
+
+    #[test]
+    fn test_memory_store() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("history.json");
+
+        let mut history = History {
+            path: path.clone(),
+            store: Box::new(MemoryStore::default()),
+            entries: VecDeque::new(),
+            retention: Vec::new(),
+            limit: 100,
+        };
+        for i in 0..3 {
+            history
+                .add(create_test_entry(i, "app", &format!("summary {}", i)))
+                .unwrap();
+        }
+
+        assert_eq!(history.len(), 3);
+        assert_eq!(history.search("summary 1").len(), 1);
+        // Nothing reaches the filesystem
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_sqlite_full_text_search() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("history.sqlite3");
+
+        let mut history = History {
+            store: Box::new(SqliteStore::open(&path).unwrap()),
+            path,
+            entries: VecDeque::new(),
+            retention: Vec::new(),
+            limit: 100,
+        };
+
+        history
+            .add(create_test_entry(1, "firefox", "Download complete"))
+            .unwrap();
+        history
+            .add(create_test_entry(2, "slack", "New message from Dave"))
+            .unwrap();
+        history
+            .add(create_test_entry(3, "firefox", "Page loaded"))
+            .unwrap();
+
+        // Prefix matching
+        let results = history.search("down*");
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].id, 1);
+
+        // Phrase queries
+        let results = history.search("\"new message\"");
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].app_name, "slack");
+
+        // Multiple terms combine with an implicit AND
+        let results = history.search("message dave");
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].id, 2);
+    }
 }
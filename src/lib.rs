@@ -20,128 +20,483 @@ pub mod notification;
 /// Command-line interface.
 pub mod cli;
 
+/// Daemon control client.
+pub mod ctl;
+
 /// Persistent notification history.
 pub mod history;
 
-use crate::config::Config;
+/// GNTP network listener.
+pub mod gntp;
+
+/// Notification sounds.
+pub mod sound;
+
+/// Console output sink.
+pub mod console;
+
+/// Color themes.
+pub mod theme;
+
+/// Bounded command executor.
+pub mod executor;
+
+/// Notification expiry timer.
+pub mod timer;
+
+/// Popup render thread.
+pub mod render;
+
+/// Shared async runtime.
+pub mod runtime;
+
+/// C embedding interface.
+pub mod ffi;
+
+/// Versioned JSON export model.
+pub mod schema;
+
+/// Markdown body rendering.
+pub mod markdown;
+
+use crate::config::{Config, ConfigOverrides, FullscreenMode, OverflowPolicy};
 use crate::error::Result;
-use crate::history::{DEFAULT_HISTORY_LIMIT, History, HistoryEntry};
+use crate::history::{DEFAULT_HISTORY_LIMIT, HistoryBackend, HistoryEntry, HistoryWriter};
 use crate::notification::Action;
-use crate::x11::X11;
+use crate::render::{Backend, RenderRequest, Renderer};
+use crate::timer::ExpiryTimer;
+use crate::x11::{X11, X11Backend};
 use estimated_read_time::Options;
 use log::{debug, info, trace};
-use notification::{Manager, Notification, Urgency};
+use notification::{Manager, Notification, NotificationEvent, Urgency};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::{AtomicI32, Ordering};
 use std::sync::mpsc;
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, RwLock};
 use std::thread;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use tokio::sync::mpsc as tokio_mpsc;
 
-/// Runs `runst`.
-pub fn run() -> Result<()> {
-    let config = Arc::new(Config::parse()?);
-
-    // Initialize core-log with the configured log level
-    core_log::CoreLogger::init_with_filter(config.global.log_verbosity);
-    trace!("{:#?}", config);
-    info!("starting runst with zbus");
-
-    // Initialize history storage
-    let history = Arc::new(Mutex::new(History::new(DEFAULT_HISTORY_LIMIT)?));
-    info!(
-        "history storage initialized with {} entries",
-        history.lock().expect("history lock").len()
-    );
-
-    let mut x11 = X11::init(None)?;
-    let window = x11.create_window(&config.global)?;
-
-    let x11 = Arc::new(x11);
-    let window = Arc::new(window);
-    let notifications = Manager::init();
-
-    let (sender, receiver) = mpsc::channel();
-
-    // Spawn X11 event handler thread
-    let x11_cloned = Arc::clone(&x11);
-    let window_cloned = Arc::clone(&window);
-    let config_cloned = Arc::clone(&config);
-    let notifications_cloned = notifications.clone();
-    let sender_cloned = sender.clone();
-
-    thread::spawn(move || {
-        if let Err(e) = x11_cloned.handle_events(
-            window_cloned,
-            notifications_cloned,
-            config_cloned,
-            move |clicked_notifications, clicked_idx, invoke_action| {
-                // Handle the specific clicked notification, or first if click location unknown
-                let notification = clicked_idx
-                    .and_then(|idx| clicked_notifications.get(idx))
-                    .or_else(|| clicked_notifications.first());
-
-                if let Some(notification) = notification {
-                    debug!(
-                        "user clicked - handling notification id={} app={} (clicked_idx={:?}, invoke={})",
-                        notification.id, notification.app_name, clicked_idx, invoke_action
-                    );
+/// Returns a set of sample notifications (one per urgency) for previewing.
+fn sample_notifications() -> Vec<Notification> {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    [
+        (1, Urgency::Low, "low urgency", "background chatter"),
+        (2, Urgency::Normal, "normal urgency", "a regular notification"),
+        (3, Urgency::Critical, "critical urgency", "something is on fire"),
+    ]
+    .into_iter()
+    .map(|(id, urgency, summary, body)| Notification {
+        id,
+        app_name: env!("CARGO_PKG_NAME").to_string(),
+        summary: summary.to_string(),
+        body: body.to_string(),
+        expire_timeout: None,
+        urgency,
+        is_read: false,
+        timestamp,
+        actions: Vec::new(),
+        deadline: None,
+        repeats: 0,
+        sound: None,
+        suppress_sound: false,
+        icon: String::new(),
+        category: None,
+        value: None,
+        hints: HashMap::new(),
+        image: None,
+    })
+    .collect()
+}
+
+/// Runs the live configuration preview mode.
+///
+/// Renders sample notifications with the given configuration and reloads
+/// the window whenever the file is saved, for quick theming iteration.
+pub fn preview(config_path: &Path) -> Result<()> {
+    core_log::CoreLogger::init_with_filter(log::LevelFilter::Info);
+    info!("previewing configuration from {}", config_path.display());
+    let mut last_modified = fs::metadata(config_path)?.modified()?;
+
+    // Waits until the configuration file is modified again.
+    let wait_for_change = |last_modified: &mut SystemTime| -> Result<()> {
+        loop {
+            thread::sleep(Duration::from_millis(250));
+            let modified = fs::metadata(config_path)?.modified()?;
+            if modified != *last_modified {
+                *last_modified = modified;
+                info!("configuration changed, reloading");
+                return Ok(());
+            }
+        }
+    };
+
+    loop {
+        let config = match Config::load(config_path) {
+            Ok(config) => config,
+            Err(e) => {
+                eprintln!("Failed to load configuration: {e}");
+                wait_for_change(&mut last_modified)?;
+                continue;
+            }
+        };
+        let mut x11 = X11::init(None)?;
+        let window = match x11.create_window(&config.global) {
+            Ok(window) => window,
+            Err(e) => {
+                eprintln!("Failed to create window: {e}");
+                wait_for_change(&mut last_modified)?;
+                continue;
+            }
+        };
+        let notifications = Manager::init();
+        for notification in sample_notifications() {
+            notifications.add(notification);
+        }
+        x11.show_window(&window, &config.animation)?;
+        loop {
+            x11.handle_pending_events(&window, &notifications, &config)?;
+            thread::sleep(Duration::from_millis(250));
+            let modified = fs::metadata(config_path)?.modified()?;
+            if modified != last_modified {
+                last_modified = modified;
+                info!("configuration changed, reloading");
+                break;
+            }
+        }
+        x11.hide_window(&window)?;
+    }
+}
+
+/// `NotificationClosed` reason code for a dismissal by the user.
+const CLOSE_REASON_DISMISSED: u32 = 2;
 
-                    // Only invoke action if not clicking the close button
-                    if invoke_action {
-                        // Actions are [key, label, key, label, ...]
-                        // Look for "default" action first, otherwise use first action
-                        let action_key = if notification.actions.contains(&"default".to_string()) {
-                            Some("default".to_string())
+/// How often the D-Bus task verifies it still owns the well-known name.
+const NAME_WATCHDOG_INTERVAL: Duration = Duration::from_secs(30);
+
+/// A D-Bus signal queued for the server thread to emit.
+enum BusSignal {
+    /// `ActionInvoked` with the action key a user picked.
+    ActionInvoked(u32, String),
+    /// `NotificationClosed` with the spec's close reason code.
+    NotificationClosed(u32, u32),
+    /// `HistoryAdded` on the control interface with the entry as JSON.
+    HistoryAdded(String),
+}
+
+/// The popup display stack, absent in headless mode.
+struct Display {
+    /// X11 connection, shared with the helper threads.
+    x11: Arc<X11>,
+    /// The popup window.
+    window: Arc<x11::X11Window>,
+    /// Handle to the render thread.
+    renderer: Renderer,
+    /// Handle to the auto-clear timer thread.
+    expiry_timer: ExpiryTimer,
+}
+
+impl Display {
+    /// Connects to the X server and spawns the window, render, input and
+    /// expiry threads around the connection.
+    ///
+    /// When the input thread loses the connection it queues
+    /// [`Action::RecreateDisplay`], so the main loop can rebuild the
+    /// whole stack instead of exiting.
+    fn connect(
+        config: &Arc<RwLock<Config>>,
+        notifications: &Manager,
+        sender: &mpsc::Sender<Action>,
+    ) -> Result<Self> {
+        let mut x11 = X11::init(None)?;
+        let window = x11.create_window(&config.read().expect("config lock").global)?;
+
+        let x11 = Arc::new(x11);
+        let window = Arc::new(window);
+
+        // The backend hides the windowing system behind the render trait
+        let backend: Arc<dyn Backend> =
+            Arc::new(X11Backend::new(Arc::clone(&x11), Arc::clone(&window)));
+
+        // All drawing happens on this thread; the main loop and the input
+        // thread only queue requests
+        let renderer = Renderer::spawn(
+            notifications.clone(),
+            Arc::clone(&backend),
+            Arc::clone(config),
+        );
+
+        // Spawn the backend input event thread
+        let backend_cloned = Arc::clone(&backend);
+        let config_cloned = Arc::clone(config);
+        let notifications_cloned = notifications.clone();
+        let sender_cloned = sender.clone();
+        let sender_for_menu = sender.clone();
+        let sender_for_reconnect = sender.clone();
+        let renderer_for_events = renderer.clone();
+
+        thread::spawn(move || {
+            if let Err(e) = backend_cloned.handle_input(
+                notifications_cloned,
+                config_cloned,
+                sender_for_menu,
+                renderer_for_events,
+                Box::new(move |clicked_notifications, clicked_idx, invoke_action| {
+                    // Handle the specific clicked notification, or first if click location unknown
+                    let notification = clicked_idx
+                        .and_then(|idx| clicked_notifications.get(idx))
+                        .or_else(|| clicked_notifications.first());
+
+                    if let Some(notification) = notification {
+                        debug!(
+                            "user clicked - handling notification id={} app={} (clicked_idx={:?}, invoke={})",
+                            notification.id, notification.app_name, clicked_idx, invoke_action
+                        );
+
+                        // Only invoke action if not clicking the close button
+                        if invoke_action {
+                            // Actions are [key, label, key, label, ...]
+                            // Look for "default" action first, otherwise use first action
+                            let action_key = if notification.actions.contains(&"default".to_string()) {
+                                Some("default".to_string())
+                            } else {
+                                notification.actions.first().cloned()
+                            };
+                            if let Some(key) = action_key {
+                                debug!("invoking action '{}' for notification {}", key, notification.id);
+                                sender_cloned
+                                    .send(Action::Invoke(notification.id, key))
+                                    .expect("failed to send invoke action");
+                            }
                         } else {
-                            notification.actions.first().cloned()
-                        };
-                        if let Some(key) = action_key {
-                            debug!("invoking action '{}' for notification {}", key, notification.id);
-                            sender_cloned
-                                .send(Action::Invoke(notification.id, key))
-                                .expect("failed to send invoke action");
+                            debug!("close button clicked - not invoking action");
                         }
-                    } else {
-                        debug!("close button clicked - not invoking action");
+
+                        // Close this notification
+                        sender_cloned
+                            .send(Action::Close(Some(notification.id)))
+                            .expect("failed to send close action");
                     }
+                }),
+            ) {
+                eprintln!("Failed to handle input events: {e}");
+                // The X connection is most likely gone (server restart or
+                // logout); ask the main loop to rebuild the display stack
+                let _ = sender_for_reconnect.send(Action::RecreateDisplay);
+            }
+        });
 
-                    // Close this notification
-                    sender_cloned
-                        .send(Action::Close(Some(notification.id)))
-                        .expect("failed to send close action");
-                }
-            },
-        ) {
-            eprintln!("Failed to handle X11 events: {e}")
+        // One thread serves all auto-clear deadlines, waking exactly when
+        // the next one is due
+        let expiry_timer = ExpiryTimer::spawn(
+            notifications.clone(),
+            Arc::clone(&x11),
+            Arc::clone(&window),
+            Arc::clone(config),
+            sender.clone(),
+        );
+
+        Ok(Self {
+            x11,
+            window,
+            renderer,
+            expiry_timer,
+        })
+    }
+}
+
+impl Drop for Display {
+    fn drop(&mut self) {
+        // The expiry thread never notices a dead connection on its own;
+        // stop it explicitly so display rebuilds don't leak it
+        self.expiry_timer.shutdown();
+    }
+}
+
+/// Builder for an embedded [`Daemon`].
+///
+/// All settings are optional: by default the daemon behaves exactly like
+/// the `runst` binary, reading the global configuration files.
+#[derive(Default)]
+pub struct DaemonBuilder {
+    /// Command-line style configuration overrides.
+    overrides: ConfigOverrides,
+    /// Programmatically supplied configuration, bypassing the files.
+    config: Option<Config>,
+    /// History backend override.
+    history_backend: Option<HistoryBackend>,
+    /// History entry limit override.
+    history_limit: Option<usize>,
+}
+
+impl DaemonBuilder {
+    /// Sets the configuration overrides (path, origin, geometry, font,
+    /// display limit), as taken from the command line.
+    pub fn overrides(mut self, overrides: ConfigOverrides) -> Self {
+        self.overrides = overrides;
+        self
+    }
+
+    /// Supplies a configuration directly instead of reading the global
+    /// files; file watching and runtime reloads are disabled.
+    pub fn config(mut self, config: Config) -> Self {
+        self.config = Some(config);
+        self
+    }
+
+    /// Overrides the history storage backend from the configuration.
+    pub fn history_backend(mut self, backend: HistoryBackend) -> Self {
+        self.history_backend = Some(backend);
+        self
+    }
+
+    /// Caps the number of entries kept in history storage.
+    pub fn history_limit(mut self, limit: usize) -> Self {
+        self.history_limit = Some(limit);
+        self
+    }
+
+    /// Builds the daemon, creating its action channel and notification
+    /// buffer.
+    pub fn build(self) -> Daemon {
+        let (sender, receiver) = mpsc::channel();
+        Daemon {
+            overrides: self.overrides,
+            config: self.config,
+            history_backend: self.history_backend,
+            history_limit: self.history_limit.unwrap_or(DEFAULT_HISTORY_LIMIT),
+            notifications: Manager::init(),
+            sender,
+            receiver,
         }
-    });
+    }
+}
+
+/// An embeddable notification daemon instance.
+///
+/// [`run`] wraps this for the `runst` binary; a host program can instead
+/// assemble a daemon with [`Daemon::builder`], keep a [`Daemon::sender`]
+/// handle for posting [`Action`]s, and drive the event loop from a
+/// thread of its own with [`Daemon::run`].
+pub struct Daemon {
+    /// Configuration overrides from the builder.
+    overrides: ConfigOverrides,
+    /// Supplied configuration, used instead of the files when set.
+    config: Option<Config>,
+    /// History backend override from the builder.
+    history_backend: Option<HistoryBackend>,
+    /// History entry limit.
+    history_limit: usize,
+    /// The notification buffer, shared out via [`Daemon::manager`].
+    notifications: Manager,
+    /// Sending side of the action channel, cloned out via [`Daemon::sender`].
+    sender: mpsc::Sender<Action>,
+    /// Receiving side of the action channel, served by [`Daemon::run`].
+    receiver: mpsc::Receiver<Action>,
+}
+
+impl Daemon {
+    /// Returns a builder with the default (binary-equivalent) settings.
+    pub fn builder() -> DaemonBuilder {
+        DaemonBuilder::default()
+    }
+
+    /// Returns a handle for posting actions into the daemon's event loop,
+    /// usable before and while [`Daemon::run`] executes.
+    pub fn sender(&self) -> mpsc::Sender<Action> {
+        self.sender.clone()
+    }
+
+    /// Returns a handle to the notification buffer, usable before and
+    /// while [`Daemon::run`] executes — e.g. to [`Manager::subscribe`] to
+    /// lifecycle events.
+    pub fn manager(&self) -> Manager {
+        self.notifications.clone()
+    }
+
+    /// Runs the daemon until its action channel closes.
+    pub fn run(self) -> Result<()> {
+        let Self {
+            overrides,
+            config: supplied_config,
+            history_backend,
+            history_limit,
+            notifications,
+            sender,
+            receiver,
+        } = self;
+        let supplied = supplied_config.is_some();
+        let config = Arc::new(RwLock::new(match supplied_config {
+            Some(config) => config,
+            None => overrides.load()?,
+        }));
 
-    // Create channel for action invocations (to emit D-Bus signals)
-    let (invoke_tx, mut invoke_rx) = tokio_mpsc::unbounded_channel::<(u32, String)>();
-    let invoke_sender = Arc::new(invoke_tx);
+        // Initialize core-log with the configured log level
+        core_log::CoreLogger::init_with_filter(config.read().expect("config lock").global.log_verbosity);
+        trace!("{:#?}", config.read().expect("config lock"));
+        info!("starting runst with zbus");
 
-    // Spawn zbus D-Bus server thread
-    let sender_for_zbus = sender.clone();
-    thread::spawn(move || {
-        debug!("starting Z-Bus server thread");
+        // Initialize the bounded executor for custom commands
+        executor::CommandExecutor::init(&config.read().expect("config lock").commands);
+
+        // Initialize history storage; loading, retention setup and compaction
+        // happen on the writer thread so startup does not wait on disk
+        let mut history_config = config.read().expect("config lock").history.clone();
+        if let Some(backend) = history_backend {
+            history_config.backend = backend;
+        }
+        let history = HistoryWriter::new_deferred(history_limit, history_config);
+
+        executor::CommandExecutor::global().set_notifier(sender.clone());
+
+        // Create channel for D-Bus signals emitted outside a method call
+        let (signal_tx, mut signal_rx) = tokio_mpsc::unbounded_channel::<BusSignal>();
+        let signal_sender = Arc::new(signal_tx);
+
+        // Relay buffer dismissals so every close path emits the
+        // NotificationClosed signal, not just the CloseNotification
+        // method; the buffer events carry no close reason, so the spec's
+        // "dismissed by the user" code stands in for all of them
+        let events = notifications.subscribe();
+        let signal_for_events = Arc::clone(&signal_sender);
+        thread::spawn(move || {
+            while let Ok(event) = events.recv() {
+                if let NotificationEvent::Closed(id) = event
+                    && signal_for_events
+                        .send(BusSignal::NotificationClosed(id, CLOSE_REASON_DISMISSED))
+                        .is_err()
+                {
+                    break;
+                }
+            }
+        });
+
+        // Start the D-Bus server on the shared runtime before the X11
+        // handshake so bus name acquisition and the X11 connection proceed
+        // in parallel; early notifications queue on the channel until the
+        // main loop starts. The control interface's window arrives over
+        // `window_tx` once it exists (never, when running headless).
+        let (window_tx, window_rx) = tokio::sync::oneshot::channel::<Arc<x11::X11Window>>();
+        let sender_for_zbus = sender.clone();
+        let notifications_for_zbus = notifications.clone();
+        let config_for_zbus = Arc::clone(&config);
+        let replace = config.read().expect("config lock").global.replace;
+        runtime::spawn(async move {
+            use zbus::fdo::{RequestNameFlags, RequestNameReply};
+
+            debug!("starting D-Bus server task");
 
-        let rt = tokio::runtime::Runtime::new().expect("Failed to create tokio runtime");
-        rt.block_on(async {
             let notifications = zbus_handler::Notifications::new(sender_for_zbus.clone());
-            let control = zbus_handler::NotificationControl::new(sender_for_zbus);
 
             match zbus::connection::Builder::session() {
-                Ok(mut builder) => {
-                    // Request the well-known name
-                    builder = match builder.name("org.freedesktop.Notifications") {
-                        Ok(b) => b,
-                        Err(e) => {
-                            eprintln!("Failed to request name: {}", e);
-                            return;
-                        }
-                    };
-
-                    // Build the connection
+                Ok(builder) => {
+                    // Build the connection; the well-known name is requested
+                    // below, after any takeover of a previous instance
                     match builder.build().await {
                         Ok(connection) => {
                             // Serve the notifications interface
@@ -154,6 +509,86 @@ pub fn run() -> Result<()> {
                                 return;
                             }
 
+                            // Ask a previous runst for its unread buffer
+                            // before taking the name from under it
+                            if replace {
+                                let snapshot = connection
+                                    .call_method(
+                                        Some("org.freedesktop.Notifications"),
+                                        "/org/freedesktop/Notifications/ctl",
+                                        Some("org.freedesktop.NotificationControl"),
+                                        "ExportUnread",
+                                        &(),
+                                    )
+                                    .await
+                                    .and_then(|reply| Ok(reply.body().deserialize::<String>()?));
+                                match snapshot {
+                                    Ok(snapshot) => {
+                                        match schema::from_json::<Vec<Notification>>(&snapshot) {
+                                            Ok(unread) => {
+                                                info!(
+                                                    "taking over {} unread notifications",
+                                                    unread.len()
+                                                );
+                                                for notification in unread {
+                                                    let _ = sender_for_zbus
+                                                        .send(Action::Show(notification));
+                                                }
+                                            }
+                                            Err(e) => log::warn!(
+                                                "invalid unread snapshot from previous instance: {}",
+                                                e
+                                            ),
+                                        }
+                                    }
+                                    Err(e) => {
+                                        debug!("no previous instance to take over from: {}", e);
+                                    }
+                                }
+                            }
+
+                            // Request the well-known name. Always allowing
+                            // replacement lets a future --replace take over
+                            // from this instance in turn.
+                            let mut flags =
+                                RequestNameFlags::AllowReplacement | RequestNameFlags::DoNotQueue;
+                            if replace {
+                                flags |= RequestNameFlags::ReplaceExisting;
+                            }
+                            match connection
+                                .request_name_with_flags("org.freedesktop.Notifications", flags)
+                                .await
+                            {
+                                Ok(
+                                    RequestNameReply::PrimaryOwner | RequestNameReply::AlreadyOwner,
+                                ) => {}
+                                Ok(_) => {
+                                    eprintln!(
+                                        "Failed to request name: another daemon owns it (use --replace to take over)"
+                                    );
+                                    return;
+                                }
+                                Err(e) => {
+                                    eprintln!("Failed to request name: {}", e);
+                                    return;
+                                }
+                            }
+
+                            // Wait for the main thread to finish the X11
+                            // handshake; headless mode drops the channel
+                            // and the control interface runs without a
+                            // window (layout queries report an error)
+                            let window = window_rx.await.ok();
+                            if window.is_none() {
+                                debug!("no window; serving the control interface without layout");
+                            }
+                            let control = zbus_handler::NotificationControl::new(
+                                sender_for_zbus.clone(),
+                                notifications_for_zbus,
+                                window,
+                                config_for_zbus,
+                            );
+
                             // Serve the control interface
                             if let Err(e) = connection
                                 .object_server()
@@ -166,24 +601,132 @@ pub fn run() -> Result<()> {
 
                             info!("Z-Bus server is running");
 
-                            // Listen for action invocations and emit signals
-                            while let Some((id, action_key)) = invoke_rx.recv().await {
-                                debug!(
-                                    "emitting ActionInvoked signal: id={}, action={}",
-                                    id, action_key
-                                );
-                                // Emit ActionInvoked signal directly
-                                if let Err(e) = connection
-                                    .emit_signal(
-                                        None::<&str>,
-                                        "/org/freedesktop/Notifications",
-                                        "org.freedesktop.Notifications",
-                                        "ActionInvoked",
-                                        &(id, &action_key),
-                                    )
-                                    .await
-                                {
-                                    log::warn!("failed to emit ActionInvoked signal: {}", e);
+                            // The watchdog below compares the name's owner
+                            // against our unique connection name
+                            let our_name = connection
+                                .unique_name()
+                                .map(|name| name.to_string())
+                                .unwrap_or_default();
+                            let watchdog = zbus::fdo::DBusProxy::new(&connection).await.ok();
+                            let bus_name = zbus::names::BusName::from_static_str(
+                                "org.freedesktop.Notifications",
+                            )
+                            .expect("valid bus name");
+                            let mut owned = true;
+                            let mut interval = tokio::time::interval(NAME_WATCHDOG_INTERVAL);
+
+                            // Emit queued signals, periodically verifying the
+                            // well-known name is still ours
+                            loop {
+                                let signal = tokio::select! {
+                                    signal = signal_rx.recv() => {
+                                        let Some(signal) = signal else { break };
+                                        signal
+                                    }
+                                    _ = interval.tick() => {
+                                        let Some(watchdog) = &watchdog else { continue };
+                                        let currently_owned = match watchdog
+                                            .get_name_owner(bus_name.clone())
+                                            .await
+                                        {
+                                            Ok(owner) => owner.as_str() == our_name,
+                                            // Nobody owns the name, or the
+                                            // bus itself is unreachable
+                                            Err(_) => false,
+                                        };
+                                        if owned && !currently_owned {
+                                            log::warn!(
+                                                "lost the notification bus name; new notifications go elsewhere until it is reacquired"
+                                            );
+                                            // Make the degradation visible
+                                            // instead of failing silently
+                                            let warning = Notification::builder()
+                                                .app_name(env!("CARGO_PKG_NAME"))
+                                                .summary("notification service lost")
+                                                .body(
+                                                    "another daemon took over org.freedesktop.Notifications; \
+                                                     runst keeps retrying to get it back",
+                                                )
+                                                .urgency(Urgency::Critical)
+                                                .build();
+                                            let _ = sender_for_zbus.send(Action::Show(warning));
+                                            owned = false;
+                                        }
+                                        if !owned {
+                                            // Succeeds once the usurper exits
+                                            // or releases the name
+                                            match connection
+                                                .request_name_with_flags(
+                                                    "org.freedesktop.Notifications",
+                                                    RequestNameFlags::AllowReplacement
+                                                        | RequestNameFlags::DoNotQueue,
+                                                )
+                                                .await
+                                            {
+                                                Ok(
+                                                    RequestNameReply::PrimaryOwner
+                                                    | RequestNameReply::AlreadyOwner,
+                                                ) => {
+                                                    info!("reacquired the notification bus name");
+                                                    owned = true;
+                                                }
+                                                Ok(_) => {
+                                                    debug!("notification bus name is still owned elsewhere");
+                                                }
+                                                Err(e) => {
+                                                    debug!("could not reacquire the bus name: {}", e);
+                                                }
+                                            }
+                                        }
+                                        continue;
+                                    }
+                                };
+                                let result = match signal {
+                                    BusSignal::ActionInvoked(id, action_key) => {
+                                        debug!(
+                                            "emitting ActionInvoked signal: id={}, action={}",
+                                            id, action_key
+                                        );
+                                        connection
+                                            .emit_signal(
+                                                None::<&str>,
+                                                "/org/freedesktop/Notifications",
+                                                "org.freedesktop.Notifications",
+                                                "ActionInvoked",
+                                                &(id, &action_key),
+                                            )
+                                            .await
+                                    }
+                                    BusSignal::NotificationClosed(id, reason) => {
+                                        debug!(
+                                            "emitting NotificationClosed signal: id={}, reason={}",
+                                            id, reason
+                                        );
+                                        connection
+                                            .emit_signal(
+                                                None::<&str>,
+                                                "/org/freedesktop/Notifications",
+                                                "org.freedesktop.Notifications",
+                                                "NotificationClosed",
+                                                &(id, reason),
+                                            )
+                                            .await
+                                    }
+                                    BusSignal::HistoryAdded(entry) => {
+                                        debug!("emitting HistoryAdded signal");
+                                        connection
+                                            .emit_signal(
+                                                None::<&str>,
+                                                "/org/freedesktop/Notifications/ctl",
+                                                "org.freedesktop.NotificationControl",
+                                                "HistoryAdded",
+                                                &(&entry,),
+                                            )
+                                            .await
+                                    }
+                                };
+                                if let Err(e) = result {
+                                    log::warn!("failed to emit D-Bus signal: {}", e);
                                 }
                             }
                         }
@@ -197,133 +740,656 @@ pub fn run() -> Result<()> {
                 }
             }
         });
-    });
-
-    // Small delay to let D-Bus server start
-    thread::sleep(Duration::from_millis(100));
-
-    if config.global.startup_notification {
-        let startup_notification = Notification {
-            id: 0,
-            app_name: env!("CARGO_PKG_NAME").to_string(),
-            summary: "startup".to_string(),
-            body: concat!(env!("CARGO_PKG_NAME"), " is up and running 🦡").to_string(),
-            expire_timeout: Some(Duration::from_secs(3)),
-            urgency: Urgency::Normal,
-            is_read: false,
-            timestamp: SystemTime::now()
-                .duration_since(UNIX_EPOCH)
-                .map(|d| d.as_secs())
-                .unwrap_or(0),
-            actions: Vec::new(),
+
+        // Headless mode skips the whole display stack: no X11 connection,
+        // no window, no render or input threads. Everything upstream of
+        // the popup (history, rules, mutes, hooks, GNTP) still runs.
+        let headless = config.read().expect("config lock").global.headless;
+        let mut display = if headless {
+            info!("running headless: notifications are recorded but not displayed");
+            // Dropping the channel tells the D-Bus task there is no
+            // window; the control interface is served windowless
+            drop(window_tx);
+            None
+        } else {
+            // A startup failure is still fatal; reconnection only covers a
+            // connection dying later
+            let display = Display::connect(&config, &notifications, &sender)?;
+
+            // Hand the window to the D-Bus task for the control interface
+            if window_tx.send(Arc::clone(&display.window)).is_err() {
+                log::warn!("D-Bus server task is not running");
+            }
+
+            Some(display)
         };
-        sender.send(Action::Show(startup_notification))?;
-    }
+        // Backoff between display reconnection attempts, reset on success
+        let mut reconnect_delay = Duration::from_secs(1);
 
-    let x11_cloned = Arc::clone(&x11);
-    loop {
-        match receiver.recv()? {
-            Action::Show(notification) => {
-                info!(
-                    "notification received: id={} app=\"{}\" urgency={} timeout={:?} summary=\"{}\" body=\"{}\"",
-                    notification.id,
-                    notification.app_name,
-                    notification.urgency,
-                    notification.expire_timeout,
-                    notification.summary,
-                    notification.body.replace('\n', "\\n")
-                );
-
-                // Save to persistent history
-                {
-                    let entry = HistoryEntry::new(
-                        notification.id,
-                        notification.app_name.clone(),
-                        notification.summary.clone(),
-                        notification.body.clone(),
-                        &notification.urgency,
-                        notification.timestamp,
-                    );
-                    if let Ok(mut hist) = history.lock()
-                        && let Err(e) = hist.add(entry)
+        // Watch the configuration file and reload on changes; a supplied
+        // configuration has no file to watch
+        if !supplied && let Some(config_path) = overrides.config.clone().or_else(Config::path) {
+            let sender_cloned = sender.clone();
+            thread::spawn(move || {
+                use notify::{RecursiveMode, Watcher};
+                let (tx, rx) = mpsc::channel();
+                let mut watcher = match notify::recommended_watcher(tx) {
+                    Ok(watcher) => watcher,
+                    Err(e) => {
+                        log::warn!("failed to create config watcher: {}", e);
+                        return;
+                    }
+                };
+                // Watch the parent directory since editors often replace the file
+                let watch_dir = config_path
+                    .parent()
+                    .map(Path::to_path_buf)
+                    .unwrap_or_else(|| config_path.clone());
+                if let Err(e) = watcher.watch(&watch_dir, RecursiveMode::NonRecursive) {
+                    log::warn!("failed to watch {}: {}", watch_dir.display(), e);
+                    return;
+                }
+                debug!("watching configuration file {}", config_path.display());
+                let mut last_reload = std::time::Instant::now();
+                for event in rx.into_iter().flatten() {
+                    let file_name = config_path.file_name().unwrap_or_default();
+                    if event.paths.iter().any(|p| p.file_name() == Some(file_name))
+                        && last_reload.elapsed() >= Duration::from_millis(300)
                     {
-                        log::warn!("failed to save notification to history: {}", e);
+                        last_reload = std::time::Instant::now();
+                        if sender_cloned.send(Action::ReloadConfig).is_err() {
+                            break;
+                        }
                     }
                 }
+            });
+        }
+
+        // Re-evaluate the quiet-hours schedule periodically so popups
+        // clear when quiet hours begin, not only on the next arrival
+        if !config.read().expect("config lock").global.quiet_hours.is_empty() {
+            let sender_cloned = sender.clone();
+            thread::spawn(move || {
+                loop {
+                    thread::sleep(Duration::from_secs(60));
+                    if sender_cloned.send(Action::EvaluateSchedule).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+
+        // Start the GNTP listener if enabled
+        gntp::spawn(
+            config.read().expect("config lock").gntp.clone(),
+            sender.clone(),
+        );
+
+        // Small delay to let D-Bus server start
+        thread::sleep(Duration::from_millis(100));
+
+        if config.read().expect("config lock").global.startup_notification {
+            let startup_notification = Notification {
+                id: 0,
+                app_name: env!("CARGO_PKG_NAME").to_string(),
+                summary: "startup".to_string(),
+                body: concat!(env!("CARGO_PKG_NAME"), " is up and running 🦡").to_string(),
+                expire_timeout: Some(Duration::from_secs(3)),
+                urgency: Urgency::Normal,
+                is_read: false,
+                timestamp: SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|d| d.as_secs())
+                    .unwrap_or(0),
+                actions: Vec::new(),
+                deadline: None,
+                repeats: 0,
+                sound: None,
+                suppress_sound: false,
+                icon: String::new(),
+                category: None,
+                value: None,
+                hints: HashMap::new(),
+                image: None,
+            };
+            sender.send(Action::Show(startup_notification))?;
+        }
+
+        // Build the console sink if enabled
+        let build_console = |config: &Config| -> Option<console::ConsoleSink> {
+            if !config.console.enabled {
+                return None;
+            }
+            match console::ConsoleSink::new(&config.console) {
+                Ok(sink) => Some(sink),
+                Err(e) => {
+                    log::warn!("failed to build console sink: {}", e);
+                    None
+                }
+            }
+        };
+        let mut console_sink = build_console(&config.read().expect("config lock"));
 
-                let timeout = notification.expire_timeout.unwrap_or_else(|| {
-                    let urgency_config = config.get_urgency_config(&notification.urgency);
-                    Duration::from_secs(if urgency_config.auto_clear.unwrap_or(false) {
-                        notification
-                            .render_message(&window.template, urgency_config.text, 0)
-                            .map(|v| estimated_read_time::text(&v, &Options::default()).seconds())
-                            .unwrap_or_default()
+        // Timestamps of recent critical notifications keyed by content hash,
+        // for the repeated-critical downgrade
+        let mut critical_repeats: HashMap<u64, Vec<u64>> = HashMap::new();
+        loop {
+            let action = receiver.recv()?;
+            // Reshow is Show minus the transform and history stages; the
+            // notification already went through them before it was snoozed
+            let reshow = matches!(action, Action::Reshow(_));
+            match action {
+                Action::Show(mut notification) | Action::Reshow(mut notification) => {
+                    // Cut pathologically large bodies down before anything
+                    // stores or lays them out
+                    let max_body_length =
+                        config.read().expect("config lock").global.max_body_length;
+                    if max_body_length > 0 && notification.body.len() > max_body_length {
+                        notification.body =
+                            notification::truncate_bytes(&notification.body, max_body_length);
+                    }
+                    // Kept pristine so a fullscreen-delayed notification can be
+                    // re-queued through the full pipeline without transforms
+                    // applying twice
+                    let incoming = notification.clone();
+                    // Apply rule overrides (urgency/timeout) before anything else
+                    let (history_ttl, history_limit_rule, rule_downgrade, rule_sound, rule_emoji) = if reshow {
+                        (None, None, None, None, None)
                     } else {
-                        urgency_config.timeout.into()
-                    })
-                });
-                if !timeout.is_zero() {
-                    debug!("notification timeout: {}ms", timeout.as_millis());
-                    let sender_cloned = sender.clone();
-                    let notifications_cloned = notifications.clone();
-                    let notification_id = notification.id;
-                    thread::spawn(move || {
-                        thread::sleep(timeout);
-                        if notifications_cloned.is_unread(notification_id) {
-                            sender_cloned
-                                .send(Action::Close(Some(notification_id)))
-                                .expect("failed to send close action");
+                        let config = config.read().expect("config lock");
+                        if let Some(rule) = config.get_matching_rule(
+                            &notification.app_name,
+                            &notification.summary,
+                            &notification.body,
+                        ) {
+                            if let Some(urgency) = &rule.urgency {
+                                debug!(
+                                    "rule overrides urgency: {} -> {}",
+                                    notification.urgency, urgency
+                                );
+                                notification.urgency = urgency.clone();
+                            }
+                            if let Some(timeout) = rule.timeout {
+                                debug!("rule overrides timeout: {}s", timeout);
+                                notification.expire_timeout =
+                                    Some(Duration::from_secs(timeout.into()));
+                            }
+                            // Text transforms apply before display and history storage
+                            rule.apply_transforms(&mut notification.summary, &mut notification.body);
+                            (
+                                rule.history_ttl(),
+                                rule.history_limit.map(|limit| (limit, rule.clone())),
+                                rule.downgrade_repeats,
+                                rule.sound.clone(),
+                                rule.expand_emoji,
+                            )
+                        } else {
+                            (None, None, None, None, None)
+                        }
+                    };
+
+                    // Expand :shortcode: emoji for webhook-style senders
+                    if !reshow
+                        && rule_emoji
+                            .unwrap_or(config.read().expect("config lock").global.expand_emoji)
+                    {
+                        notification.summary = notification::expand_shortcodes(&notification.summary);
+                        notification.body = notification::expand_shortcodes(&notification.body);
+                    }
+
+                    // Fullscreen handling per urgency: delay holds the pristine
+                    // copy back until the focused window leaves fullscreen,
+                    // suppress drops the popup after history records it below
+                    let fullscreen_mode = config
+                        .read()
+                        .expect("config lock")
+                        .get_urgency_config(&notification.urgency, &notification.app_name)
+                        .fullscreen
+                        .unwrap_or_default();
+                    let in_fullscreen = !matches!(fullscreen_mode, FullscreenMode::Show)
+                        && display
+                            .as_ref()
+                            .is_some_and(|display| display.x11.focused_window_fullscreen());
+                    if in_fullscreen
+                        && matches!(fullscreen_mode, FullscreenMode::Delay)
+                        && let Some(display) = &display
+                    {
+                        info!(
+                            "delaying notification {} until fullscreen ends",
+                            notification.id
+                        );
+                        let x11_delay = Arc::clone(&display.x11);
+                        let sender_cloned = sender.clone();
+                        thread::spawn(move || {
+                            while x11_delay.focused_window_fullscreen() {
+                                thread::sleep(Duration::from_secs(1));
+                            }
+                            // A delayed reshow stays a reshow, so the skipped
+                            // stages are not applied on the second pass either
+                            let _ = sender_cloned.send(if reshow {
+                                Action::Reshow(incoming)
+                            } else {
+                                Action::Show(incoming)
+                            });
+                        });
+                        continue;
+                    }
+
+                    // Downgrade repeated identical criticals to counter alarm
+                    // fatigue; the hash is taken before the marker is appended so
+                    // repeats keep matching each other
+                    if !reshow && matches!(notification.urgency, Urgency::Critical) {
+                        let (enabled, threshold, window_secs) = {
+                            let config = config.read().expect("config lock");
+                            (
+                                rule_downgrade.unwrap_or(config.global.downgrade_repeats),
+                                config.global.repeat_threshold,
+                                config.global.repeat_window,
+                            )
+                        };
+                        if enabled {
+                            let hash = notification.content_hash();
+                            let now = notification.timestamp;
+                            let timestamps = critical_repeats.entry(hash).or_default();
+                            timestamps.retain(|t| now.saturating_sub(*t) <= window_secs);
+                            timestamps.push(now);
+                            if timestamps.len() > threshold {
+                                debug!(
+                                    "downgrading repeated critical notification (seen {} times)",
+                                    timestamps.len()
+                                );
+                                notification.urgency = Urgency::Normal;
+                                notification.summary.push_str(" (repeated)");
+                            }
+                            // Keep the map from growing with stale content hashes
+                            critical_repeats
+                                .retain(|_, t| t.iter().any(|t| now.saturating_sub(*t) <= window_secs));
+                        }
+                    }
+                    if let Some(console_sink) = &console_sink {
+                        console_sink.print(&notification);
+                    }
+                    info!(
+                        "notification received: id={} app=\"{}\" urgency={} timeout={:?} summary=\"{}\" body=\"{}\"",
+                        notification.id,
+                        notification.app_name,
+                        notification.urgency,
+                        notification.expire_timeout,
+                        notification.summary,
+                        notification.body.replace('\n', "\\n")
+                    );
+
+                    // Save to persistent history (reshows were recorded the
+                    // first time around)
+                    if !reshow {
+                        let mut entry = HistoryEntry::new(
+                            notification.id,
+                            notification.app_name.clone(),
+                            notification.summary.clone(),
+                            notification.body.clone(),
+                            &notification.urgency,
+                            notification.timestamp,
+                        );
+                        if let Some(ttl) = history_ttl {
+                            entry.expires_at = Some(notification.timestamp + ttl.as_secs());
+                        }
+                        // Stream the entry to any `history --follow` listeners
+                        if let Ok(json) = serde_json::to_string(&entry) {
+                            let _ = signal_sender.send(BusSignal::HistoryAdded(json));
+                        }
+                        history.add(entry);
+                        if let Some((limit, rule)) = history_limit_rule {
+                            history.enforce_limit_where(limit, move |e| {
+                                rule.matches(&e.app_name, &e.summary, &e.body)
+                            });
+                        }
+                    }
+
+                    // Mutes suppress display only; history above still recorded it
+                    if notifications.is_muted(&notification.app_name) {
+                        info!("notification muted: app={}", notification.app_name);
+                        continue;
+                    }
+
+                    // Do-not-disturb likewise only suppresses the popup
+                    if config.read().expect("config lock").global.dnd {
+                        info!("notification suppressed by do-not-disturb");
+                        continue;
+                    }
+
+                    // Scheduled quiet hours queue non-criticals to history
+                    // only; criticals still break through
+                    if !matches!(notification.urgency, Urgency::Critical)
+                        && config.read().expect("config lock").global.quiet_hours_active()
+                    {
+                        info!("notification suppressed by quiet hours");
+                        continue;
+                    }
+
+                    // Same for fullscreen suppression
+                    if in_fullscreen && matches!(fullscreen_mode, FullscreenMode::Suppress) {
+                        info!("notification suppressed by fullscreen focus");
+                        continue;
+                    }
+
+                    // On-screen duplicates stack onto the displayed entry with
+                    // a bumped "×N" counter instead of adding a second one
+                    if config.read().expect("config lock").global.stack_duplicates
+                        && let Some(id) = notifications.bump_duplicate(&notification)
+                    {
+                        debug!("stacked duplicate onto displayed notification {}", id);
+                        if let Some(display) = &display {
+                            display.renderer.request(RenderRequest::Show);
+                        }
+                        continue;
+                    }
+
+                    // Play the notification sound: a rule override wins over the
+                    // sender's hints, which win over the urgency default
+                    if !notification.suppress_sound {
+                        let (sound_config, urgency_sound, volume_override) = {
+                            let config = config.read().expect("config lock");
+                            let urgency_config = config
+                                .get_urgency_config(&notification.urgency, &notification.app_name);
+                            (
+                                config.sound.clone(),
+                                urgency_config.sound,
+                                urgency_config.sound_volume,
+                            )
+                        };
+                        if let Some(name) = rule_sound
+                            .or_else(|| notification.sound.clone())
+                            .or(urgency_sound)
+                        {
+                            match sound::resolve(&name, &sound_config.theme) {
+                                Some(path) => sound::play(
+                                    path,
+                                    sound_config.effective_volume(volume_override),
+                                    sound_config.device.clone(),
+                                ),
+                                None => debug!("no sound file found for \"{}\"", name),
+                            }
                         }
+                    }
+
+                    let timeout = notification.expire_timeout.unwrap_or_else(|| {
+                        let urgency_config = config
+                            .read()
+                            .expect("config lock")
+                            .get_urgency_config(&notification.urgency, &notification.app_name);
+                        Duration::from_secs(if urgency_config.auto_clear.unwrap_or(false) {
+                            display
+                                .as_ref()
+                                .and_then(|display| {
+                                    display
+                                        .window
+                                        .render_notification(&notification, urgency_config.text, 0)
+                                        .ok()
+                                })
+                                .map(|v| estimated_read_time::text(&v, &Options::default()).seconds())
+                                .unwrap_or_default()
+                        } else {
+                            urgency_config.timeout.into()
+                        })
                     });
+                    // Criticals requiring acknowledgment never auto-clear, even
+                    // when the sender asked for a timeout
+                    let require_ack = matches!(notification.urgency, Urgency::Critical)
+                        && config.read().expect("config lock").global.critical_require_ack;
+                    if !timeout.is_zero()
+                        && !require_ack
+                        && let Some(display) = &display
+                    {
+                        debug!("notification timeout: {}ms", timeout.as_millis());
+                        // Record the auto-clear deadline for the countdown bar
+                        notification.deadline = SystemTime::now()
+                            .duration_since(UNIX_EPOCH)
+                            .ok()
+                            .map(|now| now.as_millis() as u64 + timeout.as_millis() as u64);
+                        display.expiry_timer.schedule(notification.id, timeout);
+                    }
+                    let notification_id = notification.id;
+                    notifications.add(notification);
+                    // Enforce display limit (ring buffer behavior); queueing
+                    // overflow policies keep the surplus waiting instead
+                    let (display_limit, evict, overflow, buffer_limit) = {
+                        let config = config.read().expect("config lock");
+                        (
+                            config.global.display_limit,
+                            config.global.evict,
+                            config.global.overflow,
+                            config.global.buffer_limit,
+                        )
+                    };
+                    // Keep the in-memory buffer bounded: read entries past
+                    // the cap are dropped, oldest first
+                    notifications.enforce_buffer_limit(buffer_limit);
+                    if display_limit > 0 && matches!(overflow, OverflowPolicy::Evict) {
+                        let evicted = notifications.enforce_limit(display_limit, evict);
+                        for id in evicted {
+                            debug!("evicted notification {} due to display limit", id);
+                        }
+                    }
+                    if let Some(display) = &display {
+                        // Jump back to the newest entries so the new one is visible
+                        display.window.reset_scroll();
+                        display.renderer.request(RenderRequest::Show);
+                        // Eviction may have taken the new entry straight off screen
+                        if notifications.is_unread(notification_id) {
+                            notifications.emit(NotificationEvent::Displayed(notification_id));
+                        }
+                    }
                 }
-                notifications.add(notification);
-                // Enforce display limit (ring buffer behavior)
-                let display_limit = config.global.display_limit;
-                if display_limit > 0 {
-                    let evicted = notifications.enforce_limit(display_limit);
-                    for id in evicted {
-                        debug!("evicted notification {} due to display limit", id);
+                Action::ShowLast => {
+                    debug!("showing the last notification");
+                    if notifications.count() == 0 {
+                        continue;
+                    }
+                    let restored = notifications.mark_next_as_unread();
+                    if let Some(display) = &display {
+                        display.renderer.request(if restored {
+                            RenderRequest::Show
+                        } else {
+                            RenderRequest::HideAnimated
+                        });
                     }
                 }
-                x11_cloned.hide_window(&window)?;
-                x11_cloned.show_window(&window)?;
-            }
-            Action::ShowLast => {
-                debug!("showing the last notification");
-                if notifications.count() == 0 {
-                    continue;
-                } else if notifications.mark_next_as_unread() {
-                    x11_cloned.hide_window(&window)?;
-                    x11_cloned.show_window(&window)?;
-                } else {
-                    x11_cloned.hide_window(&window)?;
+                Action::Close(id) => {
+                    if let Some(id) = id {
+                        debug!("closing notification: {}", id);
+                        notifications.mark_as_read(id);
+                    } else {
+                        debug!("closing the last notification");
+                        notifications.mark_last_as_read();
+                    }
+                    if let Some(display) = &display {
+                        display.renderer.request(if notifications.get_unread_count() >= 1 {
+                            RenderRequest::Show
+                        } else {
+                            RenderRequest::HideAnimated
+                        });
+                    }
                 }
-            }
-            Action::Close(id) => {
-                if let Some(id) = id {
-                    debug!("closing notification: {}", id);
-                    notifications.mark_as_read(id);
-                } else {
-                    debug!("closing the last notification");
-                    notifications.mark_last_as_read();
+                Action::CloseAll => {
+                    debug!("closing all notifications");
+                    notifications.mark_all_as_read();
+                    if let Some(display) = &display {
+                        display.renderer.request(RenderRequest::HideAnimated);
+                    }
                 }
-                x11_cloned.hide_window(&window)?;
-                if notifications.get_unread_count() >= 1 {
-                    x11_cloned.show_window(&window)?;
+                Action::Pop => {
+                    debug!("restoring the last dismissed notification");
+                    match notifications.restore_dismissed() {
+                        Some(id) => {
+                            info!("restored dismissed notification: {}", id);
+                            if let Some(display) = &display {
+                                display.window.reset_scroll();
+                                display.renderer.request(RenderRequest::Show);
+                            }
+                        }
+                        None => info!("no dismissed notifications to restore"),
+                    }
                 }
-            }
-            Action::CloseAll => {
-                debug!("closing all notifications");
-                notifications.mark_all_as_read();
-                x11_cloned.hide_window(&window)?;
-            }
-            Action::Invoke(id, action_key) => {
-                debug!("invoking action '{}' on notification {}", action_key, id);
-                // Send to zbus thread to emit ActionInvoked signal
-                if let Err(e) = invoke_sender.send((id, action_key)) {
-                    log::warn!("failed to send action invocation: {}", e);
+                Action::Invoke(id, action_key) => {
+                    debug!("invoking action '{}' on notification {}", action_key, id);
+                    notifications.emit(NotificationEvent::ActionInvoked(id, action_key.clone()));
+                    // Send to zbus thread to emit ActionInvoked signal
+                    if let Err(e) = signal_sender.send(BusSignal::ActionInvoked(id, action_key)) {
+                        log::warn!("failed to send action invocation: {}", e);
+                    }
+                }
+                Action::RecreateDisplay if headless => {}
+                Action::RecreateDisplay => {
+                    info!("rebuilding the display stack");
+                    // Dropping the old stack stops its expiry thread; the
+                    // render and input threads exit on their own once their
+                    // channel or connection errors out
+                    display = None;
+                    match Display::connect(&config, &notifications, &sender) {
+                        Ok(new_display) => {
+                            reconnect_delay = Duration::from_secs(1);
+                            // The control interface keeps the old window
+                            // handle; only its layout queries go stale
+                            if notifications.get_unread_count() >= 1 {
+                                new_display.window.reset_scroll();
+                                new_display.renderer.request(RenderRequest::Show);
+                            }
+                            display = Some(new_display);
+                        }
+                        Err(e) => {
+                            log::warn!(
+                                "failed to reconnect to the X server: {} (retrying in {}s)",
+                                e,
+                                reconnect_delay.as_secs()
+                            );
+                            let sender_cloned = sender.clone();
+                            let delay = reconnect_delay;
+                            reconnect_delay = (reconnect_delay * 2).min(Duration::from_secs(60));
+                            thread::spawn(move || {
+                                thread::sleep(delay);
+                                let _ = sender_cloned.send(Action::RecreateDisplay);
+                            });
+                        }
+                    }
+                }
+                Action::EvaluateSchedule => {
+                    if config.read().expect("config lock").global.quiet_hours_active()
+                        && notifications.mark_non_critical_as_read() > 0
+                    {
+                        debug!("quiet hours began, clearing non-critical popups");
+                        if let Some(display) = &display {
+                            display.renderer.request(
+                                if notifications.get_unread_count() >= 1 {
+                                    RenderRequest::Show
+                                } else {
+                                    RenderRequest::HideAnimated
+                                },
+                            );
+                        }
+                    }
+                }
+                Action::Shutdown => {
+                    info!("shutting down");
+                    break;
+                }
+                Action::ReloadConfig if supplied => {
+                    debug!("reload skipped for a programmatically supplied configuration");
+                }
+                Action::ReloadConfig => {
+                    info!("reloading configuration");
+                    match overrides.load() {
+                        Ok(new_config) => {
+                            if let Some(display) = &display
+                                && let Err(e) = display.window.apply_config(&new_config.global)
+                            {
+                                log::warn!("failed to apply new window configuration: {}", e);
+                            }
+                            console_sink = build_console(&new_config);
+                            *config.write().expect("config lock") = new_config;
+                            if let Some(display) = &display {
+                                display.renderer.request(
+                                    if notifications.get_unread_count() >= 1 {
+                                        RenderRequest::Show
+                                    } else {
+                                        RenderRequest::Hide
+                                    },
+                                );
+                            }
+                        }
+                        Err(e) => log::warn!("failed to reload configuration: {}", e),
+                    }
                 }
             }
         }
+
+        // Unmap the window before exiting so no orphaned override-redirect
+        // window lingers on the screen
+        if let Some(display) = &display
+            && let Err(e) = display.x11.hide_window(&display.window)
+        {
+            log::warn!("failed to hide window on shutdown: {}", e);
+        }
+        // Dropping the writer flushes pending entries and joins its thread
+        drop(history);
+        Ok(())
     }
 }
+
+/// Write end of the self-pipe the signal handler reports into.
+static SHUTDOWN_PIPE: AtomicI32 = AtomicI32::new(-1);
+
+/// Signal handler: only writes a byte, which is async-signal-safe.
+extern "C" fn forward_signal(_signal: libc::c_int) {
+    let fd = SHUTDOWN_PIPE.load(Ordering::Relaxed);
+    if fd >= 0 {
+        unsafe { libc::write(fd, b"x".as_ptr().cast(), 1) };
+    }
+}
+
+/// Installs SIGTERM/SIGINT handlers that shut the daemon down cleanly.
+///
+/// A helper thread turns bytes arriving on the self-pipe into
+/// [`Action::Shutdown`], so the main loop can flush history and unmap
+/// the window instead of dying mid-write.
+fn shutdown_on_signals(sender: mpsc::Sender<Action>) {
+    let mut fds = [0 as libc::c_int; 2];
+    if unsafe { libc::pipe(fds.as_mut_ptr()) } != 0 {
+        log::warn!("failed to create signal pipe; shutdown will not flush state");
+        return;
+    }
+    let (read_fd, write_fd) = (fds[0], fds[1]);
+    SHUTDOWN_PIPE.store(write_fd, Ordering::Relaxed);
+    let handler = forward_signal as extern "C" fn(libc::c_int) as libc::sighandler_t;
+    unsafe {
+        libc::signal(libc::SIGTERM, handler);
+        libc::signal(libc::SIGINT, handler);
+    }
+    if thread::Builder::new()
+        .name("runst-signals".to_string())
+        .spawn(move || {
+            let mut buffer = [0u8; 1];
+            loop {
+                let read = unsafe { libc::read(read_fd, buffer.as_mut_ptr().cast(), 1) };
+                if read > 0 {
+                    info!("received shutdown signal");
+                    if sender.send(Action::Shutdown).is_err() {
+                        break;
+                    }
+                } else if read == 0 {
+                    break;
+                }
+                // A negative count is an interrupted read; retry
+            }
+        })
+        .is_err()
+    {
+        log::warn!("failed to spawn signal thread");
+    }
+}
+
+/// Runs `runst`.
+pub fn run(overrides: ConfigOverrides) -> Result<()> {
+    let daemon = Daemon::builder().overrides(overrides).build();
+    shutdown_on_signals(daemon.sender());
+    daemon.run()
+}
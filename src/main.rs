@@ -1,27 +1,106 @@
 use clap::Parser;
-use runst::cli::{Cli, Command};
-use runst::history::{DEFAULT_HISTORY_LIMIT, History};
+use runst::cli::{Cli, Command, CtlCommand, HistoryCommand};
+use runst::history::{DEFAULT_HISTORY_LIMIT, History, HistoryEntry};
 
 fn main() {
     let cli = Cli::parse();
+    let overrides = cli.overrides();
 
     match cli.command {
         Some(Command::History {
+            command,
             count,
             search,
+            app,
+            urgency,
+            since,
             all,
             json,
+            follow,
+            format,
+            export,
             clear,
             path,
         }) => {
-            if let Err(e) = handle_history(count, search, all, json, clear, path) {
+            if let Err(e) = handle_history(
+                command, count, search, app, urgency, since, all, json, follow, format, export,
+                clear, path,
+            ) {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        Some(Command::Replay { from, speed, count }) => {
+            if let Err(e) = handle_replay(&from, &speed, count) {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        Some(Command::Pop) => {
+            if let Err(e) = runst::ctl::pop() {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        Some(Command::Count { json, watch }) => {
+            if let Err(e) = runst::ctl::count(json, watch) {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        Some(Command::List { json }) => {
+            if let Err(e) = runst::ctl::list(json) {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        Some(Command::Statusbar { watch }) => {
+            if let Err(e) = runst::ctl::statusbar(watch) {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        Some(Command::Ctl { command }) => {
+            let result = match command {
+                CtlCommand::Mute { pattern, duration } => {
+                    runst::ctl::mute(&pattern, duration.as_deref())
+                }
+                CtlCommand::Unmute { pattern } => runst::ctl::unmute(&pattern),
+                CtlCommand::Mutes => runst::ctl::mutes(),
+                CtlCommand::Layout => runst::ctl::layout(),
+                CtlCommand::GetConfig => runst::ctl::get_config(),
+                CtlCommand::SetOption { key, value } => runst::ctl::set_option(&key, &value),
+                CtlCommand::ExportUnread { file } => runst::ctl::export_unread(&file),
+                CtlCommand::ImportUnread { file } => runst::ctl::import_unread(&file),
+            };
+            if let Err(e) = result {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        Some(Command::Template {
+            json,
+            app,
+            summary,
+            body,
+            urgency,
+            template,
+        }) => {
+            if let Err(e) = handle_template(overrides, json, app, summary, body, urgency, template)
+            {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        Some(Command::Preview { config }) => {
+            if let Err(e) = runst::preview(&config) {
                 eprintln!("Error: {}", e);
                 std::process::exit(1);
             }
         }
         None => {
             // Default: run the daemon
-            if let Err(e) = runst::run() {
+            if let Err(e) = runst::run(overrides) {
                 eprintln!("Error: {}", e);
                 std::process::exit(1);
             }
@@ -29,37 +108,334 @@ fn main() {
     }
 }
 
+fn handle_replay(from: &str, speed: &str, count: Option<usize>) -> runst::error::Result<()> {
+    if from != "history" {
+        return Err(runst::error::Error::Config(format!(
+            "unsupported replay source: {from}"
+        )));
+    }
+    let speed = runst::ctl::parse_speed(speed)?;
+    let history = History::new(DEFAULT_HISTORY_LIMIT)?;
+    let mut entries: Vec<_> = history.all().into_iter().cloned().collect();
+    if let Some(count) = count {
+        let skip = entries.len().saturating_sub(count);
+        entries.drain(..skip);
+    }
+    runst::ctl::replay(&entries, speed)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn handle_template(
+    overrides: runst::config::ConfigOverrides,
+    json: Option<String>,
+    app: String,
+    summary: String,
+    body: String,
+    urgency: String,
+    template: Option<String>,
+) -> runst::error::Result<()> {
+    use runst::notification::{NOTIFICATION_MESSAGE_TEMPLATE, Notification, Urgency};
+
+    let config = overrides.load()?;
+    let notification: Notification = match json {
+        Some(json) => {
+            let data = if json == "-" {
+                use std::io::Read;
+                let mut buffer = String::new();
+                std::io::stdin().read_to_string(&mut buffer)?;
+                buffer
+            } else {
+                json
+            };
+            serde_json::from_str(&data)?
+        }
+        None => Notification {
+            id: 1,
+            app_name: app,
+            summary,
+            body,
+            urgency: match urgency.as_str() {
+                "low" => Urgency::Low,
+                "critical" => Urgency::Critical,
+                _ => Urgency::Normal,
+            },
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+            ..Default::default()
+        },
+    };
+
+    let raw_template = template.as_deref().unwrap_or(&config.global.template);
+    let mut tera = tera::Tera::default();
+    tera.add_raw_template(NOTIFICATION_MESSAGE_TEMPLATE, raw_template.trim())
+        .map_err(|e| runst::error::Error::Config(format!("invalid template: {e}")))?;
+    runst::notification::register_template_extensions(&mut tera);
+
+    let urgency_text = config
+        .get_urgency_config(&notification.urgency, &notification.app_name)
+        .text;
+    println!("{}", notification.render_message(&tera, urgency_text, 1, None)?);
+    Ok(())
+}
+
+/// Prints history entries in a pipe-friendly format: aligned columns,
+/// one-line entries, tab-separated fields, or a custom line template.
+fn print_history_entries(entries: &[HistoryEntry], format: &str) {
+    match format {
+        "table" => {
+            let width = |default: usize, field: fn(&HistoryEntry) -> usize| {
+                entries.iter().map(field).max().unwrap_or(0).max(default)
+            };
+            let id_width = width("ID".len(), |e| e.id.to_string().len());
+            let time_width = width("TIME".len(), |e| e.datetime.len());
+            let app_width = width("APP".len(), |e| e.app_name.len());
+            let urgency_width = width("URGENCY".len(), |e| e.urgency.len());
+            println!(
+                "{:<id_width$}  {:<time_width$}  {:<app_width$}  {:<urgency_width$}  SUMMARY",
+                "ID", "TIME", "APP", "URGENCY"
+            );
+            for entry in entries {
+                println!(
+                    "{:<id_width$}  {:<time_width$}  {:<app_width$}  {:<urgency_width$}  {}",
+                    entry.id, entry.datetime, entry.app_name, entry.urgency, entry.summary
+                );
+            }
+        }
+        "compact" => {
+            for entry in entries {
+                println!("[{}] {}: {}", entry.datetime, entry.app_name, entry.summary);
+            }
+        }
+        "tsv" => {
+            for entry in entries {
+                println!(
+                    "{}\t{}\t{}\t{}\t{}\t{}",
+                    entry.id,
+                    entry.datetime,
+                    entry.app_name,
+                    entry.urgency,
+                    entry.summary,
+                    entry.body.replace(['\t', '\n'], " ")
+                );
+            }
+        }
+        template => {
+            for entry in entries {
+                println!(
+                    "{}",
+                    template
+                        .replace("{id}", &entry.id.to_string())
+                        .replace("{time}", &entry.datetime)
+                        .replace("{app}", &entry.app_name)
+                        .replace("{urgency}", &entry.urgency)
+                        .replace("{summary}", &entry.summary)
+                        .replace("{body}", &entry.body.replace('\n', " "))
+                        .replace("{count}", &entry.count.to_string())
+                );
+            }
+        }
+    }
+}
+
+/// Runs the dmenu-style history picker.
+///
+/// Without a selection the entries are printed for an external picker;
+/// a selected line (from stdin or the spawned picker) is mapped back to
+/// its entry, which is then replayed or has its default action invoked.
+fn handle_history_menu(
+    history: &History,
+    run: Option<String>,
+    invoke: bool,
+) -> runst::error::Result<()> {
+    use std::io::{BufRead, IsTerminal, Write};
+    use std::process::{Command as Process, Stdio};
+
+    let line_for = |entry: &HistoryEntry| {
+        format!(
+            "{}: [{}] {}: {}",
+            entry.id, entry.datetime, entry.app_name, entry.summary
+        )
+    };
+    // Most recent first, the way pickers list candidates
+    let entries: Vec<_> = history.all().into_iter().rev().cloned().collect();
+
+    let selection = if let Some(run) = run {
+        let mut child = Process::new("sh")
+            .arg("-c")
+            .arg(&run)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()?;
+        if let Some(mut stdin) = child.stdin.take() {
+            for entry in &entries {
+                writeln!(stdin, "{}", line_for(entry))?;
+            }
+        }
+        let output = child.wait_with_output()?;
+        if !output.status.success() {
+            // Cancelled pickers exit non-zero; nothing to do
+            return Ok(());
+        }
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    } else if !std::io::stdin().is_terminal() {
+        let mut line = String::new();
+        std::io::stdin().lock().read_line(&mut line)?;
+        line.trim().to_string()
+    } else {
+        for entry in &entries {
+            println!("{}", line_for(entry));
+        }
+        return Ok(());
+    };
+    if selection.is_empty() {
+        return Ok(());
+    }
+
+    let id: u32 = selection
+        .split(':')
+        .next()
+        .and_then(|s| s.trim().parse().ok())
+        .ok_or_else(|| {
+            runst::error::Error::Config(format!("unrecognized selection: {selection}"))
+        })?;
+    let entry = entries.iter().find(|e| e.id == id).ok_or_else(|| {
+        runst::error::Error::Config(format!("no history entry with ID {id}"))
+    })?;
+    if invoke {
+        runst::ctl::invoke(id, "default")
+    } else {
+        runst::ctl::replay(std::slice::from_ref(entry), 1.0)
+    }
+}
+
+/// Prints every stored field of one history entry.
+fn show_history_entry(history: &History, id: u32) -> runst::error::Result<()> {
+    // IDs recycle across daemon restarts; the most recent entry wins
+    let entry = history
+        .all()
+        .into_iter()
+        .rev()
+        .find(|e| e.id == id)
+        .ok_or_else(|| {
+            runst::error::Error::Config(format!("no history entry with ID {id}"))
+        })?;
+
+    println!("ID:            {}", entry.id);
+    println!("App:           {}", entry.app_name);
+    println!("Urgency:       {}", entry.urgency);
+    println!("Received:      {} (unix {})", entry.datetime, entry.timestamp);
+    println!("Repeats:       ×{}", entry.count);
+    if entry.last_seen > 0 && entry.last_seen != entry.timestamp {
+        println!("Last seen:     unix {}", entry.last_seen);
+    }
+    match entry.expires_at {
+        Some(expires_at) => println!("Expires:       unix {}", expires_at),
+        None => println!("Expires:       never"),
+    }
+    println!("Content hash:  {:016x}", entry.content_hash);
+    println!("Summary:       {}", entry.summary);
+    if entry.body.is_empty() {
+        println!("Body:          (empty)");
+    } else {
+        println!("Body:");
+        for line in entry.body.lines() {
+            println!("  {line}");
+        }
+    }
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
 fn handle_history(
+    command: Option<HistoryCommand>,
     count: usize,
     search: Option<String>,
+    app: Option<String>,
+    urgency: Option<String>,
+    since: Option<String>,
     all: bool,
     json: bool,
+    follow: bool,
+    format: Option<String>,
+    export: bool,
     clear: bool,
     show_path: bool,
 ) -> runst::error::Result<()> {
+    // Following needs a running daemon, not the history file
+    if follow {
+        return runst::ctl::follow(json);
+    }
+
     let mut history = History::new(DEFAULT_HISTORY_LIMIT)?;
 
+    if let Some(command) = command {
+        return match command {
+            HistoryCommand::Show { id } => show_history_entry(&history, id),
+            HistoryCommand::Menu { run, invoke } => handle_history_menu(&history, run, invoke),
+        };
+    }
+
     if show_path {
         println!("{}", history.path().display());
         return Ok(());
     }
 
+    if export {
+        println!("{}", history.export_json()?);
+        return Ok(());
+    }
+
     if clear {
         history.clear()?;
         println!("History cleared.");
         return Ok(());
     }
 
-    let entries = if let Some(ref query) = search {
+    let filtered = app.is_some() || urgency.is_some();
+    let mut entries: Vec<_> = if let Some(ref since) = since {
+        let duration = humantime::parse_duration(since).map_err(|e| {
+            runst::error::Error::Config(format!("invalid --since duration: {}", e))
+        })?;
+        let cutoff = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0)
+            .saturating_sub(duration.as_secs());
+        let mut entries = history.since(cutoff)?;
+        if let Some(ref query) = search {
+            // Archives are not full-text indexed; filter by substring
+            let query = query.to_lowercase();
+            entries.retain(|e| {
+                e.app_name.to_lowercase().contains(&query)
+                    || e.summary.to_lowercase().contains(&query)
+                    || e.body.to_lowercase().contains(&query)
+            });
+        }
+        entries
+    } else if let Some(ref query) = search {
         history.search(query)
-    } else if all {
-        history.all()
+    } else if all || filtered {
+        history.all().into_iter().cloned().collect()
     } else {
-        history.recent(count)
+        history.recent(count).into_iter().cloned().collect()
     };
 
+    if let Some(ref app) = app {
+        entries.retain(|e| runst::config::glob_match(app, &e.app_name));
+    }
+    if let Some(ref urgency) = urgency {
+        entries.retain(|e| e.urgency.eq_ignore_ascii_case(urgency));
+    }
+    // Without --all, the filters still only show the most recent matches
+    if filtered && !all && search.is_none() && since.is_none() && entries.len() > count {
+        let skip = entries.len() - count;
+        entries.drain(..skip);
+    }
+
     if entries.is_empty() {
-        if search.is_some() {
+        if search.is_some() || filtered || since.is_some() {
             println!("No notifications found matching the search query.");
         } else {
             println!("No notifications in history.");
@@ -67,9 +443,10 @@ fn handle_history(
         return Ok(());
     }
 
-    if json {
-        let json_output = serde_json::to_string_pretty(&entries)?;
-        println!("{}", json_output);
+    if let Some(format) = format {
+        print_history_entries(&entries, &format);
+    } else if json {
+        println!("{}", runst::schema::to_json(&entries)?);
     } else {
         println!(
             "Showing {} notification{}:\n",
@@ -81,6 +458,9 @@ fn handle_history(
             println!("ID:       {}", entry.id);
             println!("App:      {}", entry.app_name);
             println!("Time:     {}", entry.datetime);
+            if entry.count > 1 {
+                println!("Repeats:  ×{}", entry.count);
+            }
             println!("Urgency:  {}", entry.urgency);
             println!("Summary:  {}", entry.summary);
             if !entry.body.is_empty() {
@@ -0,0 +1,135 @@
+//! Minimal Markdown-to-Pango conversion.
+//!
+//! Covers the subset chat bots actually send — **bold**, *italic* (and
+//! the underscore forms), `code`, [links](url) and bullet lists — and
+//! escapes everything else, so Markdown bodies stop rendering as literal
+//! asterisks without pulling in a full parser.
+
+/// Converts a Markdown body to Pango markup.
+pub fn to_pango(body: &str) -> String {
+    body.lines()
+        .map(render_line)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Renders one line, turning leading list markers into bullets.
+fn render_line(line: &str) -> String {
+    let trimmed = line.trim_start();
+    let indent = &line[..line.len() - trimmed.len()];
+    if let Some(item) = trimmed
+        .strip_prefix("- ")
+        .or_else(|| trimmed.strip_prefix("* "))
+    {
+        format!("{}• {}", indent, render_inline(item))
+    } else {
+        render_inline(line)
+    }
+}
+
+/// Inline markers, longest first so `**` is tried before `*`.
+const MARKERS: &[&str] = &["**", "__", "`", "*", "_", "["];
+
+/// Renders inline markup within a line.
+fn render_inline(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while !rest.is_empty() {
+        // Earliest marker occurrence; ties go to the longest marker
+        let next = MARKERS
+            .iter()
+            .filter_map(|marker| rest.find(marker).map(|at| (at, *marker)))
+            .min_by_key(|(at, marker)| (*at, std::cmp::Reverse(marker.len())));
+        let Some((at, marker)) = next else {
+            out.push_str(&escape(rest));
+            break;
+        };
+        out.push_str(&escape(&rest[..at]));
+        rest = &rest[at..];
+        match render_marker(rest, marker) {
+            Some((rendered, consumed)) => {
+                out.push_str(&rendered);
+                rest = &rest[consumed..];
+            }
+            None => {
+                // No closing marker: keep it as literal text
+                out.push_str(&escape(marker));
+                rest = &rest[marker.len()..];
+            }
+        }
+    }
+    out
+}
+
+/// Renders the span starting with `marker` at the start of `text`,
+/// returning the markup and the number of bytes consumed.
+fn render_marker(text: &str, marker: &str) -> Option<(String, usize)> {
+    if marker == "[" {
+        // [label](url)
+        let label_end = text.find("](")?;
+        let url_end = text[label_end..].find(')')? + label_end;
+        let label = &text[1..label_end];
+        let url = &text[label_end + 2..url_end];
+        return Some((
+            format!(
+                "<u>{}</u> <span foreground=\"#888888\">({})</span>",
+                render_inline(label),
+                escape(url)
+            ),
+            url_end + 1,
+        ));
+    }
+    let inner_start = marker.len();
+    let close = text[inner_start..].find(marker)? + inner_start;
+    let inner = &text[inner_start..close];
+    if inner.is_empty() {
+        return None;
+    }
+    let consumed = close + marker.len();
+    let rendered = match marker {
+        "`" => format!("<tt>{}</tt>", escape(inner)),
+        "**" | "__" => format!("<b>{}</b>", render_inline(inner)),
+        _ => format!("<i>{}</i>", render_inline(inner)),
+    };
+    Some((rendered, consumed))
+}
+
+/// Escapes literal text for Pango markup.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inline_markup() {
+        assert_eq!(to_pango("**bold** and *italic*"), "<b>bold</b> and <i>italic</i>");
+        assert_eq!(to_pango("__bold__ and _italic_"), "<b>bold</b> and <i>italic</i>");
+        assert_eq!(to_pango("run `rm -rf <dir>`"), "run <tt>rm -rf &lt;dir&gt;</tt>");
+        // Code spans are not parsed further
+        assert_eq!(to_pango("`**raw**`"), "<tt>**raw**</tt>");
+    }
+
+    #[test]
+    fn test_links_and_lists() {
+        assert_eq!(
+            to_pango("[build log](https://ci.example/1)"),
+            "<u>build log</u> <span foreground=\"#888888\">(https://ci.example/1)</span>"
+        );
+        assert_eq!(
+            to_pango("- first\n- **second**"),
+            "• first\n• <b>second</b>"
+        );
+    }
+
+    #[test]
+    fn test_unclosed_markers_stay_literal() {
+        assert_eq!(to_pango("2 * 3 = 6"), "2 * 3 = 6");
+        assert_eq!(to_pango("a < b && b > c"), "a &lt; b &amp;&amp; b &gt; c");
+        assert_eq!(to_pango("[not a link"), "[not a link");
+    }
+}
@@ -1,17 +1,211 @@
+use crate::config::{EvictionPolicy, OverflowPolicy};
 use crate::error::{Error, Result};
 use regex::Regex;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
 use std::error::Error as StdError;
 use std::fmt::Display;
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::mpsc;
 use std::sync::{Arc, RwLock};
-use std::time::Duration;
-use tera::{Context as TeraContext, Tera};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tera::{Context as TeraContext, Result as TeraResult, Tera, Value};
 
 /// Name of the template for rendering the notification message.
 pub const NOTIFICATION_MESSAGE_TEMPLATE: &str = "notification_message_template";
 
+/// Registers the Tera filters shared by all notification templates.
+pub fn register_template_extensions(template: &mut Tera) {
+    template.register_filter("humantime", humantime_filter);
+    template.register_filter("bar", bar_filter);
+    template.register_filter("truncate_chars", truncate_chars_filter);
+    template.register_filter("strip_markup", strip_markup_filter);
+    template.register_filter("regex_replace", regex_replace_filter);
+    template.register_filter("title_case", title_case_filter);
+    template.register_filter("strftime", strftime_filter);
+}
+
+/// Tera filter formatting a duration in seconds as a human-readable string.
+fn humantime_filter(value: &Value, _: &HashMap<String, Value>) -> TeraResult<Value> {
+    let value = tera::try_get_value!("humantime_filter", "value", u64, value);
+    let value = humantime::format_duration(Duration::new(value, 0)).to_string();
+    Ok(tera::to_value(value)?)
+}
+
+/// Tera filter rendering a textual gauge from a numeric value, e.g.
+/// `{{ value | bar(width=20) }}` for volume or battery levels.
+///
+/// The value is measured against `max` (default 100). `width` sets the
+/// number of characters (default 10); `fill` and `empty` override the
+/// glyphs used for the two segments.
+fn bar_filter(value: &Value, args: &HashMap<String, Value>) -> TeraResult<Value> {
+    let value = value
+        .as_f64()
+        .ok_or_else(|| tera::Error::msg("bar filter expects a numeric value"))?;
+    let max = args.get("max").and_then(Value::as_f64).unwrap_or(100.0);
+    if max <= 0.0 {
+        return Err(tera::Error::msg("bar filter expects a positive max"));
+    }
+    let width = args.get("width").and_then(Value::as_u64).unwrap_or(10) as usize;
+    let fill = args.get("fill").and_then(Value::as_str).unwrap_or("█");
+    let empty = args.get("empty").and_then(Value::as_str).unwrap_or("░");
+    let filled = ((value / max).clamp(0.0, 1.0) * width as f64).round() as usize;
+    Ok(tera::to_value(format!(
+        "{}{}",
+        fill.repeat(filled),
+        empty.repeat(width - filled)
+    ))?)
+}
+
+/// Tera filter truncating a string to `length` characters (not bytes),
+/// appending `ellipsis` ("…" by default) when anything was cut off, e.g.
+/// `{{ body | truncate_chars(length=80) }}`.
+fn truncate_chars_filter(value: &Value, args: &HashMap<String, Value>) -> TeraResult<Value> {
+    let value = tera::try_get_value!("truncate_chars_filter", "value", String, value);
+    let length = args
+        .get("length")
+        .and_then(Value::as_u64)
+        .ok_or_else(|| tera::Error::msg("truncate_chars filter expects a length argument"))?
+        as usize;
+    let ellipsis = args.get("ellipsis").and_then(Value::as_str).unwrap_or("…");
+    let truncated = match value.char_indices().nth(length) {
+        Some((offset, _)) => format!("{}{}", &value[..offset], ellipsis),
+        None => value,
+    };
+    Ok(tera::to_value(truncated)?)
+}
+
+/// Tera filter removing markup tags from a string, for senders that embed
+/// HTML in their bodies.
+fn strip_markup_filter(value: &Value, _: &HashMap<String, Value>) -> TeraResult<Value> {
+    let value = tera::try_get_value!("strip_markup_filter", "value", String, value);
+    let tags = Regex::new("<[^>]*>").map_err(|e| tera::Error::msg(e.to_string()))?;
+    Ok(tera::to_value(tags.replace_all(&value, "").into_owned())?)
+}
+
+/// Tera filter applying a regex substitution, e.g.
+/// `{{ summary | regex_replace(pattern="^\[bot\] ", replacement="") }}`.
+///
+/// All matches are replaced; `$1`-style groups work in the replacement.
+fn regex_replace_filter(value: &Value, args: &HashMap<String, Value>) -> TeraResult<Value> {
+    let value = tera::try_get_value!("regex_replace_filter", "value", String, value);
+    let pattern = args
+        .get("pattern")
+        .and_then(Value::as_str)
+        .ok_or_else(|| tera::Error::msg("regex_replace filter expects a pattern argument"))?;
+    let replacement = args
+        .get("replacement")
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+    let pattern = Regex::new(pattern)
+        .map_err(|e| tera::Error::msg(format!("invalid regex_replace pattern: {e}")))?;
+    Ok(tera::to_value(
+        pattern.replace_all(&value, replacement).into_owned(),
+    )?)
+}
+
+/// Tera filter capitalizing the first letter of each whitespace-separated
+/// word, leaving the rest of the word untouched.
+fn title_case_filter(value: &Value, _: &HashMap<String, Value>) -> TeraResult<Value> {
+    let value = tera::try_get_value!("title_case_filter", "value", String, value);
+    let mut result = String::with_capacity(value.len());
+    let mut at_word_start = true;
+    for c in value.chars() {
+        if at_word_start && c.is_alphabetic() {
+            result.extend(c.to_uppercase());
+        } else {
+            result.push(c);
+        }
+        at_word_start = c.is_whitespace();
+    }
+    Ok(tera::to_value(result)?)
+}
+
+/// Tera filter formatting a Unix timestamp as local time with a strftime
+/// string, e.g. `{{ timestamp | strftime(format="%H:%M") }}`.
+fn strftime_filter(value: &Value, args: &HashMap<String, Value>) -> TeraResult<Value> {
+    let value = tera::try_get_value!("strftime_filter", "value", i64, value);
+    let format = args
+        .get("format")
+        .and_then(Value::as_str)
+        .unwrap_or("%Y-%m-%d %H:%M:%S");
+    let datetime = chrono::DateTime::from_timestamp(value, 0)
+        .ok_or_else(|| tera::Error::msg("strftime filter got an out-of-range timestamp"))?
+        .with_timezone(&chrono::Local);
+    Ok(tera::to_value(datetime.format(format).to_string())?)
+}
+
+/// Expands `:rocket:`-style emoji shortcodes to Unicode emoji.
+///
+/// Unknown shortcodes and stray colons are left untouched, so timestamps
+/// like "12:30" pass through unchanged.
+pub fn expand_shortcodes(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find(':') {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        match after.find(':') {
+            Some(end) if end > 0 => {
+                let code = &after[..end];
+                let is_shortcode = code
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '+'));
+                if is_shortcode && let Some(emoji) = emojis::get_by_shortcode(code) {
+                    out.push_str(emoji.as_str());
+                    rest = &after[end + 1..];
+                } else {
+                    out.push(':');
+                    rest = after;
+                }
+            }
+            _ => {
+                out.push(':');
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Truncates text to at most `max_bytes` bytes at a character boundary,
+/// appending an ellipsis when anything was cut.
+pub fn truncate_bytes(text: &str, max_bytes: usize) -> String {
+    if text.len() <= max_bytes {
+        return text.to_string();
+    }
+    let mut end = max_bytes;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!("{}…", &text[..end])
+}
+
+/// Quotes a string for safe interpolation into a `sh -c` command line.
+pub fn shell_escape(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+/// Computes a stable content hash identifying a notification message.
+///
+/// The hash covers the fields that make two notifications "the same"
+/// (application, summary, body and urgency) but not volatile ones like the
+/// ID or timestamp, so it stays comparable across daemon restarts and
+/// remote forwarding hops.
+pub fn content_hash(app_name: &str, summary: &str, body: &str, urgency: &Urgency) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    app_name.hash(&mut hasher);
+    summary.hash(&mut hasher);
+    body.hash(&mut hasher);
+    urgency.level().hash(&mut hasher);
+    hasher.finish()
+}
+
 /// Possible urgency levels for the notification.
-#[derive(Clone, Debug, Serialize, Default)]
+#[derive(Clone, Debug, Deserialize, Serialize, Default)]
 pub enum Urgency {
     /// Low urgency.
     Low,
@@ -28,6 +222,17 @@ impl Display for Urgency {
     }
 }
 
+impl Urgency {
+    /// Returns the urgency as a comparable level (low = 0, critical = 2).
+    pub fn level(&self) -> u8 {
+        match self {
+            Self::Low => 0,
+            Self::Normal => 1,
+            Self::Critical => 2,
+        }
+    }
+}
+
 impl From<u64> for Urgency {
     fn from(value: u64) -> Self {
         match value {
@@ -42,7 +247,8 @@ impl From<u64> for Urgency {
 /// Representation of a notification.
 ///
 /// See [D-Bus Notify Parameters](https://specifications.freedesktop.org/notification-spec/latest/ar01s09.html)
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
 pub struct Notification {
     /// The optional notification ID.
     pub id: u32,
@@ -63,11 +269,225 @@ pub struct Notification {
     /// Actions available for this notification (key-label pairs flattened).
     /// Format: [key1, label1, key2, label2, ...]
     pub actions: Vec<String>,
+    /// Unix timestamp in milliseconds when the notification will auto-clear,
+    /// if a timeout applies (used for the countdown indicator).
+    #[serde(default)]
+    pub deadline: Option<u64>,
+    /// How many duplicates have stacked onto this notification while it
+    /// was displayed (drawn as a "×N" counter).
+    #[serde(default)]
+    pub repeats: u64,
+    /// Sound name or file path from the sender's `sound-name`/`sound-file`
+    /// hints, played when the notification is displayed.
+    #[serde(default)]
+    pub sound: Option<String>,
+    /// Whether the sender asked for no sound (`suppress-sound` hint).
+    #[serde(default)]
+    pub suppress_sound: bool,
+    /// Icon name or path from the sender's `app_icon` field.
+    #[serde(default)]
+    pub icon: String,
+    /// Category hint (e.g. "email.arrived"), when the sender set one.
+    #[serde(default)]
+    pub category: Option<String>,
+    /// Progress from the `value` hint (0-100), used by volume and
+    /// brightness popups.
+    #[serde(default)]
+    pub value: Option<i32>,
+    /// Remaining sender hints, stringified for template access.
+    #[serde(default)]
+    pub hints: HashMap<String, String>,
+    /// Decoded `image-data` hint pixels, if the sender attached any.
+    /// Skipped by serialization; snapshots and history carry text only.
+    #[serde(skip)]
+    pub image: Option<ImageData>,
+}
+
+/// Pixels from the `image-data` hint in Cairo's native ARGB32 layout.
+///
+/// The buffer is behind an [`Arc`] so the frequent [`Notification`]
+/// clones along the pipeline share one allocation instead of copying
+/// potentially megabytes of pixels each time.
+#[derive(Clone, Debug)]
+pub struct ImageData {
+    /// Width in pixels.
+    pub width: i32,
+    /// Height in pixels.
+    pub height: i32,
+    /// Premultiplied ARGB32 rows, `width * 4` bytes each.
+    pub data: Arc<[u8]>,
+}
+
+impl ImageData {
+    /// Converts a raw RGB(A) buffer from the D-Bus `image-data` hint.
+    ///
+    /// This is the single conversion pass; the result is shared, never
+    /// copied, from here on. Returns `None` when the advertised
+    /// dimensions don't match the buffer.
+    pub fn from_rgb(
+        width: i32,
+        height: i32,
+        rowstride: i32,
+        has_alpha: bool,
+        raw: &[u8],
+    ) -> Option<Self> {
+        let channels: i32 = if has_alpha { 4 } else { 3 };
+        if width <= 0 || height <= 0 || rowstride < width * channels {
+            return None;
+        }
+        let mut data = Vec::with_capacity((width * height * 4) as usize);
+        for row in 0..height as usize {
+            let offset = row * rowstride as usize;
+            let row_bytes = raw.get(offset..offset + (width * channels) as usize)?;
+            for pixel in row_bytes.chunks_exact(channels as usize) {
+                let (r, g, b) = (pixel[0] as u32, pixel[1] as u32, pixel[2] as u32);
+                let a = if has_alpha { pixel[3] as u32 } else { 255 };
+                // Cairo expects premultiplied alpha, native endianness
+                let argb =
+                    (a << 24) | ((r * a / 255) << 16) | ((g * a / 255) << 8) | (b * a / 255);
+                data.extend_from_slice(&argb.to_ne_bytes());
+            }
+        }
+        Some(Self {
+            width,
+            height,
+            data: data.into(),
+        })
+    }
+}
+
+/// IDs for notifications constructed locally via the builder.
+///
+/// They live in their own range so they cannot collide with the D-Bus
+/// server's counter (counting up from 1) or the GNTP listener's high
+/// range.
+static NEXT_LOCAL_ID: AtomicU32 = AtomicU32::new(0x4000_0000);
+
+/// Builder for notifications that do not arrive over D-Bus.
+///
+/// Integration tests and local tooling construct notifications directly;
+/// the builder fills the bookkeeping a real sender gets for free — a
+/// collision-free ID and the current timestamp — so only the content
+/// needs to be spelled out.
+#[derive(Debug, Default)]
+pub struct NotificationBuilder {
+    /// Explicit notification ID, if any.
+    id: Option<u32>,
+    /// Name of the sending application.
+    app_name: String,
+    /// Summary text.
+    summary: String,
+    /// Body text.
+    body: String,
+    /// Urgency level.
+    urgency: Urgency,
+    /// Expiry timeout override.
+    expire_timeout: Option<Duration>,
+    /// Flattened action key-label pairs.
+    actions: Vec<String>,
+    /// Icon name or path.
+    icon: String,
+    /// Explicit creation timestamp, if any.
+    timestamp: Option<u64>,
+}
+
+impl NotificationBuilder {
+    /// Sets an explicit ID instead of allocating a local one.
+    pub fn id(mut self, id: u32) -> Self {
+        self.id = Some(id);
+        self
+    }
+
+    /// Sets the application name.
+    pub fn app_name<S: Into<String>>(mut self, app_name: S) -> Self {
+        self.app_name = app_name.into();
+        self
+    }
+
+    /// Sets the summary text.
+    pub fn summary<S: Into<String>>(mut self, summary: S) -> Self {
+        self.summary = summary.into();
+        self
+    }
+
+    /// Sets the body text.
+    pub fn body<S: Into<String>>(mut self, body: S) -> Self {
+        self.body = body.into();
+        self
+    }
+
+    /// Sets the urgency level.
+    pub fn urgency(mut self, urgency: Urgency) -> Self {
+        self.urgency = urgency;
+        self
+    }
+
+    /// Sets the expiry timeout.
+    pub fn expire_timeout(mut self, timeout: Duration) -> Self {
+        self.expire_timeout = Some(timeout);
+        self
+    }
+
+    /// Adds an action as a key-label pair.
+    pub fn action<S: Into<String>>(mut self, key: S, label: S) -> Self {
+        self.actions.push(key.into());
+        self.actions.push(label.into());
+        self
+    }
+
+    /// Sets the icon name or path.
+    pub fn icon<S: Into<String>>(mut self, icon: S) -> Self {
+        self.icon = icon.into();
+        self
+    }
+
+    /// Sets an explicit creation timestamp (Unix seconds) instead of now.
+    pub fn timestamp(mut self, timestamp: u64) -> Self {
+        self.timestamp = Some(timestamp);
+        self
+    }
+
+    /// Builds the notification, allocating an ID and timestamp as needed.
+    pub fn build(self) -> Notification {
+        Notification {
+            id: self
+                .id
+                .unwrap_or_else(|| NEXT_LOCAL_ID.fetch_add(1, Ordering::Relaxed)),
+            app_name: self.app_name,
+            summary: self.summary,
+            body: self.body,
+            expire_timeout: self.expire_timeout,
+            urgency: self.urgency,
+            timestamp: self.timestamp.unwrap_or_else(|| {
+                SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|d| d.as_secs())
+                    .unwrap_or(0)
+            }),
+            actions: self.actions,
+            icon: self.icon,
+            ..Default::default()
+        }
+    }
 }
 
 impl Notification {
+    /// Returns a builder for constructing a notification locally.
+    pub fn builder() -> NotificationBuilder {
+        NotificationBuilder::default()
+    }
+
+    /// Returns the stable content hash of this notification.
+    pub fn content_hash(&self) -> u64 {
+        content_hash(&self.app_name, &self.summary, &self.body, &self.urgency)
+    }
+
     /// Converts [`Notification`] into [`TeraContext`].
     pub fn into_context(&self, urgency_text: String, unread_count: usize) -> Result<TeraContext> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
         Ok(TeraContext::from_serialize(Context {
             app_name: &self.app_name,
             summary: &self.summary,
@@ -75,23 +495,57 @@ impl Notification {
             urgency_text,
             unread_count,
             timestamp: self.timestamp,
+            icon: &self.icon,
+            category: self.category.as_deref().unwrap_or_default(),
+            value: self.value,
+            actions: &self.actions,
+            hints: &self.hints,
+            age: now.saturating_sub(self.timestamp),
         })?)
     }
 
+    /// Converts [`Notification`] into a [`TeraContext`] for command templates.
+    ///
+    /// Notification-derived strings are shell-escaped so they cannot break
+    /// out of the `sh -c` command line they are interpolated into; the
+    /// unescaped values remain available as `app_name_raw`, `summary_raw`
+    /// and `body_raw` for commands that do their own quoting.
+    pub fn into_command_context(
+        &self,
+        urgency_text: String,
+        unread_count: usize,
+    ) -> Result<TeraContext> {
+        let mut context = self.into_context(urgency_text, unread_count)?;
+        context.insert("app_name_raw", &self.app_name);
+        context.insert("summary_raw", &self.summary);
+        context.insert("body_raw", &self.body);
+        context.insert("app_name", &shell_escape(&self.app_name));
+        context.insert("summary", &shell_escape(&self.summary));
+        context.insert("body", &shell_escape(&self.body));
+        Ok(context)
+    }
+
     /// Renders the notification message using the given template.
+    ///
+    /// `position` is the entry's 1-based place in the displayed stack and
+    /// the stack size, exposed as `index` and `total_displayed` so
+    /// templates can render numbering like "2/5"; rendering outside a
+    /// stack context passes `None` and gets 1 of 1.
     pub fn render_message(
         &self,
         template: &Tera,
         urgency_text: Option<String>,
         unread_count: usize,
+        position: Option<(usize, usize)>,
     ) -> Result<String> {
-        match template.render(
-            NOTIFICATION_MESSAGE_TEMPLATE,
-            &self.into_context(
-                urgency_text.unwrap_or_else(|| self.urgency.to_string()),
-                unread_count,
-            )?,
-        ) {
+        let mut context = self.into_context(
+            urgency_text.unwrap_or_else(|| self.urgency.to_string()),
+            unread_count,
+        )?;
+        let (index, total_displayed) = position.unwrap_or((1, 1));
+        context.insert("index", &index);
+        context.insert("total_displayed", &total_displayed);
+        match template.render(NOTIFICATION_MESSAGE_TEMPLATE, &context) {
             Ok(v) => Ok::<String, Error>(v),
             Err(e) => {
                 if let Some(error_source) = e.source() {
@@ -136,7 +590,7 @@ pub struct NotificationFilter {
 }
 
 /// Template context for the notification.
-#[derive(Clone, Debug, Default, Serialize)]
+#[derive(Clone, Debug, Serialize)]
 struct Context<'a> {
     /// Name of the application that sends the notification.
     pub app_name: &'a str,
@@ -151,6 +605,18 @@ struct Context<'a> {
     pub unread_count: usize,
     /// Timestamp of the notification.
     pub timestamp: u64,
+    /// Icon name or path.
+    pub icon: &'a str,
+    /// Category hint, empty when the sender set none.
+    pub category: &'a str,
+    /// Progress value from the `value` hint.
+    pub value: Option<i32>,
+    /// Action keys and labels, flattened.
+    pub actions: &'a [String],
+    /// Remaining sender hints, stringified.
+    pub hints: &'a HashMap<String, String>,
+    /// Age of the notification in seconds.
+    pub age: u64,
 }
 
 /// Possible actions for a notification.
@@ -158,6 +624,9 @@ struct Context<'a> {
 pub enum Action {
     /// Show a notification.
     Show(Notification),
+    /// Re-show an already-processed notification (a snooze waking up);
+    /// rule transforms and history recording are skipped.
+    Reshow(Notification),
     /// Show the last notification.
     ShowLast,
     /// Close a notification.
@@ -166,6 +635,44 @@ pub enum Action {
     CloseAll,
     /// Invoke an action on a notification (id, action_key).
     Invoke(u32, String),
+    /// Restore the most recently dismissed notification.
+    Pop,
+    /// Re-evaluate the quiet-hours schedule.
+    EvaluateSchedule,
+    /// Rebuild the X11 display stack after a connection loss.
+    RecreateDisplay,
+    /// Reload the configuration file.
+    ReloadConfig,
+    /// Shut the daemon down cleanly, flushing pending state.
+    Shutdown,
+}
+
+/// A lifecycle event observed on the notification buffer.
+///
+/// Subsystems and library embedders consume these through
+/// [`Manager::subscribe`] instead of hooking the individual calls. The
+/// JSON form is adjacently tagged (`{"event": "closed", "data": 3}`) and
+/// follows the versioned [schema](crate::schema) guarantees.
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "event", content = "data", rename_all = "kebab-case")]
+pub enum NotificationEvent {
+    /// A notification entered the unread buffer.
+    Received(Notification),
+    /// A notification was shown on screen.
+    Displayed(u32),
+    /// A notification was closed, marked as read or evicted.
+    Closed(u32),
+    /// An action was invoked on a notification (id, action_key).
+    ActionInvoked(u32, String),
+}
+
+/// A temporary per-application mute.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Mute {
+    /// Glob pattern matching application names.
+    pub pattern: String,
+    /// Unix timestamp when the mute expires (None mutes until unmuted).
+    pub expires_at: Option<u64>,
 }
 
 /// Notification manager.
@@ -173,22 +680,155 @@ pub enum Action {
 pub struct Manager {
     /// Inner type that holds the notifications in thread-safe way.
     inner: Arc<RwLock<Vec<Notification>>>,
+    /// Active per-application mutes.
+    mutes: Arc<RwLock<Vec<Mute>>>,
+    /// Recently dismissed notifications, newest last (for undo-close).
+    dismissed: Arc<RwLock<Vec<Notification>>>,
+    /// Event subscribers; closed channels are pruned on the next emit.
+    subscribers: Arc<RwLock<Vec<mpsc::Sender<NotificationEvent>>>>,
 }
 
 impl Clone for Manager {
     fn clone(&self) -> Self {
         Self {
             inner: Arc::clone(&self.inner),
+            mutes: Arc::clone(&self.mutes),
+            dismissed: Arc::clone(&self.dismissed),
+            subscribers: Arc::clone(&self.subscribers),
         }
     }
 }
 
+/// How many dismissed notifications are kept for undo-close.
+const DISMISSED_STACK_LIMIT: usize = 10;
+
 impl Manager {
     /// Initializes the notification manager.
     pub fn init() -> Self {
         Self {
             inner: Arc::new(RwLock::new(Vec::new())),
+            mutes: Arc::new(RwLock::new(Vec::new())),
+            dismissed: Arc::new(RwLock::new(Vec::new())),
+            subscribers: Arc::new(RwLock::new(Vec::new())),
+        }
+    }
+
+    /// Subscribes to the notification event stream.
+    ///
+    /// Every buffer change is mirrored to all live subscribers; dropping
+    /// the receiver unsubscribes on the next event.
+    pub fn subscribe(&self) -> mpsc::Receiver<NotificationEvent> {
+        let (sender, receiver) = mpsc::channel();
+        self.subscribers
+            .write()
+            .expect("failed to retrieve subscribers")
+            .push(sender);
+        receiver
+    }
+
+    /// Sends an event to all subscribers, dropping closed channels.
+    pub(crate) fn emit(&self, event: NotificationEvent) {
+        self.subscribers
+            .write()
+            .expect("failed to retrieve subscribers")
+            .retain(|subscriber| subscriber.send(event.clone()).is_ok());
+    }
+
+    /// Remembers a dismissed notification for undo-close, dropping the
+    /// oldest entry when the stack is full.
+    fn remember_dismissed(&self, notification: Notification) {
+        let mut dismissed = self
+            .dismissed
+            .write()
+            .expect("failed to retrieve dismissed notifications");
+        if dismissed.len() >= DISMISSED_STACK_LIMIT {
+            dismissed.remove(0);
+        }
+        dismissed.push(notification);
+    }
+
+    /// Restores the most recently dismissed notification to the unread
+    /// buffer, returning its ID.
+    pub fn restore_dismissed(&self) -> Option<u32> {
+        let notification = self
+            .dismissed
+            .write()
+            .expect("failed to retrieve dismissed notifications")
+            .pop()?;
+        let id = notification.id;
+        let mut notifications = self
+            .inner
+            .write()
+            .expect("failed to retrieve notifications");
+        if let Some(existing) = notifications.iter_mut().find(|v| v.id == id) {
+            existing.is_read = false;
+        } else {
+            let mut notification = notification;
+            notification.is_read = false;
+            notifications.push(notification);
         }
+        Some(id)
+    }
+
+    /// Mutes applications matching the glob pattern, optionally expiring
+    /// after the given duration.
+    ///
+    /// An existing mute for the same pattern is replaced.
+    pub fn mute(&self, pattern: String, duration: Option<Duration>) {
+        let expires_at = duration.map(|duration| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0)
+                + duration.as_secs()
+        });
+        let mut mutes = self.mutes.write().expect("failed to retrieve mutes");
+        mutes.retain(|mute| mute.pattern != pattern);
+        mutes.push(Mute {
+            pattern,
+            expires_at,
+        });
+    }
+
+    /// Removes the mute with the given pattern.
+    ///
+    /// Returns true if a mute existed for the pattern.
+    pub fn unmute(&self, pattern: &str) -> bool {
+        let mut mutes = self.mutes.write().expect("failed to retrieve mutes");
+        let before = mutes.len();
+        mutes.retain(|mute| mute.pattern != pattern);
+        mutes.len() != before
+    }
+
+    /// Returns the active (non-expired) mutes.
+    pub fn mutes(&self) -> Vec<Mute> {
+        self.prune_expired_mutes();
+        self.mutes
+            .read()
+            .expect("failed to retrieve mutes")
+            .clone()
+    }
+
+    /// Returns true if notifications from the application are muted.
+    pub fn is_muted(&self, app_name: &str) -> bool {
+        self.prune_expired_mutes();
+        self.mutes
+            .read()
+            .expect("failed to retrieve mutes")
+            .iter()
+            .any(|mute| crate::config::glob_match(&mute.pattern, app_name))
+    }
+
+    /// Drops mutes whose expiry time has passed.
+    fn prune_expired_mutes(&self) {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        self.mutes
+            .write()
+            .expect("failed to retrieve mutes")
+            .retain(|mute| mute.expires_at.is_none_or(|t| t > now));
     }
 
     /// Returns the number of notifications.
@@ -201,10 +841,96 @@ impl Manager {
 
     /// Adds a new notifications to manage.
     pub fn add(&self, notification: Notification) {
+        let event = NotificationEvent::Received(notification.clone());
         self.inner
             .write()
             .expect("failed to retrieve notifications")
             .push(notification);
+        self.emit(event);
+    }
+
+    /// Posts a notification into the unread buffer.
+    ///
+    /// The stable entry point for notifications that do not arrive over
+    /// D-Bus — reminders, local tooling and remote sinks all go through
+    /// here — pairing naturally with [`Notification::builder`].
+    /// Subscribers observe a [`NotificationEvent::Received`].
+    pub fn post(&self, notification: Notification) {
+        self.add(notification);
+    }
+
+    /// Dismisses a notification by ID.
+    ///
+    /// The stable counterpart of [`Manager::post`]; subscribers observe a
+    /// [`NotificationEvent::Closed`], which the daemon relays to D-Bus
+    /// clients as a `NotificationClosed` signal.
+    pub fn dismiss(&self, id: u32) {
+        self.mark_as_read(id);
+    }
+
+    /// Dismisses every unread notification.
+    ///
+    /// Subscribers observe one [`NotificationEvent::Closed`] per
+    /// dismissed entry.
+    pub fn dismiss_all(&self) {
+        self.mark_all_as_read();
+    }
+
+    /// Caps the number of notifications kept in memory, evicting the
+    /// oldest already-read entries first.
+    ///
+    /// Unread entries are never evicted, so the buffer can still exceed
+    /// the cap when everything in it is unread. A cap of 0 disables the
+    /// check.
+    pub fn enforce_buffer_limit(&self, limit: usize) {
+        if limit == 0 {
+            return;
+        }
+        let mut notifications = self
+            .inner
+            .write()
+            .expect("failed to retrieve notifications");
+        let mut excess = notifications.len().saturating_sub(limit);
+        if excess == 0 {
+            return;
+        }
+        notifications.retain(|v| {
+            if excess > 0 && v.is_read {
+                excess -= 1;
+                false
+            } else {
+                true
+            }
+        });
+    }
+
+    /// Stacks a duplicate onto an already-displayed unread entry.
+    ///
+    /// When an unread notification with the same content hash exists, its
+    /// repeat counter is bumped and its age refreshed, and the id of the
+    /// displayed entry is returned; the incoming duplicate should then be
+    /// dropped instead of displayed as a second entry.
+    pub fn bump_duplicate(&self, notification: &Notification) -> Option<u32> {
+        let mut notifications = self
+            .inner
+            .write()
+            .expect("failed to retrieve notifications");
+        let hash = notification.content_hash();
+        let existing = notifications
+            .iter_mut()
+            .find(|v| !v.is_read && v.content_hash() == hash)?;
+        existing.repeats += 1;
+        existing.timestamp = notification.timestamp;
+        Some(existing.id)
+    }
+
+    /// Returns the notification with the given content hash, if any.
+    pub fn find_by_hash(&self, hash: u64) -> Option<Notification> {
+        let notifications = self.inner.read().expect("failed to retrieve notifications");
+        notifications
+            .iter()
+            .find(|v| v.content_hash() == hash)
+            .cloned()
     }
 
     /// Returns the last unread notification, if any.
@@ -221,6 +947,10 @@ impl Manager {
             .expect("failed to retrieve notifications");
         if let Some(notification) = notifications.iter_mut().filter(|v| !v.is_read).last() {
             notification.is_read = true;
+            let dismissed = notification.clone();
+            drop(notifications);
+            self.emit(NotificationEvent::Closed(dismissed.id));
+            self.remember_dismissed(dismissed);
         }
     }
 
@@ -257,8 +987,13 @@ impl Manager {
         if let Some(notification) = notifications
             .iter_mut()
             .find(|notification| notification.id == id)
+            .filter(|notification| !notification.is_read)
         {
             notification.is_read = true;
+            let dismissed = notification.clone();
+            drop(notifications);
+            self.emit(NotificationEvent::Closed(id));
+            self.remember_dismissed(dismissed);
         }
     }
 
@@ -268,7 +1003,44 @@ impl Manager {
             .inner
             .write()
             .expect("failed to retrieve notifications");
-        notifications.iter_mut().for_each(|v| v.is_read = true);
+        let newly_dismissed: Vec<Notification> = notifications
+            .iter_mut()
+            .filter(|v| !v.is_read)
+            .map(|v| {
+                v.is_read = true;
+                v.clone()
+            })
+            .collect();
+        drop(notifications);
+        for notification in newly_dismissed {
+            self.emit(NotificationEvent::Closed(notification.id));
+            self.remember_dismissed(notification);
+        }
+    }
+
+    /// Marks all non-critical notifications as read, returning how many
+    /// were dismissed. Quiet hours use this to clear the screen while
+    /// criticals stay up.
+    pub fn mark_non_critical_as_read(&self) -> usize {
+        let mut notifications = self
+            .inner
+            .write()
+            .expect("failed to retrieve notifications");
+        let newly_dismissed: Vec<Notification> = notifications
+            .iter_mut()
+            .filter(|v| !v.is_read && !matches!(v.urgency, Urgency::Critical))
+            .map(|v| {
+                v.is_read = true;
+                v.clone()
+            })
+            .collect();
+        drop(notifications);
+        let count = newly_dismissed.len();
+        for notification in newly_dismissed {
+            self.emit(NotificationEvent::Closed(notification.id));
+            self.remember_dismissed(notification);
+        }
+        count
     }
 
     /// Returns the number of unread notifications.
@@ -305,9 +1077,74 @@ impl Manager {
         }
     }
 
-    /// Enforces the display limit by marking oldest unread notifications as read.
+    /// Returns a window of N unread notifications (oldest first), scrolled
+    /// by `offset` and selected according to the overflow policy.
+    ///
+    /// With the default `evict` policy this shows the newest entries and
+    /// the offset pages back towards older ones; `fifo` shows the front of
+    /// the queue with the offset paging towards newer queued entries, and
+    /// `urgency` promotes the most urgent queued entries first. The offset
+    /// is clamped so the window stays full.
+    pub fn get_unread_window(
+        &self,
+        limit: usize,
+        offset: usize,
+        policy: OverflowPolicy,
+    ) -> Vec<Notification> {
+        let notifications = self.inner.read().expect("failed to retrieve notifications");
+        let mut unread: Vec<Notification> = notifications
+            .iter()
+            .filter(|v| !v.is_read)
+            .cloned()
+            .collect();
+        drop(notifications);
+        if limit == 0 || unread.len() <= limit {
+            return unread;
+        }
+        match policy {
+            OverflowPolicy::Evict => {
+                let offset = offset.min(unread.len() - limit);
+                let skip_count = unread.len() - limit - offset;
+                unread.into_iter().skip(skip_count).take(limit).collect()
+            }
+            OverflowPolicy::Fifo => {
+                let offset = offset.min(unread.len() - limit);
+                unread.into_iter().skip(offset).take(limit).collect()
+            }
+            OverflowPolicy::Urgency => {
+                // Pick the most urgent entries (oldest first within the
+                // same urgency), then restore arrival order for display
+                let mut indexed: Vec<(usize, Notification)> =
+                    unread.drain(..).enumerate().collect();
+                indexed.sort_by_key(|(idx, n)| (std::cmp::Reverse(n.urgency.level()), *idx));
+                let offset = offset.min(indexed.len() - limit);
+                let mut window: Vec<(usize, Notification)> =
+                    indexed.into_iter().skip(offset).take(limit).collect();
+                window.sort_by_key(|(idx, _)| *idx);
+                window.into_iter().map(|(_, n)| n).collect()
+            }
+        }
+    }
+
+    /// Returns whether the notification would be visible in the current
+    /// display window; queued overflow entries pause their timeout while
+    /// hidden.
+    pub fn is_displayed(
+        &self,
+        id: u32,
+        limit: usize,
+        offset: usize,
+        policy: OverflowPolicy,
+    ) -> bool {
+        self.get_unread_window(limit, offset, policy)
+            .iter()
+            .any(|n| n.id == id)
+    }
+
+    /// Enforces the display limit by marking unread notifications as read
+    /// according to the given eviction policy.
     /// Returns the IDs of notifications that were marked as read.
-    pub fn enforce_limit(&self, limit: usize) -> Vec<u32> {
+    pub fn enforce_limit(&self, limit: usize, policy: EvictionPolicy) -> Vec<u32> {
         if limit == 0 {
             return Vec::new();
         }
@@ -315,7 +1152,7 @@ impl Manager {
             .inner
             .write()
             .expect("failed to retrieve notifications");
-        let unread_indices: Vec<usize> = notifications
+        let mut unread_indices: Vec<usize> = notifications
             .iter()
             .enumerate()
             .filter(|(_, v)| !v.is_read)
@@ -323,12 +1160,45 @@ impl Manager {
             .collect();
 
         let mut evicted_ids = Vec::new();
-        if unread_indices.len() > limit {
-            let to_evict = unread_indices.len() - limit;
-            for &idx in unread_indices.iter().take(to_evict) {
-                notifications[idx].is_read = true;
-                evicted_ids.push(notifications[idx].id);
-            }
+        while unread_indices.len() > limit {
+            // Pick the index to evict according to the policy. The vector is
+            // ordered oldest first, so ties always fall back to the oldest entry.
+            let position = match policy {
+                EvictionPolicy::Oldest => 0,
+                EvictionPolicy::LowestUrgency => unread_indices
+                    .iter()
+                    .enumerate()
+                    .min_by_key(|(_, &idx)| notifications[idx].urgency.level())
+                    .map(|(pos, _)| pos)
+                    .unwrap_or(0),
+                EvictionPolicy::LargestGroup => {
+                    let mut group_sizes: HashMap<&str, usize> = HashMap::new();
+                    for &idx in &unread_indices {
+                        *group_sizes
+                            .entry(notifications[idx].app_name.as_str())
+                            .or_default() += 1;
+                    }
+                    unread_indices
+                        .iter()
+                        .enumerate()
+                        .max_by_key(|(pos, &idx)| {
+                            // Prefer the largest group; prefer older entries on ties.
+                            (
+                                group_sizes[notifications[idx].app_name.as_str()],
+                                usize::MAX - pos,
+                            )
+                        })
+                        .map(|(pos, _)| pos)
+                        .unwrap_or(0)
+                }
+            };
+            let idx = unread_indices.remove(position);
+            notifications[idx].is_read = true;
+            evicted_ids.push(notifications[idx].id);
+        }
+        drop(notifications);
+        for id in &evicted_ids {
+            self.emit(NotificationEvent::Closed(*id));
         }
         evicted_ids
     }
@@ -336,6 +1206,249 @@ impl Manager {
 #[cfg(test)]
 mod tests {
     use super::*;
+
+    #[test]
+    fn test_notification_builder() {
+        let notification = Notification::builder()
+            .app_name("tester")
+            .summary("hello")
+            .body("world")
+            .urgency(Urgency::Critical)
+            .action("default", "Open")
+            .build();
+        assert_eq!(notification.app_name, "tester");
+        assert!(matches!(notification.urgency, Urgency::Critical));
+        assert_eq!(notification.actions, vec!["default", "Open"]);
+        assert!(notification.id >= 0x4000_0000);
+        assert!(notification.timestamp > 0);
+
+        // Explicit values win over the allocated defaults
+        let other = Notification::builder().id(42).timestamp(7).build();
+        assert_eq!(other.id, 42);
+        assert_eq!(other.timestamp, 7);
+        assert_ne!(Notification::builder().build().id, notification.id);
+    }
+
+    #[test]
+    fn test_bar_filter() {
+        let mut template = Tera::default();
+        template
+            .add_raw_template("bar", "{{ value | bar(width=10) }}")
+            .unwrap();
+        register_template_extensions(&mut template);
+        let mut context = TeraContext::new();
+        context.insert("value", &40);
+        assert_eq!(template.render("bar", &context).unwrap(), "████░░░░░░");
+        context.insert("value", &150);
+        assert_eq!(template.render("bar", &context).unwrap(), "██████████");
+    }
+
+    #[test]
+    fn test_text_filters() {
+        let mut template = Tera::default();
+        template
+            .add_raw_template(
+                "text",
+                "{{ value | strip_markup | regex_replace(pattern=\"^\\[bot\\] \", replacement=\"\") \
+                 | title_case | truncate_chars(length=12) }}",
+            )
+            .unwrap();
+        register_template_extensions(&mut template);
+        let mut context = TeraContext::new();
+        context.insert("value", "[bot] <b>build</b> passed on main");
+        assert_eq!(template.render("text", &context).unwrap(), "Build Passed…");
+        context.insert("value", "short");
+        assert_eq!(template.render("text", &context).unwrap(), "Short");
+    }
+
+    #[test]
+    fn test_expand_shortcodes() {
+        assert_eq!(expand_shortcodes("ship it :rocket:"), "ship it 🚀");
+        assert_eq!(expand_shortcodes(":tada: released"), "🎉 released");
+        assert_eq!(expand_shortcodes("meeting at 12:30"), "meeting at 12:30");
+        assert_eq!(expand_shortcodes(":notarealemoji:"), ":notarealemoji:");
+        assert_eq!(expand_shortcodes("no closing :rocket"), "no closing :rocket");
+    }
+
+    #[test]
+    fn test_shell_escape() {
+        assert_eq!(shell_escape("hello"), "'hello'");
+        assert_eq!(shell_escape("it's"), "'it'\\''s'");
+        assert_eq!(shell_escape("$(reboot)"), "'$(reboot)'");
+        assert_eq!(shell_escape("`id`"), "'`id`'");
+    }
+
+    #[test]
+    fn test_command_context_escaping() {
+        let notification = Notification {
+            app_name: String::from("app"),
+            summary: String::from("'; rm -rf ~; echo '"),
+            body: String::from("`touch /tmp/pwned` $(id)"),
+            ..Default::default()
+        };
+        let context = notification
+            .into_command_context(String::from("normal"), 0)
+            .unwrap();
+        let command = Tera::one_off("notify-send {{summary}} {{body}}", &context, false).unwrap();
+        assert_eq!(
+            command,
+            "notify-send ''\\''; rm -rf ~; echo '\\''' '`touch /tmp/pwned` $(id)'"
+        );
+        // The raw values stay available as an escape hatch
+        let raw = Tera::one_off("{{body_raw}}", &context, false).unwrap();
+        assert_eq!(raw, "`touch /tmp/pwned` $(id)");
+    }
+
+    #[test]
+    fn test_content_hash() {
+        let notification = Notification {
+            id: 1,
+            app_name: String::from("app"),
+            summary: String::from("summary"),
+            body: String::from("body"),
+            timestamp: 1000,
+            ..Default::default()
+        };
+        let mut same_content = notification.clone();
+        same_content.id = 42;
+        same_content.timestamp = 2000;
+        assert_eq!(notification.content_hash(), same_content.content_hash());
+
+        let mut different = notification.clone();
+        different.body = String::from("other body");
+        assert_ne!(notification.content_hash(), different.content_hash());
+    }
+
+    #[test]
+    fn test_overflow_window_policies() {
+        let manager = Manager::init();
+        for (id, urgency) in [
+            (1, Urgency::Low),
+            (2, Urgency::Critical),
+            (3, Urgency::Normal),
+            (4, Urgency::Normal),
+        ] {
+            manager.add(Notification {
+                id,
+                urgency,
+                ..Default::default()
+            });
+        }
+        let ids =
+            |v: Vec<Notification>| v.iter().map(|n| n.id).collect::<Vec<_>>();
+
+        // Evict shows the newest, fifo the front of the queue, urgency the
+        // most urgent entries in arrival order
+        assert_eq!(ids(manager.get_unread_window(2, 0, OverflowPolicy::Evict)), vec![3, 4]);
+        assert_eq!(ids(manager.get_unread_window(2, 0, OverflowPolicy::Fifo)), vec![1, 2]);
+        assert_eq!(ids(manager.get_unread_window(2, 0, OverflowPolicy::Urgency)), vec![2, 3]);
+
+        assert!(manager.is_displayed(2, 2, 0, OverflowPolicy::Urgency));
+        assert!(!manager.is_displayed(1, 2, 0, OverflowPolicy::Urgency));
+    }
+
+    #[test]
+    fn test_bump_duplicate() {
+        let manager = Manager::init();
+        let notification = Notification {
+            id: 1,
+            app_name: String::from("slack"),
+            summary: String::from("ping"),
+            timestamp: 100,
+            ..Default::default()
+        };
+        manager.add(notification.clone());
+
+        let mut duplicate = notification.clone();
+        duplicate.id = 2;
+        duplicate.timestamp = 160;
+        assert_eq!(manager.bump_duplicate(&duplicate), Some(1));
+        assert_eq!(manager.count(), 1);
+        let shown = manager.get_last_unread().unwrap();
+        assert_eq!(shown.repeats, 1);
+        assert_eq!(shown.timestamp, 160);
+
+        // Entries that were dismissed no longer stack
+        manager.mark_as_read(1);
+        assert_eq!(manager.bump_duplicate(&duplicate), None);
+    }
+
+    #[test]
+    fn test_truncate_bytes() {
+        assert_eq!(truncate_bytes("short", 100), "short");
+        assert_eq!(truncate_bytes("abcdef", 3), "abc…");
+        // Cuts land on character boundaries, never inside one
+        assert_eq!(truncate_bytes("aé", 2), "a…");
+    }
+
+    #[test]
+    fn test_image_data_conversion() {
+        // A 2x1 image with a padded rowstride: opaque red, half-green
+        let raw = [255, 0, 0, 255, 0, 255, 0, 128, 0, 0, 0, 0];
+        let image = ImageData::from_rgb(2, 1, 12, true, &raw).unwrap();
+        assert_eq!(image.data.len(), 8);
+        let first = u32::from_ne_bytes(image.data[..4].try_into().unwrap());
+        let second = u32::from_ne_bytes(image.data[4..].try_into().unwrap());
+        assert_eq!(first, 0xFFFF_0000);
+        // Premultiplied: green scaled by its alpha
+        assert_eq!(second, 0x8000_8000);
+
+        // A buffer shorter than the advertised dimensions is rejected
+        assert!(ImageData::from_rgb(2, 2, 12, true, &raw).is_none());
+    }
+
+    #[test]
+    fn test_enforce_buffer_limit() {
+        let manager = Manager::init();
+        for id in 1..=5 {
+            manager.add(Notification {
+                id,
+                is_read: id <= 3,
+                ..Default::default()
+            });
+        }
+
+        // Only read entries are evicted, oldest first
+        manager.enforce_buffer_limit(3);
+        assert_eq!(manager.count(), 3);
+        assert_eq!(manager.get_unread_count(), 2);
+
+        // An all-unread buffer may stay over the cap
+        manager.enforce_buffer_limit(1);
+        assert_eq!(manager.count(), 2);
+        assert_eq!(manager.get_unread_count(), 2);
+    }
+
+    #[test]
+    fn test_subscribe_events() {
+        let manager = Manager::init();
+        let events = manager.subscribe();
+        manager.add(Notification {
+            id: 1,
+            ..Default::default()
+        });
+        manager.mark_as_read(1);
+        assert!(matches!(
+            events.recv(),
+            Ok(NotificationEvent::Received(n)) if n.id == 1
+        ));
+        assert!(matches!(events.recv(), Ok(NotificationEvent::Closed(1))));
+
+        // A dropped receiver is pruned on the next emit
+        drop(events);
+        manager.add(Notification {
+            id: 2,
+            ..Default::default()
+        });
+        assert!(
+            manager
+                .subscribers
+                .read()
+                .expect("failed to retrieve subscribers")
+                .is_empty()
+        );
+    }
+
     #[test]
     fn test_notification_filter() {
         let notification = Notification {
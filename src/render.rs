@@ -0,0 +1,144 @@
+//! Dedicated popup render thread.
+//!
+//! Drawing used to run inline on whichever thread needed it — the main
+//! loop for shows and closes, the X11 event thread for exposes and
+//! clicks — so one slow Cairo/Pango pass over a huge body stalled
+//! notification intake and input handling behind it. The renderer owns
+//! all popup drawing on a single thread fed by a channel of requests,
+//! and bursts coalesce down to the latest requested state instead of
+//! queueing a draw for every update.
+
+use crate::config::{AnimationConfig, Config};
+use crate::error::Result;
+use crate::notification::{Action, Manager, Notification};
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::sync::{Arc, RwLock};
+use std::thread;
+
+/// A display backend the render thread drives.
+///
+/// [`X11Backend`](crate::x11::X11Backend) is the only implementation
+/// today; the trait keeps the manager and D-Bus code unaware of the
+/// windowing system, so a Wayland, terminal or headless backend can slot
+/// in behind the same [`Renderer`] handle.
+pub trait Backend: Send + Sync {
+    /// Shows the popup, animating it in when it was hidden.
+    fn show(&self, animation: &AnimationConfig) -> Result<()>;
+
+    /// Hides the popup immediately.
+    fn hide(&self) -> Result<()>;
+
+    /// Hides the popup, animating it out first when enabled.
+    fn hide_animated(&self, animation: &AnimationConfig) -> Result<()>;
+
+    /// Draws the current unread buffer.
+    fn draw(
+        &self,
+        manager: &Manager,
+        config: &RwLock<Config>,
+        display_limit: usize,
+    ) -> Result<()>;
+
+    /// Serves input events until the connection closes.
+    ///
+    /// Clicked notifications are reported through `on_press` as
+    /// (notifications, clicked_index, invoke_action); other interactions
+    /// post [`Action`]s on the sender and queue draws on the renderer.
+    fn handle_input(
+        &self,
+        manager: Manager,
+        config: Arc<RwLock<Config>>,
+        sender: Sender<Action>,
+        renderer: Renderer,
+        on_press: Box<dyn Fn(Vec<Notification>, Option<usize>, bool) + Send>,
+    ) -> Result<()>;
+}
+
+/// A popup update for the render thread.
+///
+/// Every variant draws (or hides) the current buffer state when it is
+/// served, so a newer request fully supersedes an older one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RenderRequest {
+    /// Repaint the contents in place (exposes, refreshes, scrolling).
+    Redraw,
+    /// Hide/show cycle for content changes that resize the popup.
+    Show,
+    /// Hide the popup immediately.
+    Hide,
+    /// Animate the popup out before hiding it.
+    HideAnimated,
+}
+
+/// Handle to the render thread.
+#[derive(Clone)]
+pub struct Renderer {
+    /// Channel feeding the render thread.
+    sender: Sender<RenderRequest>,
+}
+
+impl Renderer {
+    /// Spawns the render thread and returns a handle for requesting draws.
+    pub fn spawn(
+        manager: Manager,
+        backend: Arc<dyn Backend>,
+        config: Arc<RwLock<Config>>,
+    ) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        thread::Builder::new()
+            .name("runst-render".to_string())
+            .spawn(move || Self::run(receiver, manager, backend, config))
+            .expect("failed to spawn renderer");
+        Self { sender }
+    }
+
+    /// Queues a popup update, logging if the thread is gone.
+    pub fn request(&self, request: RenderRequest) {
+        if self.sender.send(request).is_err() {
+            log::warn!("render thread is not running");
+        }
+    }
+
+    /// Render thread body: serves the latest requested state, coalescing
+    /// requests that queued up behind a slow draw.
+    fn run(
+        receiver: Receiver<RenderRequest>,
+        manager: Manager,
+        backend: Arc<dyn Backend>,
+        config: Arc<RwLock<Config>>,
+    ) {
+        while let Ok(mut request) = receiver.recv() {
+            loop {
+                match receiver.try_recv() {
+                    Ok(next) => request = next,
+                    Err(TryRecvError::Empty) => break,
+                    Err(TryRecvError::Disconnected) => return,
+                }
+            }
+            let result = match request {
+                RenderRequest::Redraw => {
+                    let display_limit = config
+                        .read()
+                        .expect("failed to read config")
+                        .global
+                        .display_limit;
+                    backend.draw(&manager, &config, display_limit)
+                }
+                RenderRequest::Show => {
+                    let animation =
+                        config.read().expect("failed to read config").animation.clone();
+                    backend.hide().and_then(|_| backend.show(&animation))
+                }
+                RenderRequest::Hide => backend.hide(),
+                RenderRequest::HideAnimated => {
+                    let animation =
+                        config.read().expect("failed to read config").animation.clone();
+                    backend.hide_animated(&animation)
+                }
+            };
+            if let Err(e) = result {
+                log::warn!("failed to render notification window: {}", e);
+            }
+        }
+    }
+}
@@ -0,0 +1,40 @@
+//! Shared async runtime for the daemon.
+//!
+//! The D-Bus server, the GNTP listener and future network integrations
+//! run as tasks on one small tokio runtime instead of each spawning a
+//! dedicated OS thread (and, before this, a private runtime per thread).
+//! The X11 connection, the render thread and the disk-bound history
+//! writer stay on their own threads: they block by nature and would only
+//! starve the async workers.
+
+use std::future::Future;
+use std::sync::OnceLock;
+use tokio::runtime::Runtime;
+use tokio::task::JoinHandle;
+
+/// Worker threads serving all async tasks.
+const WORKER_THREADS: usize = 2;
+
+/// The shared runtime, started on first use.
+static RUNTIME: OnceLock<Runtime> = OnceLock::new();
+
+/// Returns the shared runtime, starting it on first use.
+pub fn runtime() -> &'static Runtime {
+    RUNTIME.get_or_init(|| {
+        tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(WORKER_THREADS)
+            .thread_name("runst-async")
+            .enable_all()
+            .build()
+            .expect("failed to start async runtime")
+    })
+}
+
+/// Spawns a task on the shared runtime.
+pub fn spawn<F>(future: F) -> JoinHandle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    runtime().spawn(future)
+}
@@ -0,0 +1,91 @@
+//! Versioned JSON model shared by the exporting surfaces.
+//!
+//! Snapshot export/import, `runst history --export` and event feeds all
+//! emit the same envelope: a `schema_version` field next to the payload.
+//! Within a version, fields are only ever added — never removed, renamed
+//! or retyped — so downstream scripts should ignore unknown fields and
+//! keep working when the daemon updates. The version is bumped only for
+//! a breaking change, and readers reject documents from a newer version
+//! instead of silently misreading them.
+
+use crate::error::{Error, Result};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+/// Current version of the JSON model.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// The versioned envelope around an exported payload.
+#[derive(Debug, Deserialize, Serialize)]
+struct Envelope<T> {
+    /// Version of the JSON model the payload follows.
+    schema_version: u32,
+    /// The exported payload.
+    data: T,
+}
+
+/// Serializes a payload into a pretty-printed versioned document.
+pub fn to_json<T: Serialize>(data: T) -> Result<String> {
+    Ok(serde_json::to_string_pretty(&Envelope {
+        schema_version: SCHEMA_VERSION,
+        data,
+    })?)
+}
+
+/// Deserializes a versioned document back into its payload.
+///
+/// Documents from a newer schema version are rejected. Bare payloads
+/// without an envelope — exports from before versioning existed — are
+/// still accepted.
+pub fn from_json<T: DeserializeOwned>(json: &str) -> Result<T> {
+    match serde_json::from_str::<Envelope<T>>(json) {
+        Ok(envelope) => {
+            if envelope.schema_version > SCHEMA_VERSION {
+                return Err(Error::Config(format!(
+                    "document uses schema version {} but this build supports up to {}",
+                    envelope.schema_version, SCHEMA_VERSION
+                )));
+            }
+            Ok(envelope.data)
+        }
+        Err(envelope_error) => {
+            // Legacy pre-envelope export: the payload at the top level
+            serde_json::from_str::<T>(json).map_err(|_| Error::Json(envelope_error))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notification::Notification;
+
+    #[test]
+    fn test_schema_round_trip() {
+        let notifications = vec![
+            Notification::builder().summary("one").build(),
+            Notification::builder().summary("two").build(),
+        ];
+        let json = to_json(&notifications).unwrap();
+        assert!(json.contains("\"schema_version\": 1"));
+
+        let restored: Vec<Notification> = from_json(&json).unwrap();
+        assert_eq!(restored.len(), 2);
+        assert_eq!(restored[0].summary, "one");
+    }
+
+    #[test]
+    fn test_schema_legacy_payload() {
+        // Exports from before the envelope existed are a bare array
+        let legacy = "[{\"summary\": \"old\"}]";
+        let restored: Vec<Notification> = from_json(legacy).unwrap();
+        assert_eq!(restored.len(), 1);
+        assert_eq!(restored[0].summary, "old");
+    }
+
+    #[test]
+    fn test_schema_rejects_newer_version() {
+        let json = "{\"schema_version\": 99, \"data\": []}";
+        assert!(from_json::<Vec<Notification>>(&json).is_err());
+    }
+}
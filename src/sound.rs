@@ -0,0 +1,236 @@
+//! Notification sound support.
+//!
+//! Resolves `sound-name` hints against the freedesktop sound theme
+//! specification so that names like "message-new-instant" map to an actual
+//! file, falling back to the `freedesktop` theme when the configured theme
+//! has no match, and plays the resolved file through rodio.
+
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::error::Error as StdError;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use std::thread;
+
+/// Theme searched when the configured theme has no match.
+const FALLBACK_THEME: &str = "freedesktop";
+
+/// File extensions accepted by the spec, in preference order.
+const EXTENSIONS: &[&str] = &["oga", "ogg", "wav"];
+
+/// Output profile directories, in preference order.
+const PROFILES: &[&str] = &["stereo", ""];
+
+/// Sound configuration.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SoundConfig {
+    /// Sound theme to resolve sound names against.
+    #[serde(default = "default_theme")]
+    pub theme: String,
+    /// Playback volume from 0.0 to 1.0.
+    #[serde(default = "default_volume")]
+    pub volume: f32,
+    /// Audio device to play through. Unset plays on the default sink.
+    #[serde(default)]
+    pub device: Option<String>,
+    /// Whether sounds are muted entirely (set during quiet hours).
+    #[serde(default)]
+    pub mute: bool,
+}
+
+impl Default for SoundConfig {
+    fn default() -> Self {
+        Self {
+            theme: default_theme(),
+            volume: default_volume(),
+            device: None,
+            mute: false,
+        }
+    }
+}
+
+impl SoundConfig {
+    /// Returns the volume to play at, honoring mute and a per-urgency
+    /// override, clamped to the valid range.
+    pub fn effective_volume(&self, override_volume: Option<f32>) -> f32 {
+        if self.mute {
+            0.0
+        } else {
+            override_volume.unwrap_or(self.volume).clamp(0.0, 1.0)
+        }
+    }
+}
+
+/// Default sound theme.
+fn default_theme() -> String {
+    String::from(FALLBACK_THEME)
+}
+
+/// Default playback volume.
+fn default_volume() -> f32 {
+    1.0
+}
+
+/// Returns the base directories containing `sounds/` per the XDG spec.
+fn sound_dirs() -> Vec<PathBuf> {
+    let mut sound_dirs = Vec::new();
+    if let Some(data_home) = env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .or_else(|| dirs::home_dir().map(|p| p.join(".local/share")))
+    {
+        sound_dirs.push(data_home.join("sounds"));
+    }
+    let data_dirs = env::var("XDG_DATA_DIRS")
+        .unwrap_or_else(|_| String::from("/usr/local/share:/usr/share"));
+    for dir in data_dirs.split(':').filter(|v| !v.is_empty()) {
+        sound_dirs.push(PathBuf::from(dir).join("sounds"));
+    }
+    sound_dirs
+}
+
+/// Resolves a sound name (or absolute path) to a playable file.
+///
+/// Sound names are looked up in the given theme first and then in the
+/// fallback theme, dropping trailing `-` separated components until a match
+/// is found (e.g. `message-new-instant` → `message-new` → `message`).
+pub fn resolve(name: &str, theme: &str) -> Option<PathBuf> {
+    let path = Path::new(name);
+    if path.is_absolute() {
+        return path.exists().then(|| path.to_path_buf());
+    }
+    resolve_in(&sound_dirs(), name, theme)
+}
+
+/// Resolves a sound name within the given base directories.
+fn resolve_in(dirs: &[PathBuf], name: &str, theme: &str) -> Option<PathBuf> {
+    let mut candidate = name;
+    loop {
+        for theme in [theme, FALLBACK_THEME] {
+            if let Some(path) = lookup(dirs, theme, candidate) {
+                return Some(path);
+            }
+        }
+        match candidate.rsplit_once('-') {
+            Some((rest, _)) => candidate = rest,
+            None => return None,
+        }
+    }
+}
+
+/// Plays a sound file on a background thread at the given volume.
+///
+/// Playback errors are logged rather than surfaced; a missing audio stack
+/// should never keep a notification from being displayed.
+pub fn play(path: PathBuf, volume: f32, device: Option<String>) {
+    if volume <= 0.0 {
+        return;
+    }
+    thread::Builder::new()
+        .name("runst-sound".to_string())
+        .spawn(move || {
+            if let Err(e) = play_blocking(&path, volume, device.as_deref()) {
+                log::warn!("failed to play {}: {}", path.display(), e);
+            }
+        })
+        .expect("failed to spawn sound thread");
+}
+
+/// Plays a sound file, blocking until it finishes.
+fn play_blocking(path: &Path, volume: f32, device: Option<&str>) -> Result<(), Box<dyn StdError>> {
+    let stream = open_stream(device)?;
+    let sink = rodio::Sink::connect_new(stream.mixer());
+    sink.set_volume(volume);
+    sink.append(rodio::Decoder::new(BufReader::new(File::open(path)?))?);
+    sink.sleep_until_end();
+    Ok(())
+}
+
+/// Opens an output stream on the configured device, falling back to the
+/// default sink when the device is unset or missing.
+fn open_stream(device: Option<&str>) -> Result<rodio::OutputStream, Box<dyn StdError>> {
+    if let Some(name) = device {
+        use rodio::cpal::traits::{DeviceTrait, HostTrait};
+        match rodio::cpal::default_host()
+            .output_devices()?
+            .find(|device| device.name().map(|n| n == name).unwrap_or(false))
+        {
+            Some(device) => {
+                return Ok(rodio::OutputStreamBuilder::from_device(device)?.open_stream()?);
+            }
+            None => log::warn!("audio device \"{}\" not found, using the default", name),
+        }
+    }
+    Ok(rodio::OutputStreamBuilder::open_default_stream()?)
+}
+
+/// Looks up a single sound name in a single theme.
+fn lookup(dirs: &[PathBuf], theme: &str, name: &str) -> Option<PathBuf> {
+    for dir in dirs {
+        for profile in PROFILES {
+            let base = if profile.is_empty() {
+                dir.join(theme)
+            } else {
+                dir.join(theme).join(profile)
+            };
+            for extension in EXTENSIONS {
+                let path = base.join(format!("{name}.{extension}"));
+                if path.exists() {
+                    return Some(path);
+                }
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_resolve_with_name_fallback() {
+        let dir = tempdir().unwrap();
+        let theme_dir = dir.path().join("mytheme").join("stereo");
+        fs::create_dir_all(&theme_dir).unwrap();
+        let sound_path = theme_dir.join("message-new.oga");
+        fs::write(&sound_path, []).unwrap();
+
+        let dirs = vec![dir.path().to_path_buf()];
+        // Exact name is missing, so the last component is dropped
+        assert_eq!(
+            resolve_in(&dirs, "message-new-instant", "mytheme"),
+            Some(sound_path.clone())
+        );
+        assert_eq!(
+            resolve_in(&dirs, "message-new", "mytheme"),
+            Some(sound_path)
+        );
+        assert_eq!(resolve_in(&dirs, "battery-low", "mytheme"), None);
+    }
+
+    #[test]
+    fn test_effective_volume() {
+        let mut config = SoundConfig::default();
+        assert_eq!(config.effective_volume(None), 1.0);
+        assert_eq!(config.effective_volume(Some(0.5)), 0.5);
+        assert_eq!(config.effective_volume(Some(2.0)), 1.0);
+        config.mute = true;
+        assert_eq!(config.effective_volume(Some(0.5)), 0.0);
+    }
+
+    #[test]
+    fn test_resolve_fallback_theme() {
+        let dir = tempdir().unwrap();
+        let theme_dir = dir.path().join(FALLBACK_THEME).join("stereo");
+        fs::create_dir_all(&theme_dir).unwrap();
+        let sound_path = theme_dir.join("bell.wav");
+        fs::write(&sound_path, []).unwrap();
+
+        let dirs = vec![dir.path().to_path_buf()];
+        // Name missing from the configured theme resolves via the fallback
+        assert_eq!(resolve_in(&dirs, "bell", "mytheme"), Some(sound_path));
+    }
+}
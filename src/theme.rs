@@ -0,0 +1,106 @@
+//! Built-in and user-loadable color themes.
+//!
+//! A theme maps onto the urgency foreground/background fields and is
+//! selected with `theme = "nord"` in the global configuration. User themes
+//! are loaded from `<config dir>/runst/themes/<name>.toml`.
+
+use crate::error::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+
+/// Colors for a single urgency level.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ThemeColors {
+    /// Background color (hex).
+    pub background: String,
+    /// Foreground color (hex).
+    pub foreground: String,
+}
+
+/// A selectable color theme.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Theme {
+    /// Colors for low urgency.
+    pub urgency_low: ThemeColors,
+    /// Colors for normal urgency.
+    pub urgency_normal: ThemeColors,
+    /// Colors for critical urgency.
+    pub urgency_critical: ThemeColors,
+}
+
+impl Theme {
+    /// Returns a built-in theme by name.
+    pub fn builtin(name: &str) -> Option<Self> {
+        let ((low_bg, low_fg), (normal_bg, normal_fg), (critical_bg, critical_fg)) = match name {
+            "gruvbox" => (
+                ("#282828", "#a89984"),
+                ("#282828", "#ebdbb2"),
+                ("#cc241d", "#fbf1c7"),
+            ),
+            "nord" => (
+                ("#2e3440", "#81a1c1"),
+                ("#3b4252", "#eceff4"),
+                ("#bf616a", "#eceff4"),
+            ),
+            "catppuccin" => (
+                ("#1e1e2e", "#a6adc8"),
+                ("#1e1e2e", "#cdd6f4"),
+                ("#f38ba8", "#11111b"),
+            ),
+            "solarized" => (
+                ("#002b36", "#586e75"),
+                ("#073642", "#839496"),
+                ("#dc322f", "#fdf6e3"),
+            ),
+            _ => return None,
+        };
+        Some(Self {
+            urgency_low: ThemeColors {
+                background: low_bg.to_string(),
+                foreground: low_fg.to_string(),
+            },
+            urgency_normal: ThemeColors {
+                background: normal_bg.to_string(),
+                foreground: normal_fg.to_string(),
+            },
+            urgency_critical: ThemeColors {
+                background: critical_bg.to_string(),
+                foreground: critical_fg.to_string(),
+            },
+        })
+    }
+
+    /// Loads a theme by name: built-ins first, then the user themes directory.
+    pub fn load(name: &str) -> Result<Self> {
+        if let Some(theme) = Self::builtin(name) {
+            return Ok(theme);
+        }
+        let path = dirs::config_dir().map(|p| {
+            p.join(env!("CARGO_PKG_NAME"))
+                .join("themes")
+                .join(format!("{name}.toml"))
+        });
+        if let Some(path) = path.filter(|p| p.exists()) {
+            let contents = fs::read_to_string(&path)?;
+            toml::from_str(&contents).map_err(|e| {
+                Error::Config(format!("failed to parse theme {}: {}", path.display(), e))
+            })
+        } else {
+            Err(Error::Config(format!("unknown theme: {name}")))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_themes() {
+        for name in ["gruvbox", "nord", "catppuccin", "solarized"] {
+            let theme = Theme::builtin(name).expect("missing built-in theme");
+            assert!(theme.urgency_normal.background.starts_with('#'));
+        }
+        assert!(Theme::builtin("no-such-theme").is_none());
+    }
+}
@@ -0,0 +1,169 @@
+//! Central notification expiry timer.
+//!
+//! Auto-clear used to spawn one sleep thread per notification, ticking
+//! on a coarse interval. The timer keeps all expiry deadlines in a
+//! single queue served by one thread that wakes exactly when the next
+//! deadline is due, deferring entries while they are hovered or queued
+//! off screen.
+
+use crate::config::{Config, OverflowPolicy};
+use crate::notification::{Action, Manager};
+use crate::x11::{X11, X11Window};
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::Sender;
+use std::sync::{Arc, Condvar, Mutex, RwLock};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How long a due-but-paused expiry is deferred before being rechecked.
+const PAUSE_RECHECK: Duration = Duration::from_millis(100);
+
+/// Queue of pending expiries, ordered soonest first.
+type ExpiryQueue = Mutex<BinaryHeap<Reverse<(Instant, u32)>>>;
+
+/// Handle to the expiry timer thread.
+#[derive(Clone)]
+pub struct ExpiryTimer {
+    /// Pending deadlines and the condvar waking the thread on schedule.
+    queue: Arc<(ExpiryQueue, Condvar)>,
+    /// Set when the owning display stack is torn down.
+    shutdown: Arc<AtomicBool>,
+}
+
+impl ExpiryTimer {
+    /// Spawns the timer thread and returns a handle for scheduling.
+    pub fn spawn(
+        manager: Manager,
+        x11: Arc<X11>,
+        window: Arc<X11Window>,
+        config: Arc<RwLock<Config>>,
+        sender: Sender<Action>,
+    ) -> Self {
+        let queue = Arc::new((Mutex::new(BinaryHeap::new()), Condvar::new()));
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let timer = Self {
+            queue: Arc::clone(&queue),
+            shutdown: Arc::clone(&shutdown),
+        };
+        thread::Builder::new()
+            .name("runst-expiry".to_string())
+            .spawn(move || Self::run(queue, shutdown, manager, x11, window, config, sender))
+            .expect("failed to spawn expiry timer");
+        timer
+    }
+
+    /// Stops the timer thread.
+    ///
+    /// Called when the display stack is torn down (display reconnect or
+    /// daemon shutdown) so the thread does not keep polling a dead X11
+    /// connection through its stale handles.
+    pub fn shutdown(&self) {
+        self.shutdown.store(true, Ordering::Relaxed);
+        let (queue, condvar) = &*self.queue;
+        // Take the lock so the wake-up cannot slip in between the
+        // thread's flag check and its wait
+        drop(queue.lock().expect("failed to lock expiry queue"));
+        condvar.notify_one();
+    }
+
+    /// Schedules a notification to auto-clear after the given timeout.
+    pub fn schedule(&self, id: u32, timeout: Duration) {
+        let (queue, condvar) = &*self.queue;
+        queue
+            .lock()
+            .expect("failed to lock expiry queue")
+            .push(Reverse((Instant::now() + timeout, id)));
+        condvar.notify_one();
+    }
+
+    /// Timer thread body: sleeps until the next deadline, then closes the
+    /// due notifications (or defers them while they are paused).
+    fn run(
+        queue: Arc<(ExpiryQueue, Condvar)>,
+        shutdown: Arc<AtomicBool>,
+        manager: Manager,
+        x11: Arc<X11>,
+        window: Arc<X11Window>,
+        config: Arc<RwLock<Config>>,
+        sender: Sender<Action>,
+    ) {
+        let (lock, condvar) = &*queue;
+        let mut heap = lock.lock().expect("failed to lock expiry queue");
+        loop {
+            if shutdown.load(Ordering::Relaxed) {
+                return;
+            }
+            let now = Instant::now();
+            match heap.peek() {
+                // Nothing scheduled: wait until something is
+                None => {
+                    heap = condvar
+                        .wait(heap)
+                        .expect("failed to wait on expiry queue");
+                }
+                // Next deadline still ahead: sleep exactly until then
+                // (or until an earlier one is scheduled)
+                Some(Reverse((deadline, _))) if *deadline > now => {
+                    let wait = *deadline - now;
+                    heap = condvar
+                        .wait_timeout(heap, wait)
+                        .expect("failed to wait on expiry queue")
+                        .0;
+                }
+                Some(_) => {
+                    let Some(Reverse((_, id))) = heap.pop() else {
+                        continue;
+                    };
+                    if !manager.is_unread(id) {
+                        continue;
+                    }
+                    if Self::paused(id, &manager, &x11, &window, &config) {
+                        // Hovered or queued off screen: push the deadline
+                        // back and recheck shortly
+                        heap.push(Reverse((now + PAUSE_RECHECK, id)));
+                        continue;
+                    }
+                    if sender.send(Action::Close(Some(id))).is_err() {
+                        // The main loop is gone; so is our purpose
+                        return;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns whether the notification's expiry is currently paused:
+    /// the pointer hovers the popup, the user has been idle beyond the
+    /// configured threshold, or a queueing overflow policy keeps the
+    /// entry waiting off screen.
+    fn paused(
+        id: u32,
+        manager: &Manager,
+        x11: &X11,
+        window: &X11Window,
+        config: &RwLock<Config>,
+    ) -> bool {
+        if window.is_hovered() {
+            return true;
+        }
+        let (limit, overflow, idle_threshold) = {
+            let config = config.read().expect("failed to read config");
+            (
+                config.global.display_limit,
+                config.global.overflow,
+                config.global.idle_threshold,
+            )
+        };
+        if idle_threshold > 0
+            && x11
+                .idle_time()
+                .is_some_and(|idle| idle >= Duration::from_secs(idle_threshold))
+        {
+            return true;
+        }
+        !matches!(overflow, OverflowPolicy::Evict)
+            && !manager.is_displayed(id, limit, window.get_scroll_offset(), overflow)
+    }
+}
@@ -1,6 +1,12 @@
-use crate::config::{Config, GlobalConfig, Origin};
+use crate::config::{
+    AgeFormat, AnimationConfig, AnimationStyle, Config, GlobalConfig, Origin, OverflowPolicy,
+    TextDirection,
+};
 use crate::error::{Error, Result};
-use crate::notification::{Manager, NOTIFICATION_MESSAGE_TEMPLATE, Notification};
+use crate::notification::{
+    Action, ImageData, Manager, NOTIFICATION_MESSAGE_TEMPLATE, Notification, Urgency,
+};
+use crate::render::{Backend, RenderRequest, Renderer};
 use cairo::{
     Context as CairoContext, XCBConnection as CairoXCBConnection, XCBDrawable, XCBSurface,
     XCBVisualType,
@@ -8,13 +14,19 @@ use cairo::{
 use colorsys::ColorAlpha;
 use pango::{Context as PangoContext, FontDescription, Layout as PangoLayout};
 use pangocairo::functions as pango_functions;
-use std::collections::HashMap;
+use serde::Serialize;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
 use std::error::Error as StdError;
+use std::hash::{Hash, Hasher};
+use std::os::fd::{AsRawFd, RawFd};
+use std::str;
 use std::sync::Arc;
 use std::time::Duration;
-use tera::{Result as TeraResult, Tera, Value};
+use tera::{Context as TeraContext, Tera};
 use x11rb::COPY_DEPTH_FROM_PARENT;
 use x11rb::connection::Connection;
+use x11rb::protocol::randr::ConnectionExt as _;
 use x11rb::protocol::{Event, xproto::*};
 use x11rb::xcb_ffi::XCBConnection;
 
@@ -57,11 +69,28 @@ pub struct X11 {
     connection: XCBConnection,
     cairo: CairoXCBConnection,
     screen: Screen,
+    screen_num: usize,
 }
 
 unsafe impl Send for X11 {}
 unsafe impl Sync for X11 {}
 
+/// Screen area the notification window is positioned against.
+///
+/// Either the whole virtual screen or a single RandR monitor, depending on
+/// the `monitor` configuration option.
+#[derive(Clone, Copy, Debug)]
+pub struct MonitorRect {
+    /// X offset of the area in the virtual screen.
+    pub x: i32,
+    /// Y offset of the area in the virtual screen.
+    pub y: i32,
+    /// Width of the area in pixels.
+    pub width: u16,
+    /// Height of the area in pixels.
+    pub height: u16,
+}
+
 /// Calculates window position based on origin anchor point.
 fn calculate_position_from_origin(
     origin: Origin,
@@ -69,11 +98,10 @@ fn calculate_position_from_origin(
     offset_y: u32,
     width: u32,
     height: u32,
-    screen_width: u16,
-    screen_height: u16,
+    monitor: MonitorRect,
 ) -> (i16, i16) {
-    let screen_w = screen_width as i32;
-    let screen_h = screen_height as i32;
+    let screen_w = monitor.width as i32;
+    let screen_h = monitor.height as i32;
     let off_x = offset_x as i32;
     let off_y = offset_y as i32;
     let w = width as i32;
@@ -86,7 +114,7 @@ fn calculate_position_from_origin(
         Origin::BottomRight => (screen_w - w - off_x, screen_h - h - off_y),
     };
 
-    (x.max(0) as i16, y.max(0) as i16)
+    ((monitor.x + x.max(0)) as i16, (monitor.y + y.max(0)) as i16)
 }
 
 impl X11 {
@@ -96,7 +124,8 @@ impl X11 {
         log::trace!("Default screen num: {:?}", default_screen_num);
         let setup_info = connection.setup();
         log::trace!("Setup info status: {:?}", setup_info.status);
-        let screen = setup_info.roots[screen_num.unwrap_or(default_screen_num)].clone();
+        let screen_num = screen_num.unwrap_or(default_screen_num);
+        let screen = setup_info.roots[screen_num].clone();
         log::trace!("Screen root: {:?}", screen.root);
         let cairo =
             unsafe { CairoXCBConnection::from_raw_none(connection.get_raw_xcb_connection() as _) };
@@ -104,12 +133,87 @@ impl X11 {
             connection,
             screen,
             cairo,
+            screen_num,
         })
     }
 
+    /// Returns how long the user has been idle, according to the X11
+    /// screensaver extension (`None` when the server lacks it).
+    pub fn idle_time(&self) -> Option<Duration> {
+        let reply = x11rb::protocol::screensaver::query_info(&self.connection, self.screen.root)
+            .ok()?
+            .reply()
+            .ok()?;
+        Some(Duration::from_millis(u64::from(reply.ms_since_user_input)))
+    }
+
+    /// Returns whether the currently focused window is fullscreen,
+    /// according to the EWMH `_NET_ACTIVE_WINDOW` and `_NET_WM_STATE`
+    /// hints (false when the window manager does not set them).
+    pub fn focused_window_fullscreen(&self) -> bool {
+        let check = || -> Result<bool> {
+            let active_atom = self
+                .connection
+                .intern_atom(false, b"_NET_ACTIVE_WINDOW")?
+                .reply()?
+                .atom;
+            let state_atom = self
+                .connection
+                .intern_atom(false, b"_NET_WM_STATE")?
+                .reply()?
+                .atom;
+            let fullscreen_atom = self
+                .connection
+                .intern_atom(false, b"_NET_WM_STATE_FULLSCREEN")?
+                .reply()?
+                .atom;
+            let active = self
+                .connection
+                .get_property(
+                    false,
+                    self.screen.root,
+                    active_atom,
+                    AtomEnum::WINDOW,
+                    0,
+                    1,
+                )?
+                .reply()?;
+            let Some(window) = active.value32().and_then(|mut values| values.next()) else {
+                return Ok(false);
+            };
+            if window == 0 {
+                return Ok(false);
+            }
+            let state = self
+                .connection
+                .get_property(false, window, state_atom, AtomEnum::ATOM, 0, 32)?
+                .reply()?;
+            Ok(state
+                .value32()
+                .is_some_and(|mut atoms| atoms.any(|atom| atom == fullscreen_atom)))
+        };
+        check().unwrap_or(false)
+    }
+
     /// Creates a window.
     pub fn create_window(&mut self, config: &GlobalConfig) -> Result<X11Window> {
-        let visual_id = self.screen.root_visual;
+        // Prefer a 32-bit visual when a compositor is running so alpha in
+        // the configured colors yields real translucency; fall back to the
+        // root visual (and depth) otherwise.
+        let (depth, visual_id, colormap) = match self.find_argb_visual() {
+            Some(argb_visual_id) if self.compositor_running() => {
+                let colormap = self.connection.generate_id()?;
+                self.connection.create_colormap(
+                    ColormapAlloc::NONE,
+                    colormap,
+                    self.screen.root,
+                    argb_visual_id,
+                )?;
+                log::debug!("Using ARGB visual {:#x}", argb_visual_id);
+                (32, argb_visual_id, Some(colormap))
+            }
+            _ => (COPY_DEPTH_FROM_PARENT, self.screen.root_visual, None),
+        };
         let mut visual_type = self
             .find_xcb_visualtype(visual_id)
             .ok_or_else(|| Error::X11Other(String::from("cannot find a XCB visual type")))?;
@@ -117,8 +221,7 @@ impl X11 {
         let window_id = self.connection.generate_id()?;
         log::trace!("Window ID: {:?}", window_id);
 
-        let screen_width = self.screen.width_in_pixels;
-        let screen_height = self.screen.height_in_pixels;
+        let monitor = self.resolve_monitor(config.monitor.as_deref());
         let initial_width = config.geometry.width;
         let initial_height = config.geometry.height;
 
@@ -130,23 +233,36 @@ impl X11 {
             config.geometry.y,
             initial_width,
             initial_height,
-            screen_width,
-            screen_height,
+            monitor,
         );
 
         log::debug!(
-            "Creating window at ({}, {}) size {}x{} origin={} screen={}x{}",
+            "Creating window at ({}, {}) size {}x{} origin={} monitor={:?}",
             x,
             y,
             initial_width,
             initial_height,
             config.origin,
-            screen_width,
-            screen_height
+            monitor
         );
 
+        // A non-root depth requires its own colormap, and border/background
+        // pixels must be valid for that depth (0 = fully transparent black).
+        let mut aux = CreateWindowAux::new()
+            .override_redirect(1)
+            .event_mask(
+                EventMask::EXPOSURE
+                    | EventMask::BUTTON_PRESS
+                    | EventMask::KEY_PRESS
+                    | EventMask::ENTER_WINDOW
+                    | EventMask::LEAVE_WINDOW,
+            );
+        aux = match colormap {
+            Some(colormap) => aux.colormap(colormap).border_pixel(0).background_pixel(0),
+            None => aux.border_pixel(self.screen.white_pixel),
+        };
         self.connection.create_window(
-            COPY_DEPTH_FROM_PARENT,
+            depth,
             window_id,
             self.screen.root,
             x,
@@ -156,10 +272,7 @@ impl X11 {
             0,
             WindowClass::INPUT_OUTPUT,
             visual_id,
-            &CreateWindowAux::new()
-                .border_pixel(self.screen.white_pixel)
-                .override_redirect(1)
-                .event_mask(EventMask::EXPOSURE | EventMask::BUTTON_PRESS),
+            &aux,
         )?;
         let surface = XCBSurface::create(
             &self.cairo,
@@ -171,18 +284,105 @@ impl X11 {
         let context = CairoContext::new(&surface)?;
         X11Window::new(
             window_id,
+            self.screen.root,
             surface,
             context,
             &config.font,
-            Box::leak(config.template.to_string().into_boxed_str()),
+            &config.template,
             config.origin,
             config.geometry.x,
             config.geometry.y,
-            screen_width,
-            screen_height,
+            monitor,
         )
     }
 
+    /// Resolves the screen area to position the window against.
+    ///
+    /// The monitor can be selected by RandR output name or index; an
+    /// unknown selection falls back to the whole virtual screen with a
+    /// warning, as does a server without RandR monitor support.
+    fn resolve_monitor(&self, selection: Option<&str>) -> MonitorRect {
+        let whole_screen = MonitorRect {
+            x: 0,
+            y: 0,
+            width: self.screen.width_in_pixels,
+            height: self.screen.height_in_pixels,
+        };
+        let Some(selection) = selection else {
+            return whole_screen;
+        };
+        let monitors = match self
+            .connection
+            .randr_get_monitors(self.screen.root, true)
+            .ok()
+            .and_then(|cookie| cookie.reply().ok())
+        {
+            Some(reply) => reply.monitors,
+            None => {
+                log::warn!("RandR monitor query failed, using the whole screen");
+                return whole_screen;
+            }
+        };
+        let selected = if let Ok(index) = selection.parse::<usize>() {
+            monitors.get(index)
+        } else {
+            monitors.iter().find(|monitor| {
+                self.connection
+                    .get_atom_name(monitor.name)
+                    .ok()
+                    .and_then(|cookie| cookie.reply().ok())
+                    .and_then(|reply| String::from_utf8(reply.name).ok())
+                    .is_some_and(|name| name == selection)
+            })
+        };
+        match selected {
+            Some(monitor) => MonitorRect {
+                x: monitor.x as i32,
+                y: monitor.y as i32,
+                width: monitor.width,
+                height: monitor.height,
+            },
+            None => {
+                log::warn!("monitor `{}` not found, using the whole screen", selection);
+                whole_screen
+            }
+        }
+    }
+
+    /// Checks whether a compositing manager owns the `_NET_WM_CM_Sn`
+    /// selection for this screen.
+    ///
+    /// Without a compositor an ARGB window would render its transparent
+    /// parts as black, so the root visual is the safer choice.
+    fn compositor_running(&self) -> bool {
+        let atom_name = format!("_NET_WM_CM_S{}", self.screen_num);
+        let Some(atom) = self
+            .connection
+            .intern_atom(false, atom_name.as_bytes())
+            .ok()
+            .and_then(|cookie| cookie.reply().ok())
+            .map(|reply| reply.atom)
+        else {
+            return false;
+        };
+        self.connection
+            .get_selection_owner(atom)
+            .ok()
+            .and_then(|cookie| cookie.reply().ok())
+            .is_some_and(|reply| reply.owner != x11rb::NONE)
+    }
+
+    /// Finds a 32-bit TrueColor visual on this screen, if one exists.
+    fn find_argb_visual(&self) -> Option<u32> {
+        self.screen
+            .allowed_depths
+            .iter()
+            .filter(|depth| depth.depth == 32)
+            .flat_map(|depth| &depth.visuals)
+            .find(|visual| visual.class == VisualClass::TRUE_COLOR)
+            .map(|visual| visual.visual_id)
+    }
+
     /// Find a `xcb_visualtype_t` based on its ID number
     fn find_xcb_visualtype(&self, visual_id: u32) -> Option<xcb_visualtype_t> {
         for root in &self.connection.setup().roots {
@@ -197,44 +397,224 @@ impl X11 {
         None
     }
 
-    /// Shows the given X11 window.
-    pub fn show_window(&self, window: &X11Window) -> Result<()> {
+    /// Shows the given X11 window, animating it in when the window was not
+    /// visible before and animations are enabled.
+    pub fn show_window(&self, window: &X11Window, animation: &AnimationConfig) -> Result<()> {
+        let was_visible = window
+            .visible
+            .swap(true, std::sync::atomic::Ordering::SeqCst);
+        let animate = animation.enabled && !was_visible;
+        if animate && animation.style == AnimationStyle::Fade {
+            // Map fully transparent so the first frame doesn't flash
+            self.set_window_opacity(window, 0.0)?;
+        }
         window.show(&self.connection)?;
         self.connection.flush()?;
+        if animate {
+            self.animate(window, animation, true)?;
+        }
         Ok(())
     }
 
-    /// Hides the given X11 window.
+    /// Hides the given X11 window immediately.
+    ///
+    /// This is the right call for refresh hide/show pairs; use
+    /// [`X11::hide_window_animated`] when the popup actually disappears.
     pub fn hide_window(&self, window: &X11Window) -> Result<()> {
         window.hide(&self.connection)?;
         self.connection.flush()?;
         Ok(())
     }
 
+    /// Hides the given X11 window, animating it out first when animations
+    /// are enabled.
+    pub fn hide_window_animated(
+        &self,
+        window: &X11Window,
+        animation: &AnimationConfig,
+    ) -> Result<()> {
+        let was_visible = window
+            .visible
+            .swap(false, std::sync::atomic::Ordering::SeqCst);
+        if animation.enabled && was_visible {
+            self.animate(window, animation, false)?;
+        }
+        self.hide_window(window)
+    }
+
+    /// Interval between animation frames in milliseconds (~60 fps).
+    const FRAME_INTERVAL_MS: u64 = 16;
+
+    /// Runs the show (`appearing = true`) or hide animation to completion.
+    ///
+    /// This blocks the calling thread for the configured duration; the
+    /// durations involved are short enough that queued actions just see a
+    /// slightly delayed refresh.
+    fn animate(&self, window: &X11Window, animation: &AnimationConfig, appearing: bool) -> Result<()> {
+        let steps = (animation.duration_ms / Self::FRAME_INTERVAL_MS).max(1);
+        match animation.style {
+            AnimationStyle::Fade => {
+                for step in 0..=steps {
+                    let progress = animation.easing.apply(step as f64 / steps as f64);
+                    let opacity = if appearing { progress } else { 1.0 - progress };
+                    self.set_window_opacity(window, opacity)?;
+                    self.connection.flush()?;
+                    std::thread::sleep(Duration::from_millis(Self::FRAME_INTERVAL_MS));
+                }
+                // Leave the window fully opaque for the non-animated paths
+                self.set_window_opacity(window, 1.0)?;
+                self.connection.flush()?;
+            }
+            AnimationStyle::Slide => {
+                let geometry = self.connection.get_geometry(window.id)?.reply()?;
+                let final_x = geometry.x as f64;
+                let width = geometry.width as f64;
+                // Start just past the horizontal edge the origin anchors to
+                let origin = window
+                    .params
+                    .read()
+                    .expect("failed to read window parameters")
+                    .origin;
+                let start_x = match origin {
+                    Origin::TopLeft | Origin::BottomLeft => window.monitor.x as f64 - width,
+                    Origin::TopRight | Origin::BottomRight => {
+                        (window.monitor.x + window.monitor.width as i32) as f64
+                    }
+                };
+                for step in 0..=steps {
+                    let progress = animation.easing.apply(step as f64 / steps as f64);
+                    let progress = if appearing { progress } else { 1.0 - progress };
+                    let x = start_x + (final_x - start_x) * progress;
+                    self.connection.configure_window(
+                        window.id,
+                        &ConfigureWindowAux::default().x(Some(x as i32)),
+                    )?;
+                    self.connection.flush()?;
+                    std::thread::sleep(Duration::from_millis(Self::FRAME_INTERVAL_MS));
+                }
+                // Settle exactly on the final position
+                self.connection.configure_window(
+                    window.id,
+                    &ConfigureWindowAux::default().x(Some(final_x as i32)),
+                )?;
+                self.connection.flush()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Sets the `_NET_WM_WINDOW_OPACITY` property honored by compositors.
+    fn set_window_opacity(&self, window: &X11Window, opacity: f64) -> Result<()> {
+        let atom = self
+            .connection
+            .intern_atom(false, b"_NET_WM_WINDOW_OPACITY")?
+            .reply()?
+            .atom;
+        let value = (opacity.clamp(0.0, 1.0) * u32::MAX as f64) as u32;
+        self.connection.change_property32(
+            PropMode::REPLACE,
+            window.id,
+            atom,
+            AtomEnum::CARDINAL,
+            &[value],
+        )?;
+        Ok(())
+    }
+
+    /// Redraws the window content from the current unread buffer.
+    pub fn redraw(&self, window: &X11Window, manager: &Manager, config: &Config) -> Result<()> {
+        let notifications = manager.get_unread_window(
+            config.global.display_limit,
+            window.get_scroll_offset(),
+            config.global.overflow,
+        );
+        let unread_count = manager.get_unread_count();
+        if !notifications.is_empty() {
+            window.draw(&self.connection, notifications, unread_count, config)?;
+        }
+        self.connection.flush()?;
+        Ok(())
+    }
+
+    /// Processes any pending X11 events without blocking, redrawing on expose.
+    pub fn handle_pending_events(
+        &self,
+        window: &X11Window,
+        manager: &Manager,
+        config: &Config,
+    ) -> Result<()> {
+        while let Some(event) = self.connection.poll_for_event()? {
+            log::trace!("New event: {:?}", event);
+            if let Event::Expose(_) = event {
+                self.redraw(window, manager, config)?;
+            }
+        }
+        Ok(())
+    }
+
     /// Width of the close button area on the right side of each notification.
     const CLOSE_BUTTON_WIDTH: i32 = 30;
 
+    /// Mouse button that toggles the inline filter prompt.
+    const MIDDLE_BUTTON: u8 = 2;
+
+    /// Mouse button that opens the context menu on an entry.
+    const RIGHT_BUTTON: u8 = 3;
+
+    /// Mouse scroll wheel up (pages towards older notifications).
+    const SCROLL_UP: u8 = 4;
+
+    /// Mouse scroll wheel down (pages towards newer notifications).
+    const SCROLL_DOWN: u8 = 5;
+
+    /// X11 keysym for Escape.
+    const KEYSYM_ESCAPE: u32 = 0xff1b;
+
+    /// X11 keysym for Return.
+    const KEYSYM_RETURN: u32 = 0xff0d;
+
+    /// X11 keysym for BackSpace.
+    const KEYSYM_BACKSPACE: u32 = 0xff08;
+
+    /// X11 keysym for the Up arrow.
+    const KEYSYM_UP: u32 = 0xff52;
+
+    /// X11 keysym for the Down arrow.
+    const KEYSYM_DOWN: u32 = 0xff54;
+
+    /// How long a snoozed notification stays away before re-posting.
+    const SNOOZE_DURATION: Duration = Duration::from_secs(5 * 60);
+
     /// Handles X11 events in a loop, calling `on_press` when a notification is clicked.
     /// The callback receives (notifications, clicked_index, invoke_action) where
     /// invoke_action is false if the close button was clicked.
+    ///
+    /// Drawing is delegated to the render thread so a slow draw never
+    /// blocks input handling here.
     pub fn handle_events<F>(
         &self,
         window: Arc<X11Window>,
         manager: Manager,
-        config: Arc<Config>,
+        config: Arc<std::sync::RwLock<Config>>,
+        sender: std::sync::mpsc::Sender<Action>,
+        renderer: Renderer,
         on_press: F,
     ) -> Result<()>
     where
         F: Fn(Vec<Notification>, Option<usize>, bool), // (notifications, clicked_idx, invoke_action)
     {
-        let display_limit = config.global.display_limit;
-        let refresh_interval = config.global.refresh_interval_ms;
-
-        // Use short poll interval for responsiveness, track time for redraws
-        const POLL_INTERVAL_MS: u64 = 50;
         let mut last_redraw = std::time::Instant::now();
 
         loop {
+            // Re-read on every iteration so configuration reloads take effect
+            let (display_limit, refresh_interval, overflow) = {
+                let config = config.read().expect("failed to read config");
+                (
+                    config.global.display_limit,
+                    config.global.refresh_interval_ms,
+                    config.global.overflow,
+                )
+            };
             self.connection.flush()?;
 
             // If refresh is enabled and there are unread notifications, use polling with timeout
@@ -247,16 +627,19 @@ impl X11 {
                 let mut event_opt = self.connection.poll_for_event()?;
 
                 if event_opt.is_none() {
-                    // No events, short sleep for responsiveness
-                    std::thread::sleep(Duration::from_millis(POLL_INTERVAL_MS));
+                    // Sleep on the X11 socket until an event arrives or
+                    // the next refresh tick is due, instead of waking
+                    // every few milliseconds to look for either
+                    let elapsed_ms = last_redraw.elapsed().as_millis() as u64;
+                    let timeout_ms = refresh_interval.saturating_sub(elapsed_ms).max(1);
+                    Self::wait_for_readable(self.connection.as_raw_fd(), timeout_ms)?;
+                    event_opt = self.connection.poll_for_event()?;
+                }
 
-                    // Only redraw at refresh_interval rate
+                if event_opt.is_none() {
+                    // Still nothing: the wait ended on the redraw timeout
                     if last_redraw.elapsed().as_millis() >= refresh_interval as u128 {
-                        let notifications = manager.get_unread_buffer(display_limit);
-                        let unread_count = manager.get_unread_count();
-                        if !notifications.is_empty() {
-                            window.draw(&self.connection, notifications, unread_count, &config)?;
-                        }
+                        renderer.request(RenderRequest::Redraw);
                         last_redraw = std::time::Instant::now();
                     }
                     continue;
@@ -267,18 +650,90 @@ impl X11 {
                     log::trace!("New event: {:?}", event);
                     match event {
                         Event::Expose(_) => {
-                            let notifications = manager.get_unread_buffer(display_limit);
-                            let unread_count = manager.get_unread_count();
-                            window.draw(&self.connection, notifications, unread_count, &config)?;
+                            renderer.request(RenderRequest::Redraw);
+                        }
+                        Event::ButtonPress(ev) if ev.detail == Self::MIDDLE_BUTTON => {
+                            self.toggle_filter(&window)?;
+                            renderer.request(RenderRequest::Redraw);
+                        }
+                        Event::ButtonPress(ev) if ev.detail == Self::SCROLL_UP => {
+                            window.scroll_by(1, manager.get_unread_count(), display_limit);
+                            renderer.request(RenderRequest::Redraw);
+                        }
+                        Event::ButtonPress(ev) if ev.detail == Self::SCROLL_DOWN => {
+                            window.scroll_by(-1, manager.get_unread_count(), display_limit);
+                            renderer.request(RenderRequest::Redraw);
+                        }
+                        Event::ButtonPress(_)
+                            if window.menu.lock().expect("failed to lock menu").is_some() =>
+                        {
+                            // Any click while the menu is open dismisses it
+                            self.close_menu(&window)?;
+                            renderer.request(RenderRequest::Redraw);
+                        }
+                        Event::ButtonPress(ev) if ev.detail == Self::RIGHT_BUTTON => {
+                            self.open_menu(
+                                &window,
+                                &manager,
+                                display_limit,
+                                overflow,
+                                ev.event_y as i32,
+                            )?;
+                            renderer.request(RenderRequest::Redraw);
+                        }
+                        Event::ButtonPress(ev)
+                            if window.low_strip_contains(ev.event_y as i32) =>
+                        {
+                            window.toggle_low_expanded();
+                            renderer.request(RenderRequest::Redraw);
+                        }
+                        Event::ButtonPress(ev)
+                            if window.group_at(ev.event_y as i32).is_some() =>
+                        {
+                            if let Some(app) = window.group_at(ev.event_y as i32) {
+                                window.toggle_group(&app);
+                            }
+                            renderer.request(RenderRequest::Redraw);
                         }
                         Event::ButtonPress(ev) => {
-                            let unread = manager.get_unread_buffer(display_limit);
-                            let clicked_idx = window.get_clicked_index(ev.event_y as i32);
+                            let unread = manager.get_unread_window(
+                                display_limit,
+                                window.get_scroll_offset(),
+                                overflow,
+                            );
+                            // Bounds carry IDs (the drawn stack may be
+                            // filtered); map back to the unfiltered window
+                            let clicked_idx = window
+                                .get_clicked_id(ev.event_y as i32)
+                                .and_then(|id| unread.iter().position(|n| n.id == id));
                             let window_width = window.get_window_width();
                             let invoke_action = (ev.event_x as i32) < window_width - Self::CLOSE_BUTTON_WIDTH;
                             // Don't mark all as read here - let callback handle individual closes
                             on_press(unread, clicked_idx, invoke_action);
                         }
+                        Event::KeyPress(ev)
+                            if window.menu.lock().expect("failed to lock menu").is_some() =>
+                        {
+                            self.handle_menu_key(&window, &manager, ev.detail, &sender)?;
+                            renderer.request(RenderRequest::Redraw);
+                        }
+                        Event::KeyPress(ev) => {
+                            self.handle_filter_key(
+                                &window,
+                                &manager,
+                                &config,
+                                ev.detail,
+                                display_limit,
+                                &on_press,
+                            )?;
+                            renderer.request(RenderRequest::Redraw);
+                        }
+                        Event::EnterNotify(_) => {
+                            window.set_hovered(true);
+                        }
+                        Event::LeaveNotify(_) => {
+                            window.set_hovered(false);
+                        }
                         _ => {}
                     }
                     event_opt = self.connection.poll_for_event()?;
@@ -291,18 +746,90 @@ impl X11 {
                     log::trace!("New event: {:?}", event);
                     match event {
                         Event::Expose(_) => {
-                            let notifications = manager.get_unread_buffer(display_limit);
-                            let unread_count = manager.get_unread_count();
-                            window.draw(&self.connection, notifications, unread_count, &config)?;
+                            renderer.request(RenderRequest::Redraw);
+                        }
+                        Event::ButtonPress(ev) if ev.detail == Self::MIDDLE_BUTTON => {
+                            self.toggle_filter(&window)?;
+                            renderer.request(RenderRequest::Redraw);
+                        }
+                        Event::ButtonPress(ev) if ev.detail == Self::SCROLL_UP => {
+                            window.scroll_by(1, manager.get_unread_count(), display_limit);
+                            renderer.request(RenderRequest::Redraw);
+                        }
+                        Event::ButtonPress(ev) if ev.detail == Self::SCROLL_DOWN => {
+                            window.scroll_by(-1, manager.get_unread_count(), display_limit);
+                            renderer.request(RenderRequest::Redraw);
+                        }
+                        Event::ButtonPress(_)
+                            if window.menu.lock().expect("failed to lock menu").is_some() =>
+                        {
+                            // Any click while the menu is open dismisses it
+                            self.close_menu(&window)?;
+                            renderer.request(RenderRequest::Redraw);
+                        }
+                        Event::ButtonPress(ev) if ev.detail == Self::RIGHT_BUTTON => {
+                            self.open_menu(
+                                &window,
+                                &manager,
+                                display_limit,
+                                overflow,
+                                ev.event_y as i32,
+                            )?;
+                            renderer.request(RenderRequest::Redraw);
+                        }
+                        Event::ButtonPress(ev)
+                            if window.low_strip_contains(ev.event_y as i32) =>
+                        {
+                            window.toggle_low_expanded();
+                            renderer.request(RenderRequest::Redraw);
+                        }
+                        Event::ButtonPress(ev)
+                            if window.group_at(ev.event_y as i32).is_some() =>
+                        {
+                            if let Some(app) = window.group_at(ev.event_y as i32) {
+                                window.toggle_group(&app);
+                            }
+                            renderer.request(RenderRequest::Redraw);
                         }
                         Event::ButtonPress(ev) => {
-                            let unread = manager.get_unread_buffer(display_limit);
-                            let clicked_idx = window.get_clicked_index(ev.event_y as i32);
+                            let unread = manager.get_unread_window(
+                                display_limit,
+                                window.get_scroll_offset(),
+                                overflow,
+                            );
+                            // Bounds carry IDs (the drawn stack may be
+                            // filtered); map back to the unfiltered window
+                            let clicked_idx = window
+                                .get_clicked_id(ev.event_y as i32)
+                                .and_then(|id| unread.iter().position(|n| n.id == id));
                             let window_width = window.get_window_width();
                             let invoke_action = (ev.event_x as i32) < window_width - Self::CLOSE_BUTTON_WIDTH;
                             // Don't mark all as read here - let callback handle individual closes
                             on_press(unread, clicked_idx, invoke_action);
                         }
+                        Event::KeyPress(ev)
+                            if window.menu.lock().expect("failed to lock menu").is_some() =>
+                        {
+                            self.handle_menu_key(&window, &manager, ev.detail, &sender)?;
+                            renderer.request(RenderRequest::Redraw);
+                        }
+                        Event::KeyPress(ev) => {
+                            self.handle_filter_key(
+                                &window,
+                                &manager,
+                                &config,
+                                ev.detail,
+                                display_limit,
+                                &on_press,
+                            )?;
+                            renderer.request(RenderRequest::Redraw);
+                        }
+                        Event::EnterNotify(_) => {
+                            window.set_hovered(true);
+                        }
+                        Event::LeaveNotify(_) => {
+                            window.set_hovered(false);
+                        }
                         _ => {}
                     }
                     event_opt = self.connection.poll_for_event()?;
@@ -310,12 +837,496 @@ impl X11 {
             }
         }
     }
+
+    /// Blocks until the given file descriptor becomes readable or the
+    /// timeout (in milliseconds) elapses.
+    fn wait_for_readable(fd: RawFd, timeout_ms: u64) -> Result<()> {
+        let mut pollfd = libc::pollfd {
+            fd,
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        let timeout = timeout_ms.min(i32::MAX as u64) as i32;
+        // SAFETY: `pollfd` points at a single valid structure for the
+        // duration of the call
+        let ret = unsafe { libc::poll(&mut pollfd, 1, timeout) };
+        if ret < 0 {
+            let err = std::io::Error::last_os_error();
+            // A signal cutting the wait short is indistinguishable from
+            // an early wakeup and handled by the caller's re-poll
+            if err.kind() != std::io::ErrorKind::Interrupted {
+                return Err(Error::Io(err));
+            }
+        }
+        Ok(())
+    }
+
+    /// Redraws the window from the unread buffer.
+    ///
+    /// Unlike [`X11::redraw`], this draws even when the filter narrows the
+    /// stack down to nothing, so the prompt line stays visible.
+    pub(crate) fn redraw_unread(
+        &self,
+        window: &X11Window,
+        manager: &Manager,
+        config: &std::sync::RwLock<Config>,
+        display_limit: usize,
+    ) -> Result<()> {
+        let config = config.read().expect("failed to read config");
+        let notifications = manager.get_unread_window(
+            display_limit,
+            window.get_scroll_offset(),
+            config.global.overflow,
+        );
+        let unread_count = manager.get_unread_count();
+        window.draw(&self.connection, notifications, unread_count, &config)?;
+        self.connection.flush()?;
+        Ok(())
+    }
+
+    /// Toggles the inline filter prompt, grabbing the keyboard while the
+    /// prompt is open so key presses reach the override-redirect window.
+    fn toggle_filter(&self, window: &X11Window) -> Result<()> {
+        let activate = {
+            let mut filter = window.filter.lock().expect("failed to lock filter");
+            if filter.is_none() {
+                *filter = Some(String::new());
+                true
+            } else {
+                *filter = None;
+                false
+            }
+        };
+        if activate {
+            self.connection
+                .grab_keyboard(
+                    false,
+                    window.id,
+                    x11rb::CURRENT_TIME,
+                    GrabMode::ASYNC,
+                    GrabMode::ASYNC,
+                )?
+                .reply()?;
+        } else {
+            self.connection.ungrab_keyboard(x11rb::CURRENT_TIME)?;
+        }
+        self.connection.flush()?;
+        Ok(())
+    }
+
+    /// Handles a key press while the inline filter prompt is open.
+    ///
+    /// Typing narrows the displayed entries, Return invokes the top match,
+    /// Escape restores the full stack.
+    fn handle_filter_key<F>(
+        &self,
+        window: &X11Window,
+        manager: &Manager,
+        config: &std::sync::RwLock<Config>,
+        keycode: u8,
+        display_limit: usize,
+        on_press: &F,
+    ) -> Result<()>
+    where
+        F: Fn(Vec<Notification>, Option<usize>, bool),
+    {
+        let keysym = self.keycode_to_keysym(keycode);
+        let overflow = config.read().expect("failed to read config").global.overflow;
+        let mut invoke_matches: Option<Vec<Notification>> = None;
+        let closed = {
+            let mut filter = window.filter.lock().expect("failed to lock filter");
+            let Some(query) = filter.as_mut() else {
+                return Ok(());
+            };
+            match keysym {
+                Self::KEYSYM_ESCAPE => *filter = None,
+                Self::KEYSYM_RETURN => {
+                    let matches = apply_filter(
+                        manager.get_unread_window(
+                            display_limit,
+                            window.get_scroll_offset(),
+                            overflow,
+                        ),
+                        query,
+                    );
+                    if !matches.is_empty() {
+                        invoke_matches = Some(matches);
+                    }
+                    *filter = None;
+                }
+                Self::KEYSYM_BACKSPACE => {
+                    query.pop();
+                }
+                keysym if keysym < 0x80 => {
+                    if let Some(character) = char::from_u32(keysym)
+                        && !character.is_control()
+                    {
+                        query.push(character);
+                    }
+                }
+                _ => return Ok(()),
+            }
+            filter.is_none()
+        };
+        if closed {
+            self.connection.ungrab_keyboard(x11rb::CURRENT_TIME)?;
+        }
+        if let Some(matches) = invoke_matches {
+            // The stack renders newest first, so the top match is the last one
+            let top = matches.len() - 1;
+            on_press(matches, Some(top), true);
+        }
+        Ok(())
+    }
+
+    /// Opens the context menu for the entry under the given Y coordinate.
+    ///
+    /// The menu lists the notification's own actions plus built-ins (copy
+    /// body, open URL, snooze, mute app) and grabs the keyboard for
+    /// selection while open.
+    fn open_menu(
+        &self,
+        window: &X11Window,
+        manager: &Manager,
+        display_limit: usize,
+        overflow: OverflowPolicy,
+        y: i32,
+    ) -> Result<()> {
+        let unread =
+            manager.get_unread_window(display_limit, window.get_scroll_offset(), overflow);
+        let Some(notification) = window
+            .get_clicked_id(y)
+            .and_then(|id| unread.iter().find(|n| n.id == id).cloned())
+        else {
+            return Ok(());
+        };
+
+        let mut items = Vec::new();
+        // Actions come as [key, label, key, label, ...] pairs
+        for pair in notification.actions.chunks(2) {
+            if let [key, label] = pair {
+                items.push((label.clone(), MenuAction::Invoke(key.clone())));
+            }
+        }
+        if !notification.body.is_empty() {
+            items.push((String::from("Copy body"), MenuAction::CopyBody));
+        }
+        if let Some(url) = find_url(&notification.summary).or_else(|| find_url(&notification.body))
+        {
+            items.push((format!("Open {}", url), MenuAction::OpenUrl(url)));
+        }
+        items.push((String::from("Snooze for 5 minutes"), MenuAction::Snooze));
+        items.push((
+            format!("Never show from {}", notification.app_name),
+            MenuAction::MuteApp,
+        ));
+
+        *window.menu.lock().expect("failed to lock menu") = Some(MenuState {
+            notification,
+            items,
+            selected: 0,
+        });
+        self.connection
+            .grab_keyboard(
+                false,
+                window.id,
+                x11rb::CURRENT_TIME,
+                GrabMode::ASYNC,
+                GrabMode::ASYNC,
+            )?
+            .reply()?;
+        self.connection.flush()?;
+        Ok(())
+    }
+
+    /// Closes the context menu, releasing the keyboard unless the inline
+    /// filter prompt still needs it.
+    fn close_menu(&self, window: &X11Window) -> Result<()> {
+        *window.menu.lock().expect("failed to lock menu") = None;
+        if window.filter.lock().expect("failed to lock filter").is_none() {
+            self.connection.ungrab_keyboard(x11rb::CURRENT_TIME)?;
+            self.connection.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Handles a key press while the context menu is open.
+    ///
+    /// Up/Down move the highlight, Return activates the highlighted item,
+    /// Escape closes the menu.
+    fn handle_menu_key(
+        &self,
+        window: &X11Window,
+        manager: &Manager,
+        keycode: u8,
+        sender: &std::sync::mpsc::Sender<Action>,
+    ) -> Result<()> {
+        let keysym = self.keycode_to_keysym(keycode);
+        let activated = {
+            let mut menu = window.menu.lock().expect("failed to lock menu");
+            let Some(state) = menu.as_mut() else {
+                return Ok(());
+            };
+            match keysym {
+                Self::KEYSYM_UP => {
+                    state.selected = state.selected.saturating_sub(1);
+                    return Ok(());
+                }
+                Self::KEYSYM_DOWN => {
+                    state.selected = (state.selected + 1).min(state.items.len() - 1);
+                    return Ok(());
+                }
+                Self::KEYSYM_RETURN => menu.take(),
+                Self::KEYSYM_ESCAPE => None,
+                _ => return Ok(()),
+            }
+        };
+        if let Some(state) = activated {
+            self.activate_menu_item(state, manager, sender);
+        }
+        self.close_menu(window)
+    }
+
+    /// Performs the highlighted menu item's effect.
+    fn activate_menu_item(
+        &self,
+        state: MenuState,
+        manager: &Manager,
+        sender: &std::sync::mpsc::Sender<Action>,
+    ) {
+        let notification = state.notification;
+        let Some((label, action)) = state.items.into_iter().nth(state.selected) else {
+            return;
+        };
+        log::debug!(
+            "menu item '{}' activated for notification {}",
+            label,
+            notification.id
+        );
+        match action {
+            MenuAction::Invoke(key) => {
+                let _ = sender.send(Action::Invoke(notification.id, key));
+                let _ = sender.send(Action::Close(Some(notification.id)));
+            }
+            MenuAction::CopyBody => {
+                copy_to_clipboard(&notification.body);
+            }
+            MenuAction::OpenUrl(url) => {
+                if let Err(e) = std::process::Command::new("xdg-open")
+                    .arg(&url)
+                    .stdout(std::process::Stdio::null())
+                    .stderr(std::process::Stdio::null())
+                    .spawn()
+                {
+                    log::warn!("failed to open {}: {}", url, e);
+                }
+            }
+            MenuAction::Snooze => {
+                let _ = sender.send(Action::Close(Some(notification.id)));
+                let sender = sender.clone();
+                std::thread::spawn(move || {
+                    std::thread::sleep(Self::SNOOZE_DURATION);
+                    let mut notification = notification;
+                    notification.is_read = false;
+                    // The buffer holds the processed copy (rules and repeat
+                    // marker applied, history recorded), so Reshow skips
+                    // those stages instead of running them twice
+                    let _ = sender.send(Action::Reshow(notification));
+                });
+            }
+            MenuAction::MuteApp => {
+                manager.mute(notification.app_name.clone(), None);
+                let _ = sender.send(Action::Close(Some(notification.id)));
+            }
+        }
+    }
+
+    /// Returns the unmodified keysym for a keycode.
+    fn keycode_to_keysym(&self, keycode: u8) -> u32 {
+        self.connection
+            .get_keyboard_mapping(keycode, 1)
+            .ok()
+            .and_then(|cookie| cookie.reply().ok())
+            .and_then(|reply| reply.keysyms.first().copied())
+            .unwrap_or(0)
+    }
+}
+
+/// The X11 implementation of the render [`Backend`].
+pub struct X11Backend {
+    /// Shared X11 connection handle.
+    x11: Arc<X11>,
+    /// The popup window.
+    window: Arc<X11Window>,
+}
+
+impl X11Backend {
+    /// Creates the backend from the existing connection and window.
+    pub fn new(x11: Arc<X11>, window: Arc<X11Window>) -> Self {
+        Self { x11, window }
+    }
+}
+
+impl Backend for X11Backend {
+    fn show(&self, animation: &AnimationConfig) -> Result<()> {
+        self.x11.show_window(&self.window, animation)
+    }
+
+    fn hide(&self) -> Result<()> {
+        self.x11.hide_window(&self.window)
+    }
+
+    fn hide_animated(&self, animation: &AnimationConfig) -> Result<()> {
+        self.x11.hide_window_animated(&self.window, animation)
+    }
+
+    fn draw(
+        &self,
+        manager: &Manager,
+        config: &std::sync::RwLock<Config>,
+        display_limit: usize,
+    ) -> Result<()> {
+        self.x11
+            .redraw_unread(&self.window, manager, config, display_limit)
+    }
+
+    fn handle_input(
+        &self,
+        manager: Manager,
+        config: Arc<std::sync::RwLock<Config>>,
+        sender: std::sync::mpsc::Sender<Action>,
+        renderer: Renderer,
+        on_press: Box<dyn Fn(Vec<Notification>, Option<usize>, bool) + Send>,
+    ) -> Result<()> {
+        self.x11.handle_events(
+            Arc::clone(&self.window),
+            manager,
+            config,
+            sender,
+            renderer,
+            on_press,
+        )
+    }
+}
+
+/// Returns the first http(s) URL found in the text, with trailing
+/// punctuation trimmed.
+fn find_url(text: &str) -> Option<String> {
+    text.split_whitespace()
+        .find(|word| word.starts_with("http://") || word.starts_with("https://"))
+        .map(|url| url.trim_end_matches(['.', ',', ';', ')', '>']).to_string())
+}
+
+/// Copies text to the clipboard by piping it through `xclip` or `xsel`.
+fn copy_to_clipboard(text: &str) {
+    use std::io::Write;
+    for (command, args) in [
+        ("xclip", ["-selection", "clipboard"].as_slice()),
+        ("xsel", ["--input", "--clipboard"].as_slice()),
+    ] {
+        let child = std::process::Command::new(command)
+            .args(args)
+            .stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .spawn();
+        if let Ok(mut child) = child {
+            if let Some(stdin) = child.stdin.as_mut()
+                && stdin.write_all(text.as_bytes()).is_ok()
+            {
+                let _ = child.wait();
+                return;
+            }
+            let _ = child.wait();
+        }
+    }
+    log::warn!("failed to copy to clipboard: neither xclip nor xsel available");
+}
+
+/// Filters notifications by a case-insensitive substring over the
+/// application name, summary and body.
+fn apply_filter(notifications: Vec<Notification>, query: &str) -> Vec<Notification> {
+    let query = query.to_lowercase();
+    notifications
+        .into_iter()
+        .filter(|notification| {
+            notification.app_name.to_lowercase().contains(&query)
+                || notification.summary.to_lowercase().contains(&query)
+                || notification.body.to_lowercase().contains(&query)
+        })
+        .collect()
+}
+
+/// Snapshot of the popup's position, size and per-entry bounds, for
+/// external tools interacting with specific entries.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct LayoutSnapshot {
+    /// X position of the window in root coordinates.
+    pub x: i32,
+    /// Y position of the window in root coordinates.
+    pub y: i32,
+    /// Width of the window in pixels.
+    pub width: u32,
+    /// Height of the window in pixels.
+    pub height: u32,
+    /// Bounds of the displayed notification entries.
+    pub entries: Vec<LayoutEntry>,
+}
+
+/// Bounds of a single displayed notification entry.
+#[derive(Clone, Debug, Serialize)]
+pub struct LayoutEntry {
+    /// ID of the notification shown in this entry.
+    pub id: u32,
+    /// Y coordinate where the entry starts, relative to the window.
+    pub y_start: i32,
+    /// Y coordinate where the entry ends, relative to the window.
+    pub y_end: i32,
+}
+
+/// What a context menu item does when activated.
+pub enum MenuAction {
+    /// Invoke the notification action with this key.
+    Invoke(String),
+    /// Copy the notification body to the clipboard.
+    CopyBody,
+    /// Open this URL with the system handler.
+    OpenUrl(String),
+    /// Dismiss the notification and re-post it after a delay.
+    Snooze,
+    /// Mute the sending application until unmuted.
+    MuteApp,
+}
+
+/// State of the open right-click context menu.
+pub struct MenuState {
+    /// The notification the menu was opened for.
+    pub notification: Notification,
+    /// Menu items as (label, action) pairs.
+    pub items: Vec<(String, MenuAction)>,
+    /// Index of the highlighted item.
+    pub selected: usize,
+}
+
+/// Window parameters that can change when the configuration is reloaded.
+pub struct WindowParams {
+    /// Raw text format source; compiled into the template cache on
+    /// first render rather than during startup.
+    pub template: String,
+    /// Window origin/anchor point.
+    pub origin: Origin,
+    /// X offset from origin.
+    pub offset_x: u32,
+    /// Y offset from origin.
+    pub offset_y: u32,
 }
 
 /// Representation of a X11 window.
 pub struct X11Window {
     /// Window ID.
     pub id: u32,
+    /// Root window of the screen.
+    pub root: u32,
     /// Cairo surface for drawing.
     pub surface: XCBSurface,
     /// Graphics renderer context.
@@ -324,22 +1335,45 @@ pub struct X11Window {
     pub pango_context: PangoContext,
     /// Window layout.
     pub layout: PangoLayout,
-    /// Text format.
-    pub template: Tera,
-    /// Window origin/anchor point.
-    pub origin: Origin,
-    /// X offset from origin.
-    pub offset_x: u32,
-    /// Y offset from origin.
-    pub offset_y: u32,
-    /// Screen width in pixels.
-    pub screen_width: u16,
-    /// Screen height in pixels.
-    pub screen_height: u16,
-    /// Entry bounds for click detection: (y_start, y_end, index in original notifications vec)
-    pub entry_bounds: std::sync::Mutex<Vec<(i32, i32, usize)>>,
+    /// Reloadable window parameters (template, origin, offsets).
+    pub params: std::sync::RwLock<WindowParams>,
+    /// Screen area the window is positioned against.
+    pub monitor: MonitorRect,
+    /// Entry bounds for click detection: (y_start, y_end, notification id).
+    /// IDs rather than indices, since the drawn stack may be narrowed by
+    /// the inline filter while click handlers see the unfiltered window.
+    pub entry_bounds: std::sync::Mutex<Vec<(i32, i32, u32)>>,
     /// Current window width (updated during draw)
     pub current_width: std::sync::Mutex<i32>,
+    /// Active inline filter query (None while the prompt is closed).
+    pub filter: std::sync::Mutex<Option<String>>,
+    /// Layout of the last draw, exposed through the control interface.
+    pub layout_snapshot: std::sync::Mutex<LayoutSnapshot>,
+    /// Whether the popup is logically visible, as opposed to briefly
+    /// unmapped during a refresh hide/show pair (drives animations).
+    pub visible: std::sync::atomic::AtomicBool,
+    /// Number of newest unread notifications scrolled out of view.
+    pub scroll_offset: std::sync::atomic::AtomicUsize,
+    /// Whether the pointer is currently over the popup (pauses auto-clear).
+    pub hovered: std::sync::atomic::AtomicBool,
+    /// Open right-click context menu, if any.
+    pub menu: std::sync::Mutex<Option<MenuState>>,
+    /// Whether the collapsed low-urgency strip is currently expanded.
+    pub low_expanded: std::sync::atomic::AtomicBool,
+    /// Y range of the low-urgency strip in the last draw, for click
+    /// detection (None when no strip was drawn).
+    pub low_strip_bounds: std::sync::Mutex<Option<(i32, i32)>>,
+    /// Applications whose group is currently expanded (group_by_app).
+    pub expanded_groups: std::sync::Mutex<std::collections::HashSet<String>>,
+    /// Y ranges of group lines in the last draw, mapped to the app they
+    /// expand or collapse.
+    pub group_bounds: std::sync::Mutex<Vec<(i32, i32, String)>>,
+    /// Compiled per-entry template overrides, keyed by their raw source.
+    pub template_cache: std::sync::Mutex<std::collections::HashMap<String, Tera>>,
+    /// Measured entry heights from the last draw, keyed by notification
+    /// id and a hash of the rendered markup, so unchanged entries skip
+    /// the re-measure on every refresh.
+    pub height_cache: std::sync::Mutex<std::collections::HashMap<u32, (u64, i32)>>,
 }
 
 unsafe impl Send for X11Window {}
@@ -350,20 +1384,53 @@ impl X11Window {
     #[allow(clippy::too_many_arguments)]
     pub fn new(
         id: u32,
+        root: u32,
         surface: XCBSurface,
         cairo_context: CairoContext,
         font: &str,
-        raw_template: &'static str,
+        raw_template: &str,
         origin: Origin,
         offset_x: u32,
         offset_y: u32,
-        screen_width: u16,
-        screen_height: u16,
+        monitor: MonitorRect,
     ) -> Result<Self> {
         let pango_context = pango_functions::create_context(&cairo_context);
         let layout = PangoLayout::new(&pango_context);
         let font_description = FontDescription::from_string(font);
         pango_context.set_font_description(Some(&font_description));
+        Ok(Self {
+            id,
+            root,
+            surface,
+            cairo_context,
+            pango_context,
+            layout,
+            params: std::sync::RwLock::new(WindowParams {
+                template: raw_template.to_string(),
+                origin,
+                offset_x,
+                offset_y,
+            }),
+            monitor,
+            entry_bounds: std::sync::Mutex::new(Vec::new()),
+            current_width: std::sync::Mutex::new(0),
+            filter: std::sync::Mutex::new(None),
+            layout_snapshot: std::sync::Mutex::new(LayoutSnapshot::default()),
+            visible: std::sync::atomic::AtomicBool::new(false),
+            scroll_offset: std::sync::atomic::AtomicUsize::new(0),
+            hovered: std::sync::atomic::AtomicBool::new(false),
+            menu: std::sync::Mutex::new(None),
+            low_expanded: std::sync::atomic::AtomicBool::new(false),
+            low_strip_bounds: std::sync::Mutex::new(None),
+            expanded_groups: std::sync::Mutex::new(std::collections::HashSet::new()),
+            group_bounds: std::sync::Mutex::new(Vec::new()),
+            template_cache: std::sync::Mutex::new(std::collections::HashMap::new()),
+            height_cache: std::sync::Mutex::new(std::collections::HashMap::new()),
+        })
+    }
+
+    /// Compiles the raw notification template.
+    fn build_template(raw_template: &str) -> Result<Tera> {
         let mut template = Tera::default();
         if let Err(e) =
             template.add_raw_template(NOTIFICATION_MESSAGE_TEMPLATE, raw_template.trim())
@@ -374,66 +1441,230 @@ impl X11Window {
                 Err(Error::Template(e))
             };
         }
-        template.register_filter(
-            "humantime",
-            |value: &Value, _: &HashMap<String, Value>| -> TeraResult<Value> {
-                let value = tera::try_get_value!("humantime_filter", "value", u64, value);
-                let value = humantime::format_duration(Duration::new(value, 0)).to_string();
-                Ok(tera::to_value(value)?)
-            },
-        );
-        Ok(Self {
-            id,
-            surface,
-            cairo_context,
-            pango_context,
-            layout,
-            template,
-            origin,
-            offset_x,
-            offset_y,
-            screen_width,
-            screen_height,
-            entry_bounds: std::sync::Mutex::new(Vec::new()),
-            current_width: std::sync::Mutex::new(0),
-        })
+        crate::notification::register_template_extensions(&mut template);
+        Ok(template)
+    }
+
+    /// Applies a reloaded configuration to the window (template, font, geometry).
+    pub fn apply_config(&self, config: &GlobalConfig) -> Result<()> {
+        // Compile eagerly so a broken template fails the reload instead
+        // of surfacing as render warnings later
+        let template = Self::build_template(&config.template)?;
+        let font_description = FontDescription::from_string(&config.font);
+        self.pango_context.set_font_description(Some(&font_description));
+        let mut params = self
+            .params
+            .write()
+            .expect("failed to update window parameters");
+        params.template = config.template.clone();
+        params.origin = config.origin;
+        params.offset_x = config.geometry.x;
+        params.offset_y = config.geometry.y;
+        // Urgency and rule template overrides may have changed too
+        let mut cache = self
+            .template_cache
+            .lock()
+            .expect("failed to lock template cache");
+        cache.clear();
+        cache.insert(config.template.clone(), template);
+        drop(cache);
+        // A font change invalidates every measured height
+        self.height_cache
+            .lock()
+            .expect("failed to lock height cache")
+            .clear();
+        Ok(())
+    }
+
+    /// Renders the notification message using the window's template,
+    /// compiling it into the cache on first use.
+    pub fn render_notification(
+        &self,
+        notification: &Notification,
+        urgency_text: Option<String>,
+        unread_count: usize,
+    ) -> Result<String> {
+        let params = self.params.read().expect("failed to read window parameters");
+        let mut cache = self
+            .template_cache
+            .lock()
+            .expect("failed to lock template cache");
+        if !cache.contains_key(&params.template) {
+            cache.insert(params.template.clone(), Self::build_template(&params.template)?);
+        }
+        notification.render_message(&cache[&params.template], urgency_text, unread_count, None)
     }
 
-    /// Returns the index of the clicked notification based on y coordinate.
+    /// Renders the notification through a per-entry template override,
+    /// compiling and caching the template on first use.
+    pub fn render_notification_with(
+        &self,
+        raw_template: &str,
+        notification: &Notification,
+        urgency_text: Option<String>,
+        unread_count: usize,
+        position: Option<(usize, usize)>,
+    ) -> Result<String> {
+        let mut cache = self
+            .template_cache
+            .lock()
+            .expect("failed to lock template cache");
+        if !cache.contains_key(raw_template) {
+            cache.insert(raw_template.to_string(), Self::build_template(raw_template)?);
+        }
+        notification.render_message(&cache[raw_template], urgency_text, unread_count, position)
+    }
+
+    /// Renders a header or footer template with stack-level context
+    /// (`unread_count`, `displayed_count`, `hidden_older`, `hidden_newer`).
+    fn render_stack_template(
+        &self,
+        raw_template: &str,
+        unread_count: usize,
+        displayed_count: usize,
+        hidden_older: usize,
+        hidden_newer: usize,
+    ) -> Result<String> {
+        let mut cache = self
+            .template_cache
+            .lock()
+            .expect("failed to lock template cache");
+        if !cache.contains_key(raw_template) {
+            cache.insert(raw_template.to_string(), Self::build_template(raw_template)?);
+        }
+        let mut context = TeraContext::new();
+        context.insert("unread_count", &unread_count);
+        context.insert("displayed_count", &displayed_count);
+        context.insert("hidden_older", &hidden_older);
+        context.insert("hidden_newer", &hidden_newer);
+        match cache[raw_template].render(NOTIFICATION_MESSAGE_TEMPLATE, &context) {
+            Ok(markup) => Ok(markup),
+            Err(e) => {
+                if let Some(error_source) = e.source() {
+                    Err(Error::TemplateRender(error_source.to_string()))
+                } else {
+                    Err(Error::Template(e))
+                }
+            }
+        }
+    }
+
+    /// Returns the ID of the clicked notification based on y coordinate.
     /// Returns None if click was on a separator or outside notification bounds.
-    pub fn get_clicked_index(&self, y: i32) -> Option<usize> {
+    pub fn get_clicked_id(&self, y: i32) -> Option<u32> {
         if let Ok(bounds) = self.entry_bounds.lock() {
-            for (y_start, y_end, idx) in bounds.iter() {
+            for (y_start, y_end, id) in bounds.iter() {
                 if y >= *y_start && y < *y_end {
-                    return Some(*idx);
+                    return Some(*id);
                 }
             }
         }
         None
     }
 
+    /// Returns the layout of the last draw.
+    pub fn get_layout(&self) -> LayoutSnapshot {
+        self.layout_snapshot
+            .lock()
+            .map(|snapshot| snapshot.clone())
+            .unwrap_or_default()
+    }
+
     /// Returns the current window width.
     pub fn get_window_width(&self) -> i32 {
         self.current_width.lock().map(|w| *w).unwrap_or(0)
     }
 
-    /// Calculates the X,Y position based on origin, offsets, and window size.
-    pub fn calculate_position(&self, width: u32, height: u32) -> (i32, i32) {
-        let screen_w = self.screen_width as i32;
-        let screen_h = self.screen_height as i32;
-        let offset_x = self.offset_x as i32;
-        let offset_y = self.offset_y as i32;
-        let w = width as i32;
-        let h = height as i32;
+    /// Returns how many of the newest unread notifications are scrolled
+    /// out of view.
+    pub fn get_scroll_offset(&self) -> usize {
+        self.scroll_offset.load(std::sync::atomic::Ordering::SeqCst)
+    }
+
+    /// Adjusts the scroll offset by `delta` (positive scrolls towards
+    /// older notifications), clamped so the visible window stays full.
+    ///
+    /// Has no effect when everything already fits on screen.
+    pub fn scroll_by(&self, delta: i64, unread_count: usize, display_limit: usize) {
+        let max_offset = if display_limit == 0 {
+            0
+        } else {
+            unread_count.saturating_sub(display_limit)
+        };
+        let current = self.get_scroll_offset() as i64;
+        let offset = (current + delta).clamp(0, max_offset as i64) as usize;
+        self.scroll_offset
+            .store(offset, std::sync::atomic::Ordering::SeqCst);
+    }
+
+    /// Scrolls back to the newest notifications.
+    pub fn reset_scroll(&self) {
+        self.scroll_offset
+            .store(0, std::sync::atomic::Ordering::SeqCst);
+    }
+
+    /// Returns whether the pointer is currently over the popup.
+    pub fn is_hovered(&self) -> bool {
+        self.hovered.load(std::sync::atomic::Ordering::SeqCst)
+    }
+
+    /// Records whether the pointer is over the popup.
+    pub fn set_hovered(&self, hovered: bool) {
+        self.hovered
+            .store(hovered, std::sync::atomic::Ordering::SeqCst);
+    }
+
+    /// Returns whether the given Y coordinate falls inside the
+    /// low-urgency strip of the last draw.
+    pub fn low_strip_contains(&self, y: i32) -> bool {
+        self.low_strip_bounds
+            .lock()
+            .expect("failed to lock low strip bounds")
+            .is_some_and(|(y_start, y_end)| y >= y_start && y < y_end)
+    }
 
-        match self.origin {
-            Origin::TopLeft => (offset_x, offset_y),
-            Origin::TopRight => (screen_w - w - offset_x, offset_y),
-            Origin::BottomLeft => (offset_x, screen_h - h - offset_y),
-            Origin::BottomRight => (screen_w - w - offset_x, screen_h - h - offset_y),
+    /// Expands or collapses the low-urgency strip.
+    pub fn toggle_low_expanded(&self) {
+        self.low_expanded
+            .fetch_xor(true, std::sync::atomic::Ordering::SeqCst);
+    }
+
+    /// Returns the application whose group line covers the given Y
+    /// coordinate in the last draw, if any.
+    pub fn group_at(&self, y: i32) -> Option<String> {
+        self.group_bounds
+            .lock()
+            .expect("failed to lock group bounds")
+            .iter()
+            .find(|(y_start, y_end, _)| y >= *y_start && y < *y_end)
+            .map(|(_, _, app)| app.clone())
+    }
+
+    /// Expands or collapses the group of the given application.
+    pub fn toggle_group(&self, app: &str) {
+        let mut expanded = self
+            .expanded_groups
+            .lock()
+            .expect("failed to lock expanded groups");
+        if !expanded.remove(app) {
+            expanded.insert(app.to_string());
         }
     }
 
+    /// Calculates the X,Y position based on origin, offsets, and window size.
+    pub fn calculate_position(&self, width: u32, height: u32) -> (i32, i32) {
+        let params = self.params.read().expect("failed to read window parameters");
+        let (x, y) = calculate_position_from_origin(
+            params.origin,
+            params.offset_x,
+            params.offset_y,
+            width,
+            height,
+            self.monitor,
+        );
+        (x as i32, y as i32)
+    }
+
     /// Shows the window.
     fn show(&self, connection: &impl Connection) -> Result<()> {
         connection.map_window(self.id)?;
@@ -446,6 +1677,87 @@ impl X11Window {
         Ok(())
     }
 
+    /// Returns a position near the top-right corner of the application's
+    /// window, clamped to the screen, or `None` if the application has no
+    /// visible window.
+    fn anchor_position(
+        &self,
+        connection: &XCBConnection,
+        app_name: &str,
+        width: u32,
+        height: u32,
+    ) -> Option<(i16, i16)> {
+        let (app_x, app_y, app_width, _) = self.find_app_window_geometry(connection, app_name)?;
+        let min_x = self.monitor.x;
+        let min_y = self.monitor.y;
+        let max_x = min_x + self.monitor.width as i32;
+        let max_y = min_y + self.monitor.height as i32;
+        let w = width as i32;
+        let h = height as i32;
+        let x = (app_x + app_width - w).clamp(min_x, (max_x - w).max(min_x));
+        let y = app_y.clamp(min_y, (max_y - h).max(min_y));
+        Some((x as i16, y as i16))
+    }
+
+    /// Finds the geometry of a visible application window whose `WM_CLASS`
+    /// matches the given app name (case-insensitive), via EWMH.
+    ///
+    /// Returns `(x, y, width, height)` in root window coordinates.
+    fn find_app_window_geometry(
+        &self,
+        connection: &XCBConnection,
+        app_name: &str,
+    ) -> Option<(i32, i32, i32, i32)> {
+        let net_client_list = connection
+            .intern_atom(false, b"_NET_CLIENT_LIST")
+            .ok()?
+            .reply()
+            .ok()?
+            .atom;
+        let clients = connection
+            .get_property(
+                false,
+                self.root,
+                net_client_list,
+                AtomEnum::WINDOW,
+                0,
+                u32::MAX,
+            )
+            .ok()?
+            .reply()
+            .ok()?;
+        let app_name_lower = app_name.to_lowercase();
+        for client in clients.value32()? {
+            let class = connection
+                .get_property(false, client, AtomEnum::WM_CLASS, AtomEnum::STRING, 0, 1024)
+                .ok()?
+                .reply()
+                .ok()?;
+            // WM_CLASS holds two NUL-terminated strings (instance, class)
+            let matches = class
+                .value
+                .split(|byte| *byte == 0)
+                .filter_map(|part| str::from_utf8(part).ok())
+                .any(|part| part.to_lowercase() == app_name_lower);
+            if !matches {
+                continue;
+            }
+            let geometry = connection.get_geometry(client).ok()?.reply().ok()?;
+            let translated = connection
+                .translate_coordinates(client, self.root, 0, 0)
+                .ok()?
+                .reply()
+                .ok()?;
+            return Some((
+                translated.dst_x as i32,
+                translated.dst_y as i32,
+                geometry.width as i32,
+                geometry.height as i32,
+            ));
+        }
+        None
+    }
+
     /// Escapes text for safe inclusion in Pango markup.
     fn escape_markup(s: &str) -> String {
         s.replace('&', "&amp;")
@@ -455,6 +1767,66 @@ impl X11Window {
             .replace('\'', "&#39;")
     }
 
+    /// Returns true when the first strong directional character in the
+    /// summary or body belongs to a right-to-left script (Hebrew,
+    /// Arabic and their presentation forms).
+    fn is_rtl(summary: &str, body: &str) -> bool {
+        for ch in summary.chars().chain(body.chars()) {
+            match ch as u32 {
+                0x0590..=0x08FF | 0xFB1D..=0xFDFF | 0xFE70..=0xFEFF => return true,
+                // Latin through Georgian: strong left-to-right
+                _ if ch.is_ascii_alphabetic() => return false,
+                0x00C0..=0x058F | 0x10A0..=0x10FF => return false,
+                _ => {}
+            }
+        }
+        false
+    }
+
+    /// Formats an entry's age according to the configured style.
+    fn format_age(age_secs: u64, timestamp: u64, format: AgeFormat) -> String {
+        match format {
+            AgeFormat::Compact => {
+                if age_secs < 60 {
+                    format!("{:>3}s", age_secs)
+                } else if age_secs < 3600 {
+                    format!("{:>3}m", age_secs / 60)
+                } else {
+                    format!("{:>3}h", age_secs / 3600)
+                }
+            }
+            AgeFormat::Human => {
+                if age_secs < 10 {
+                    String::from("just now")
+                } else if age_secs < 60 {
+                    format!("{} sec", age_secs)
+                } else if age_secs < 3600 {
+                    format!("{} min", age_secs / 60)
+                } else if age_secs < 86400 {
+                    format!("{} h", age_secs / 3600)
+                } else {
+                    format!("{} d", age_secs / 86400)
+                }
+            }
+            AgeFormat::Clock => {
+                if age_secs < 60 {
+                    return String::from("now");
+                }
+                match chrono::DateTime::from_timestamp(timestamp as i64, 0) {
+                    Some(datetime) => {
+                        let datetime = datetime.with_timezone(&chrono::Local);
+                        if age_secs < 86400 {
+                            datetime.format("%H:%M").to_string()
+                        } else {
+                            datetime.format("%m-%d %H:%M").to_string()
+                        }
+                    }
+                    None => String::new(),
+                }
+            }
+        }
+    }
+
     /// Draws the window content with multiple notifications.
     fn draw(
         &self,
@@ -463,14 +1835,21 @@ impl X11Window {
         unread_count: usize,
         config: &Config,
     ) -> Result<()> {
-        if notifications.is_empty() {
+        // Narrow the stack down to the entries matching the inline filter
+        let filter_query = self.filter.lock().expect("failed to lock filter").clone();
+        let notifications = match &filter_query {
+            Some(query) if !query.is_empty() => apply_filter(notifications, query),
+            _ => notifications,
+        };
+        if notifications.is_empty() && filter_query.is_none() {
             return Ok(());
         }
 
-        let now = std::time::SystemTime::now()
+        let now_ms = std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
-            .map(|d| d.as_secs())
+            .map(|d| d.as_millis() as u64)
             .unwrap_or(0);
+        let now = now_ms / 1000;
 
         // Set layout width for text wrapping
         let wrap_width = config.global.min_width.unwrap_or(600) as i32;
@@ -480,6 +1859,18 @@ impl X11Window {
         // Reverse to show newest first
         let mut notifications_reversed: Vec<_> = notifications.iter().collect();
         notifications_reversed.reverse();
+        // Maps a reversed index back to the original notifications vec
+        let mut original_indices: Vec<usize> = (0..notifications_reversed.len()).rev().collect();
+        // Unacknowledged criticals stay pinned at the top of the stack,
+        // newest first among themselves, no matter what arrives later
+        if config.global.critical_require_ack {
+            let mut paired: Vec<_> = original_indices
+                .into_iter()
+                .zip(notifications_reversed)
+                .collect();
+            paired.sort_by_key(|(_, n)| usize::from(!matches!(n.urgency, Urgency::Critical)));
+            (original_indices, notifications_reversed) = paired.into_iter().unzip();
+        }
 
         // Build notification entries with their markup and background colors
         struct NotificationEntry {
@@ -489,13 +1880,183 @@ impl X11Window {
             is_separator: bool,
             /// Index in original notifications vec (None for separators and footer)
             original_index: Option<usize>,
+            /// Remaining fraction of the timeout and bar color, if a
+            /// countdown bar should be drawn for this entry
+            countdown: Option<(f64, Option<String>)>,
+            /// App name to render as a badge, when badges are enabled
+            badge: Option<String>,
+            /// App whose group this line expands or collapses on click
+            /// (collapsed group summaries and expanded group headers)
+            group: Option<String>,
+            /// Whether the entry lays out right-to-left
+            rtl: bool,
+            /// Image thumbnail from the sender's `image-data` hint
+            image: Option<ImageData>,
         }
 
         let separator_height = 2; // pixels
+        let countdown_bar_height = 2; // pixels
+
+        // Horizontal space reserved for the app badge on each entry
+        let badge_indent = if config.global.show_app_badge {
+            Self::BADGE_SIZE + 2.0 * Self::BADGE_PADDING
+        } else {
+            0.0
+        };
         let mut entries: Vec<NotificationEntry> = Vec::new();
 
-        for (idx, notification) in notifications_reversed.iter().enumerate() {
-            let urgency_config = config.get_urgency_config(&notification.urgency);
+        // Show the inline filter prompt as the first line while it is open
+        if let Some(query) = &filter_query {
+            let prompt_markup = format!("<tt>/{}▏</tt>", Self::escape_markup(query));
+            self.layout.set_markup(&prompt_markup);
+            let (_, height) = self.layout.pixel_size();
+            entries.push(NotificationEntry {
+                markup: prompt_markup,
+                bg_color: None,
+                height,
+                is_separator: false,
+                original_index: None,
+                countdown: None,
+                badge: None,
+                group: None,
+                rtl: false,
+                image: None,
+            });
+        }
+
+        // Entries hidden on each side of the visible window while the
+        // unread buffer extends past it (used by the header and footer)
+        let hidden_newer = self
+            .scroll_offset
+            .load(std::sync::atomic::Ordering::SeqCst)
+            .min(unread_count.saturating_sub(notifications.len()));
+        let hidden_older = unread_count
+            .saturating_sub(notifications.len())
+            .saturating_sub(hidden_newer);
+
+        // Configurable header line above the stack; rendering to
+        // whitespace omits it
+        if let Some(raw_template) = config.global.header_template.as_deref() {
+            match self.render_stack_template(
+                raw_template,
+                unread_count,
+                notifications.len(),
+                hidden_older,
+                hidden_newer,
+            ) {
+                Ok(markup) if !markup.trim().is_empty() => {
+                    self.layout.set_markup(&markup);
+                    let (_, height) = self.layout.pixel_size();
+                    entries.push(NotificationEntry {
+                        markup,
+                        bg_color: None,
+                        height,
+                        is_separator: false,
+                        original_index: None,
+                        countdown: None,
+                        badge: None,
+                        group: None,
+                        rtl: false,
+                        image: None,
+                    });
+                }
+                Ok(_) => {}
+                Err(e) => log::warn!("failed to render header template: {}", e),
+            }
+        }
+
+        // With collapse_low enabled, low-urgency entries fold into a
+        // one-line strip until the user clicks it
+        let hide_low = config.global.collapse_low
+            && !self
+                .low_expanded
+                .load(std::sync::atomic::Ordering::SeqCst);
+        let low_count = if config.global.collapse_low {
+            notifications_reversed
+                .iter()
+                .filter(|n| matches!(n.urgency, Urgency::Low))
+                .count()
+        } else {
+            0
+        };
+        let mut strip_entry_index = None;
+        let mut pushed_notification = false;
+
+        // With group_by_app, apps with several entries render as one
+        // collapsed line until expanded; expanded groups gather their
+        // entries behind the newest one under a fold header
+        let grouping = config.global.group_by_app;
+        let expanded_groups = if grouping {
+            self.expanded_groups
+                .lock()
+                .expect("failed to lock expanded groups")
+                .clone()
+        } else {
+            HashSet::new()
+        };
+        let mut group_counts: HashMap<&str, usize> = HashMap::new();
+        if grouping {
+            for notification in &notifications_reversed {
+                if hide_low && matches!(notification.urgency, Urgency::Low) {
+                    continue;
+                }
+                *group_counts.entry(notification.app_name.as_str()).or_insert(0) += 1;
+            }
+        }
+        let display_order: Vec<usize> = if grouping {
+            let mut order = Vec::new();
+            let mut seen = HashSet::new();
+            for (idx, notification) in notifications_reversed.iter().enumerate() {
+                if !seen.insert(notification.app_name.as_str()) {
+                    continue;
+                }
+                for (jdx, other) in notifications_reversed.iter().enumerate().skip(idx) {
+                    if other.app_name == notification.app_name {
+                        order.push(jdx);
+                    }
+                }
+            }
+            order
+        } else {
+            (0..notifications_reversed.len()).collect()
+        };
+        let mut grouped_seen: HashSet<String> = HashSet::new();
+
+        // Measuring is the expensive half of a refresh and most entries'
+        // markup is unchanged between frames, so reuse last draw's
+        // heights where the markup hash still matches
+        let mut height_cache = self
+            .height_cache
+            .lock()
+            .expect("failed to lock height cache");
+        let mut measured_heights = HashMap::new();
+
+        // 1-based position of the entry being built, for template numbering
+        let mut display_position = 0;
+        for idx in display_order {
+            let notification = notifications_reversed[idx];
+            if hide_low && matches!(notification.urgency, Urgency::Low) {
+                continue;
+            }
+            let group_size = if grouping {
+                group_counts
+                    .get(notification.app_name.as_str())
+                    .copied()
+                    .unwrap_or(1)
+            } else {
+                1
+            };
+            let grouped = group_size > 1;
+            let group_expanded = grouped && expanded_groups.contains(&notification.app_name);
+            let first_of_group = grouped && grouped_seen.insert(notification.app_name.clone());
+            let collapsed_group = grouped && !group_expanded;
+            if collapsed_group && !first_of_group {
+                // Already summarized by the group's newest entry
+                continue;
+            }
+            display_position += 1;
+            let urgency_config =
+                config.get_urgency_config(&notification.urgency, &notification.app_name);
             urgency_config.run_commands(notification)?;
 
             // Calculate age in seconds
@@ -511,69 +2072,311 @@ impl X11Window {
             // Get background color from rule or app_colors
             let bg_color = matching_rule
                 .and_then(|r| r.background.as_ref())
+                .or_else(|| {
+                    config
+                        .get_app_config(&notification.app_name)
+                        .and_then(|app_config| app_config.background.as_ref())
+                })
                 .or_else(|| config.get_app_color(&notification.app_name))
                 .cloned();
 
-            // Format age display
-            let age_display = if age_secs < 60 {
-                format!("{:>3}s", age_secs)
-            } else if age_secs < 3600 {
-                format!("{:>3}m", age_secs / 60)
-            } else {
-                format!("{:>3}h", age_secs / 3600)
+            // Format age display (rules can pick a different style)
+            let age_format = matching_rule
+                .and_then(|r| r.age_format)
+                .unwrap_or(config.global.age_format);
+            let age_display = Self::format_age(age_secs, notification.timestamp, age_format);
+
+            // Base direction: forced per rule, otherwise detected from
+            // the first strong directional character of the content
+            let rtl = match matching_rule
+                .and_then(|r| r.text_direction)
+                .unwrap_or_default()
+            {
+                TextDirection::Auto => {
+                    Self::is_rtl(&notification.summary, &notification.body)
+                }
+                TextDirection::Ltr => false,
+                TextDirection::Rtl => true,
             };
 
             // Escape text for Pango markup (preserve newlines in body)
             let app_name_escaped = Self::escape_markup(&notification.app_name);
             let summary_escaped = Self::escape_markup(&notification.summary);
-            let body_escaped = Self::escape_markup(&notification.body);
+            let body_escaped = if config.global.render_markdown {
+                crate::markdown::to_pango(&notification.body)
+            } else {
+                Self::escape_markup(&notification.body)
+            };
+
+            // Whitespace-only bodies collapse to the single-line layout
+            // unless explicitly configured otherwise
+            let body_is_empty = if config.global.collapse_empty_body {
+                notification.body.trim().is_empty()
+            } else {
+                notification.body.is_empty()
+            };
+
+            // Stacked duplicates show their total count after the summary
+            let repeat_badge = if notification.repeats > 0 {
+                format!(
+                    " <span foreground=\"#888888\">×{}</span>",
+                    notification.repeats + 1
+                )
+            } else {
+                String::new()
+            };
+
+            // A rule-level template override wins over the urgency
+            // section's; unset entries use the built-in layout below
+            let template_override = matching_rule
+                .and_then(|r| r.template.as_deref())
+                .or(urgency_config.template.as_deref());
 
             // Build the notification line with Pango markup (no background attr)
-            let markup = format!(
-                "<tt><span foreground=\"#888888\">{}</span></tt> {} <b>{}</b>{}",
-                age_display,
-                app_name_escaped,
-                summary_escaped,
-                if notification.body.is_empty() {
-                    String::new()
-                } else {
-                    format!("\n  {}", body_escaped)
+            let standard_markup = || {
+                format!(
+                    "<tt><span foreground=\"#888888\">{}</span></tt> {} <b>{}</b>{}{}",
+                    age_display,
+                    app_name_escaped,
+                    summary_escaped,
+                    repeat_badge,
+                    if body_is_empty {
+                        String::new()
+                    } else {
+                        format!("\n  {}", body_escaped)
+                    }
+                )
+            };
+            let markup = if collapsed_group {
+                // One line for the whole app, with the newest body
+                format!(
+                    "<tt><span foreground=\"#888888\">{}</span></tt> <b>▸ {} ({})</b>{}",
+                    age_display,
+                    app_name_escaped,
+                    group_size,
+                    if body_is_empty {
+                        String::new()
+                    } else {
+                        format!("\n  {}", body_escaped)
+                    }
+                )
+            } else if let Some(raw_template) = template_override {
+                match self.render_notification_with(
+                    raw_template,
+                    notification,
+                    urgency_config.text.clone(),
+                    unread_count,
+                    Some((display_position, notifications.len())),
+                ) {
+                    Ok(markup) => markup,
+                    Err(e) => {
+                        log::warn!("failed to render template override: {}", e);
+                        standard_markup()
+                    }
                 }
-            );
+            } else {
+                standard_markup()
+            };
 
-            // Calculate height for this entry
-            self.layout.set_markup(&markup);
-            let (_, height) = self.layout.pixel_size();
+            // Indented entries wrap earlier; mirror the paint path, where
+            // the image thumbnail wins over the badge when both apply
+            let text_indent = if notification.image.is_some() && !collapsed_group {
+                (Self::IMAGE_SIZE + 2.0 * Self::BADGE_PADDING) as i32
+            } else {
+                badge_indent as i32
+            };
 
-            // Map reversed index back to original: notifications_reversed[idx] == notifications[len-1-idx]
-            let original_idx = notifications.len() - 1 - idx;
+            // Calculate height for this entry, reusing the cached
+            // measurement when the markup and indent are unchanged
+            let markup_hash = {
+                let mut hasher = DefaultHasher::new();
+                markup.hash(&mut hasher);
+                wrap_width.hash(&mut hasher);
+                text_indent.hash(&mut hasher);
+                rtl.hash(&mut hasher);
+                hasher.finish()
+            };
+            let height = match height_cache.get(&notification.id) {
+                Some((hash, height)) if *hash == markup_hash => *height,
+                _ => {
+                    self.layout
+                        .set_width((wrap_width - text_indent) * pango::SCALE);
+                    if rtl {
+                        self.layout.context().set_base_dir(pango::Direction::Rtl);
+                    }
+                    self.layout.set_markup(&markup);
+                    let (_, height) = self.layout.pixel_size();
+                    if rtl {
+                        self.layout.context().set_base_dir(pango::Direction::Ltr);
+                    }
+                    self.layout.set_width(wrap_width * pango::SCALE);
+                    height
+                }
+            };
+            measured_heights.insert(notification.id, (markup_hash, height));
+            let height = if config.global.show_app_badge {
+                height.max((Self::BADGE_SIZE + 2.0 * Self::BADGE_PADDING) as i32)
+            } else {
+                height
+            };
+            // Entries with an image thumbnail are at least tall enough for it
+            let height = if notification.image.is_some() && !collapsed_group {
+                height.max((Self::IMAGE_SIZE + 2.0 * Self::BADGE_PADDING) as i32)
+            } else {
+                height
+            };
 
-            entries.push(NotificationEntry {
-                markup,
-                bg_color,
-                height,
-                is_separator: false,
-                original_index: Some(original_idx),
-            });
+            // Remaining fraction of the timeout for the countdown bar
+            let countdown = if config.global.show_countdown {
+                notification.deadline.and_then(|deadline| {
+                    let total = deadline.saturating_sub(notification.timestamp * 1000);
+                    if total == 0 {
+                        return None;
+                    }
+                    let remaining = deadline.saturating_sub(now_ms);
+                    Some((
+                        (remaining as f64 / total as f64).clamp(0.0, 1.0),
+                        urgency_config.countdown_color.clone(),
+                    ))
+                })
+            } else {
+                None
+            };
+            // Reserve room below the text so the bar never overlaps it
+            let height = height
+                + if countdown.is_some() {
+                    countdown_bar_height
+                } else {
+                    0
+                };
 
-            // Add separator between notifications (but not after the last one)
-            if idx < notifications_reversed.len() - 1 {
+            let original_idx = original_indices[idx];
+
+            // Add separator between notifications (but not before the first)
+            if pushed_notification {
                 entries.push(NotificationEntry {
                     markup: String::new(),
                     bg_color: None,
                     height: separator_height,
                     is_separator: true,
                     original_index: None,
+                    countdown: None,
+                    badge: None,
+                    group: None,
+                    rtl: false,
+                    image: None,
+                });
+            }
+            // Expanded groups start with a fold header that collapses
+            // them again on click
+            if grouped && group_expanded && first_of_group {
+                let header_markup = format!(
+                    "<span foreground=\"#888888\"><i>▾ {} ({})</i></span>",
+                    app_name_escaped, group_size
+                );
+                self.layout.set_markup(&header_markup);
+                let (_, header_height) = self.layout.pixel_size();
+                entries.push(NotificationEntry {
+                    markup: header_markup,
+                    bg_color: None,
+                    height: header_height,
+                    is_separator: false,
+                    original_index: None,
+                    countdown: None,
+                    badge: None,
+                    group: Some(notification.app_name.clone()),
+                    rtl: false,
+                    image: None,
                 });
             }
+            entries.push(NotificationEntry {
+                markup,
+                bg_color,
+                height,
+                is_separator: false,
+                original_index: (!collapsed_group).then_some(original_idx),
+                countdown: if collapsed_group { None } else { countdown },
+                badge: config
+                    .global
+                    .show_app_badge
+                    .then(|| notification.app_name.clone()),
+                group: collapsed_group.then(|| notification.app_name.clone()),
+                rtl,
+                image: (!collapsed_group)
+                    .then(|| notification.image.clone())
+                    .flatten(),
+            });
+            pushed_notification = true;
         }
 
-        // Add unread count if more than displayed
-        if unread_count > notifications.len() {
-            let more_markup = format!(
-                "<span foreground=\"#888888\"><i>... and {} more</i></span>",
-                unread_count - notifications.len()
-            );
+        // Swap in this frame's measurements so closed entries don't
+        // accumulate in the cache
+        *height_cache = measured_heights;
+        drop(height_cache);
+
+        // The low-urgency strip: collapsed it summarizes the hidden
+        // entries, expanded it offers a line to fold them away again
+        if config.global.collapse_low && low_count > 0 {
+            let strip_markup = if hide_low {
+                format!(
+                    "<span foreground=\"#888888\"><i>▸ {} low priority message{}</i></span>",
+                    low_count,
+                    if low_count == 1 { "" } else { "s" }
+                )
+            } else {
+                String::from("<span foreground=\"#888888\"><i>▾ hide low priority</i></span>")
+            };
+            self.layout.set_markup(&strip_markup);
+            let (_, height) = self.layout.pixel_size();
+            strip_entry_index = Some(entries.len());
+            entries.push(NotificationEntry {
+                markup: strip_markup,
+                bg_color: None,
+                height,
+                is_separator: false,
+                original_index: None,
+                countdown: None,
+                badge: None,
+                group: None,
+                rtl: false,
+                image: None,
+            });
+        }
+
+        // Add a footer: the configured template when one is set, otherwise
+        // the built-in line shown while the unread buffer extends past the
+        // visible window (scrolled, it shows what lies on each side)
+        let footer_markup = if let Some(raw_template) = config.global.footer_template.as_deref() {
+            match self.render_stack_template(
+                raw_template,
+                unread_count,
+                notifications.len(),
+                hidden_older,
+                hidden_newer,
+            ) {
+                Ok(markup) if !markup.trim().is_empty() => Some(markup),
+                Ok(_) => None,
+                Err(e) => {
+                    log::warn!("failed to render footer template: {}", e);
+                    None
+                }
+            }
+        } else if unread_count > notifications.len() {
+            Some(if hidden_newer > 0 {
+                format!(
+                    "<span foreground=\"#888888\"><i>... {} older below, {} newer above</i></span>",
+                    hidden_older, hidden_newer
+                )
+            } else {
+                format!(
+                    "<span foreground=\"#888888\"><i>... and {} more</i></span>",
+                    hidden_older
+                )
+            })
+        } else {
+            None
+        };
+        if let Some(more_markup) = footer_markup {
             self.layout.set_markup(&more_markup);
             let (_, height) = self.layout.pixel_size();
             entries.push(NotificationEntry {
@@ -582,17 +2385,80 @@ impl X11Window {
                 height,
                 is_separator: false,
                 original_index: None,
+                countdown: None,
+                badge: None,
+                group: None,
+                rtl: false,
+                image: None,
             });
         }
 
+        // Append the context menu below the stack while it is open
+        if let Some(menu) = &*self.menu.lock().expect("failed to lock menu") {
+            entries.push(NotificationEntry {
+                markup: String::new(),
+                bg_color: None,
+                height: separator_height,
+                is_separator: true,
+                original_index: None,
+                countdown: None,
+                badge: None,
+                group: None,
+                rtl: false,
+                image: None,
+            });
+            let header_markup = format!(
+                "<span foreground=\"#888888\"><i>{}</i></span>",
+                Self::escape_markup(&menu.notification.summary)
+            );
+            self.layout.set_markup(&header_markup);
+            let (_, height) = self.layout.pixel_size();
+            entries.push(NotificationEntry {
+                markup: header_markup,
+                bg_color: None,
+                height,
+                is_separator: false,
+                original_index: None,
+                countdown: None,
+                badge: None,
+                group: None,
+                rtl: false,
+                image: None,
+            });
+            for (idx, (label, _)) in menu.items.iter().enumerate() {
+                let selected = idx == menu.selected;
+                let item_markup = format!(
+                    "  {} {}",
+                    if selected { "▶" } else { " " },
+                    Self::escape_markup(label)
+                );
+                self.layout.set_markup(&item_markup);
+                let (_, height) = self.layout.pixel_size();
+                entries.push(NotificationEntry {
+                    markup: item_markup,
+                    bg_color: selected.then(|| String::from("#3a3a3a")),
+                    height,
+                    is_separator: false,
+                    original_index: None,
+                    countdown: None,
+                    badge: None,
+                    group: None,
+                    rtl: false,
+                    image: None,
+                });
+            }
+        }
+
         // Calculate total height
         let total_height: i32 = entries.iter().map(|e| e.height).sum();
 
-        // Use the urgency of the most recent notification for default background color
-        let newest_notification = notifications_reversed
-            .first()
-            .expect("notifications not empty");
-        let urgency_config = config.get_urgency_config(&newest_notification.urgency);
+        // Use the urgency of the most recent notification for default background
+        // color; with the filter showing no matches, fall back to normal urgency
+        let newest_notification = notifications_reversed.first();
+        let urgency_config = match newest_notification {
+            Some(newest) => config.get_urgency_config(&newest.urgency, &newest.app_name),
+            None => config.urgency_normal.clone(),
+        };
 
         // Calculate window dimensions
         let width_u32 = wrap_width as u32;
@@ -604,17 +2470,28 @@ impl X11Window {
         }
 
         // Calculate and apply window size if wrap_content is enabled
-        if config.global.wrap_content {
-            // Calculate new position based on origin and new size
-            let (x, y) = calculate_position_from_origin(
-                self.origin,
-                self.offset_x,
-                self.offset_y,
-                width_u32,
-                height_u32,
-                self.screen_width,
-                self.screen_height,
-            );
+        let (window_x, window_y) = if config.global.wrap_content {
+            // Anchor near the sending application's window if requested
+            let anchored = if config.global.anchor_to_app {
+                newest_notification.and_then(|newest| {
+                    self.anchor_position(connection, &newest.app_name, width_u32, height_u32)
+                })
+            } else {
+                None
+            };
+
+            // Otherwise calculate the position based on origin and new size
+            let (x, y) = anchored.unwrap_or_else(|| {
+                let params = self.params.read().expect("failed to read window parameters");
+                calculate_position_from_origin(
+                    params.origin,
+                    params.offset_x,
+                    params.offset_y,
+                    width_u32,
+                    height_u32,
+                    self.monitor,
+                )
+            });
 
             // Resize and reposition the window
             let values = ConfigureWindowAux::default()
@@ -626,17 +2503,46 @@ impl X11Window {
 
             // Resize the cairo surface to match the new window size
             self.surface.set_size(width_u32 as i32, height_u32 as i32)?;
+
+            (x as i32, y as i32)
+        } else {
+            self.calculate_position(width_u32, height_u32)
+        };
+
+        // Render the frame into an offscreen buffer and blit it in one
+        // operation, so a refresh never exposes a half-painted window
+        let buffer_surface = cairo::ImageSurface::create(
+            cairo::Format::ARgb32,
+            width_u32 as i32,
+            height_u32 as i32,
+        )?;
+        let buffer_context = CairoContext::new(&buffer_surface)?;
+        pango_functions::update_layout(&buffer_context, &self.layout);
+
+        // Shape the popup with rounded corners by clipping all drawing
+        buffer_context.reset_clip();
+        let corner_radius = config.global.corner_radius as f64;
+        if corner_radius > 0.0 {
+            Self::rounded_rectangle(
+                &buffer_context,
+                0.0,
+                0.0,
+                width_u32 as f64,
+                height_u32 as f64,
+                corner_radius,
+            );
+            buffer_context.clip();
         }
 
         // Clear the entire surface with default background color
         let background_color = urgency_config.background;
-        self.cairo_context.set_source_rgba(
+        buffer_context.set_source_rgba(
             background_color.red() / 255.0,
             background_color.green() / 255.0,
             background_color.blue() / 255.0,
             background_color.alpha(),
         );
-        self.cairo_context.paint()?;
+        buffer_context.paint()?;
 
         // Draw each entry with its background and text
         let foreground_color = urgency_config.foreground;
@@ -644,48 +2550,87 @@ impl X11Window {
 
         // Clear and rebuild entry bounds for click detection
         let mut new_bounds = Vec::new();
+        let mut strip_bounds = None;
+        let mut new_group_bounds = Vec::new();
 
-        for entry in &entries {
+        for (entry_idx, entry) in entries.iter().enumerate() {
             let y_start = y_pos as i32;
             let y_end = (y_pos + entry.height as f64) as i32;
+            if Some(entry_idx) == strip_entry_index {
+                strip_bounds = Some((y_start, y_end));
+            }
 
             if entry.is_separator {
                 // Draw separator as a horizontal line
-                self.cairo_context.set_source_rgba(0.27, 0.27, 0.27, 1.0); // #444444
-                self.cairo_context
+                buffer_context.set_source_rgba(0.27, 0.27, 0.27, 1.0); // #444444
+                buffer_context
                     .rectangle(0.0, y_pos, width_u32 as f64, entry.height as f64);
-                self.cairo_context.fill()?;
+                buffer_context.fill()?;
             } else {
                 // Track bounds for notification entries (not footer)
                 if let Some(idx) = entry.original_index {
-                    new_bounds.push((y_start, y_end, idx));
+                    new_bounds.push((y_start, y_end, notifications[idx].id));
+                }
+                if let Some(app) = &entry.group {
+                    new_group_bounds.push((y_start, y_end, app.clone()));
                 }
 
                 // Draw background rectangle if this entry has a custom color
                 if let Some(ref color) = entry.bg_color
                     && let Ok(rgb) = colorsys::Rgb::from_hex_str(color)
                 {
-                    self.cairo_context.set_source_rgba(
+                    buffer_context.set_source_rgba(
                         rgb.red() / 255.0,
                         rgb.green() / 255.0,
                         rgb.blue() / 255.0,
                         1.0,
                     );
-                    self.cairo_context
+                    buffer_context
                         .rectangle(0.0, y_pos, width_u32 as f64, entry.height as f64);
-                    self.cairo_context.fill()?;
+                    buffer_context.fill()?;
                 }
 
+                // Draw the image thumbnail or app badge on the left,
+                // vertically centered (the image wins when both apply)
+                let text_x = if let Some(ref image) = entry.image {
+                    let image_y = y_pos
+                        + (entry.height as f64
+                            - Self::IMAGE_SIZE.min(image.height as f64))
+                            / 2.0;
+                    self.draw_image(&buffer_context, image, Self::BADGE_PADDING, image_y)?;
+                    Self::IMAGE_SIZE + 2.0 * Self::BADGE_PADDING
+                } else if let Some(ref app_name) = entry.badge {
+                    let badge_y =
+                        y_pos + (entry.height as f64 - Self::BADGE_SIZE) / 2.0;
+                    self.draw_app_badge(&buffer_context, app_name, Self::BADGE_PADDING, badge_y)?;
+                    badge_indent
+                } else {
+                    0.0
+                };
+
                 // Draw the text
-                self.cairo_context.set_source_rgba(
+                buffer_context.set_source_rgba(
                     foreground_color.red() / 255.0,
                     foreground_color.green() / 255.0,
                     foreground_color.blue() / 255.0,
                     foreground_color.alpha(),
                 );
-                self.cairo_context.move_to(0., y_pos);
+                buffer_context.move_to(text_x, y_pos);
+                self.layout
+                    .set_width((wrap_width - text_x as i32) * pango::SCALE);
+                // RTL entries lay out from the right edge so the text
+                // hugs the side the script reads from
+                if entry.rtl {
+                    self.layout.context().set_base_dir(pango::Direction::Rtl);
+                    self.layout.set_alignment(pango::Alignment::Right);
+                }
                 self.layout.set_markup(&entry.markup);
-                pango_functions::show_layout(&self.cairo_context, &self.layout);
+                pango_functions::show_layout(&buffer_context, &self.layout);
+                if entry.rtl {
+                    self.layout.context().set_base_dir(pango::Direction::Ltr);
+                    self.layout.set_alignment(pango::Alignment::Left);
+                }
+                self.layout.set_width(wrap_width * pango::SCALE);
 
                 // Draw close button (×) on the right side for notification entries
                 if entry.original_index.is_some() {
@@ -694,34 +2639,254 @@ impl X11Window {
                     let center_y = y_pos + (entry.height as f64 / 2.0);
 
                     // Draw subtle background for close button
-                    self.cairo_context.set_source_rgba(0.3, 0.3, 0.3, 0.5);
-                    self.cairo_context
+                    buffer_context.set_source_rgba(0.3, 0.3, 0.3, 0.5);
+                    buffer_context
                         .rectangle(close_x, y_pos, close_btn_width, entry.height as f64);
-                    self.cairo_context.fill()?;
+                    buffer_context.fill()?;
 
                     // Draw × symbol
-                    self.cairo_context.set_source_rgba(0.7, 0.7, 0.7, 1.0);
+                    buffer_context.set_source_rgba(0.7, 0.7, 0.7, 1.0);
                     self.layout.set_markup("<b>×</b>");
                     let (text_w, text_h) = self.layout.pixel_size();
-                    self.cairo_context.move_to(
+                    buffer_context.move_to(
                         close_x + (close_btn_width - text_w as f64) / 2.0,
                         center_y - (text_h as f64 / 2.0),
                     );
-                    pango_functions::show_layout(&self.cairo_context, &self.layout);
+                    pango_functions::show_layout(&buffer_context, &self.layout);
+                }
+
+                // Draw the shrinking countdown bar along the entry's bottom edge
+                if let Some((fraction, color)) = &entry.countdown {
+                    let bar_color = color
+                        .as_deref()
+                        .and_then(|color| colorsys::Rgb::from_hex_str(color).ok())
+                        .unwrap_or_else(|| foreground_color.clone());
+                    buffer_context.set_source_rgba(
+                        bar_color.red() / 255.0,
+                        bar_color.green() / 255.0,
+                        bar_color.blue() / 255.0,
+                        1.0,
+                    );
+                    buffer_context.rectangle(
+                        0.0,
+                        y_pos + entry.height as f64 - countdown_bar_height as f64,
+                        width_u32 as f64 * fraction,
+                        countdown_bar_height as f64,
+                    );
+                    buffer_context.fill()?;
                 }
             }
 
             y_pos += entry.height as f64;
         }
 
+        // Record where the low-urgency strip ended up for click detection
+        *self
+            .low_strip_bounds
+            .lock()
+            .expect("failed to lock low strip bounds") = strip_bounds;
+
+        // Likewise for the group lines
+        *self
+            .group_bounds
+            .lock()
+            .expect("failed to lock group bounds") = new_group_bounds;
+
+        // Publish the layout for the control interface before the bounds
+        // are handed over for click detection
+        if let Ok(mut snapshot) = self.layout_snapshot.lock() {
+            *snapshot = LayoutSnapshot {
+                x: window_x,
+                y: window_y,
+                width: width_u32,
+                height: height_u32,
+                entries: new_bounds
+                    .iter()
+                    .map(|(y_start, y_end, id)| LayoutEntry {
+                        id: *id,
+                        y_start: *y_start,
+                        y_end: *y_end,
+                    })
+                    .collect(),
+            };
+        }
+
         // Store bounds for click detection
         if let Ok(mut bounds) = self.entry_bounds.lock() {
             *bounds = new_bounds;
         }
 
-        // Flush the surface to ensure changes are visible
+        // Draw the window border on top of the content
+        let border_width = config.global.border_width as f64;
+        if border_width > 0.0 {
+            let border_color = config
+                .global
+                .border_color
+                .as_deref()
+                .and_then(|color| colorsys::Rgb::from_hex_str(color).ok())
+                .unwrap_or_else(|| foreground_color.clone());
+            buffer_context.set_source_rgba(
+                border_color.red() / 255.0,
+                border_color.green() / 255.0,
+                border_color.blue() / 255.0,
+                1.0,
+            );
+            buffer_context.set_line_width(border_width);
+            // Stroke along the middle of the border so it stays inside the window
+            let inset = border_width / 2.0;
+            Self::rounded_rectangle(
+                &buffer_context,
+                inset,
+                inset,
+                width_u32 as f64 - border_width,
+                height_u32 as f64 - border_width,
+                (corner_radius - inset).max(0.0),
+            );
+            buffer_context.stroke()?;
+        }
+
+        // Blit the finished frame onto the window surface
+        drop(buffer_context);
+        self.cairo_context.reset_clip();
+        if corner_radius > 0.0 {
+            Self::rounded_rectangle(
+                &self.cairo_context,
+                0.0,
+                0.0,
+                width_u32 as f64,
+                height_u32 as f64,
+                corner_radius,
+            );
+            self.cairo_context.clip();
+        }
+        self.cairo_context.set_source_surface(&buffer_surface, 0.0, 0.0)?;
+        self.cairo_context.paint()?;
         self.surface.flush();
 
         Ok(())
     }
+
+    /// Builds a rounded rectangle path on the cairo context.
+    ///
+    /// A radius of zero (or less) falls back to a plain rectangle.
+    fn rounded_rectangle(
+        context: &CairoContext,
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+        radius: f64,
+    ) {
+        use std::f64::consts::{FRAC_PI_2, PI};
+        if radius <= 0.0 {
+            context.rectangle(x, y, width, height);
+            return;
+        }
+        let radius = radius.min(width / 2.0).min(height / 2.0);
+        context.new_sub_path();
+        context.arc(x + width - radius, y + radius, radius, -FRAC_PI_2, 0.0);
+        context.arc(x + width - radius, y + height - radius, radius, 0.0, FRAC_PI_2);
+        context.arc(x + radius, y + height - radius, radius, FRAC_PI_2, PI);
+        context.arc(x + radius, y + radius, radius, PI, PI + FRAC_PI_2);
+        context.close_path();
+    }
+
+    /// Side length the image-data thumbnail is scaled to fit, in pixels.
+    const IMAGE_SIZE: f64 = 32.0;
+
+    /// Paints the sender's image hint as a thumbnail at the given
+    /// position, scaled down to [`X11Window::IMAGE_SIZE`] if needed.
+    ///
+    /// The Cairo surface is created directly over the shared pixel
+    /// buffer, so redraws never copy the image data again.
+    fn draw_image(&self, context: &CairoContext, image: &ImageData, x: f64, y: f64) -> Result<()> {
+        let data = Arc::clone(&image.data);
+        // SAFETY: `data` keeps the buffer alive past the paint below, and
+        // cairo only reads from source surfaces
+        let surface = unsafe {
+            cairo::ImageSurface::create_for_data_unsafe(
+                data.as_ptr().cast_mut(),
+                cairo::Format::ARgb32,
+                image.width,
+                image.height,
+                image.width * 4,
+            )?
+        };
+        let scale = (Self::IMAGE_SIZE / image.width as f64)
+            .min(Self::IMAGE_SIZE / image.height as f64)
+            .min(1.0);
+        context.save()?;
+        context.translate(x, y);
+        context.scale(scale, scale);
+        context.set_source_surface(&surface, 0.0, 0.0)?;
+        context.paint()?;
+        context.restore()?;
+        drop(data);
+        Ok(())
+    }
+
+    /// Side length of the app badge square in pixels.
+    const BADGE_SIZE: f64 = 20.0;
+
+    /// Horizontal padding around the app badge in pixels.
+    const BADGE_PADDING: f64 = 4.0;
+
+    /// Draws a colored rounded square with the app's initials at the
+    /// given position.
+    ///
+    /// The color is picked deterministically from a fixed palette so the
+    /// same application always gets the same badge.
+    fn draw_app_badge(&self, context: &CairoContext, app_name: &str, x: f64, y: f64) -> Result<()> {
+        let (red, green, blue) = Self::badge_color(app_name);
+        context.set_source_rgba(red, green, blue, 1.0);
+        Self::rounded_rectangle(context, x, y, Self::BADGE_SIZE, Self::BADGE_SIZE, 5.0);
+        context.fill()?;
+
+        context.set_source_rgba(1.0, 1.0, 1.0, 1.0);
+        self.layout.set_markup(&format!(
+            "<span size=\"x-small\"><b>{}</b></span>",
+            Self::escape_markup(&Self::app_initials(app_name))
+        ));
+        let (text_w, text_h) = self.layout.pixel_size();
+        context.move_to(
+            x + (Self::BADGE_SIZE - text_w as f64) / 2.0,
+            y + (Self::BADGE_SIZE - text_h as f64) / 2.0,
+        );
+        pango_functions::show_layout(context, &self.layout);
+        Ok(())
+    }
+
+    /// Returns up to two uppercased initials for the application name,
+    /// splitting on whitespace, dashes and underscores.
+    fn app_initials(app_name: &str) -> String {
+        let initials: String = app_name
+            .split(|c: char| c.is_whitespace() || c == '-' || c == '_')
+            .filter_map(|word| word.chars().next())
+            .take(2)
+            .flat_map(char::to_uppercase)
+            .collect();
+        if initials.is_empty() {
+            String::from("?")
+        } else {
+            initials
+        }
+    }
+
+    /// Picks a badge color for the application from a fixed palette,
+    /// keyed on a hash of the name.
+    fn badge_color(app_name: &str) -> (f64, f64, f64) {
+        const PALETTE: [(f64, f64, f64); 8] = [
+            (0.80, 0.36, 0.36), // red
+            (0.85, 0.55, 0.25), // orange
+            (0.75, 0.70, 0.25), // yellow
+            (0.35, 0.65, 0.35), // green
+            (0.30, 0.60, 0.60), // teal
+            (0.35, 0.50, 0.80), // blue
+            (0.55, 0.40, 0.75), // purple
+            (0.75, 0.40, 0.60), // pink
+        ];
+        let mut hasher = DefaultHasher::new();
+        app_name.hash(&mut hasher);
+        PALETTE[(hasher.finish() % PALETTE.len() as u64) as usize]
+    }
 }
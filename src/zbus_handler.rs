@@ -1,6 +1,6 @@
 #![allow(missing_docs, clippy::too_many_arguments)]
 
-use crate::notification::{Action, Notification, Urgency};
+use crate::notification::{Action, ImageData, Manager, Notification, Urgency};
 use std::collections::HashMap;
 use std::sync::mpsc::Sender;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
@@ -9,6 +9,23 @@ use zbus::{fdo, interface};
 
 const NOTIFICATION_SPEC_VERSION: &str = "1.2";
 
+/// Decodes an `image-data` hint structure (`iiibiiay`) into shared pixels.
+///
+/// The raw bytes leave the D-Bus message exactly once; everything past
+/// this point clones the [`Arc`](std::sync::Arc) instead of the buffer.
+fn parse_image_hint(value: &zbus::zvariant::Value<'_>) -> Option<ImageData> {
+    let zbus::zvariant::Value::Structure(structure) = value else {
+        return None;
+    };
+    let fields = structure.fields();
+    let width: i32 = fields.first()?.try_into().ok()?;
+    let height: i32 = fields.get(1)?.try_into().ok()?;
+    let rowstride: i32 = fields.get(2)?.try_into().ok()?;
+    let has_alpha: bool = fields.get(3)?.try_into().ok()?;
+    let data: Vec<u8> = fields.get(6)?.try_clone().ok()?.try_into().ok()?;
+    ImageData::from_rgb(width, height, rowstride, has_alpha, &data)
+}
+
 /// Notification interface exposed over D-Bus.
 pub struct Notifications {
     /// Counter for generating unique notification IDs.
@@ -45,6 +62,8 @@ impl Notifications {
             "body".to_string(),
             "body-markup".to_string(),
             "actions".to_string(),
+            "sound".to_string(),
+            "icon-static".to_string(),
         ])
     }
 
@@ -53,7 +72,7 @@ impl Notifications {
         &self,
         app_name: String,     // Name of the app sending the notification
         replaces_id: u32,     // ID of notification to replace, if any
-        _app_icon: String,    // Icon field
+        app_icon: String,     // Icon field
         summary: String,      // Title of the notification
         body: String,         // Body text
         actions: Vec<String>, // Action keys and labels
@@ -79,6 +98,38 @@ impl Notifications {
             .map(|v: u8| Urgency::from(v as u64))
             .unwrap_or_default();
 
+        // Parse the sound hints: a file path takes precedence over a
+        // themed sound name, and suppress-sound silences both.
+        let sound = hints
+            .get("sound-file")
+            .or_else(|| hints.get("sound-name"))
+            .and_then(|v| v.downcast_ref::<&str>().ok())
+            .map(String::from);
+        let suppress_sound = hints
+            .get("suppress-sound")
+            .and_then(|v| v.try_into().ok())
+            .unwrap_or(false);
+
+        // Hints exposed to templates: the well-known ones as typed fields,
+        // the rest stringified (bulky image payloads are skipped)
+        let category = hints
+            .get("category")
+            .and_then(|v| v.downcast_ref::<&str>().ok())
+            .map(String::from);
+        let value = hints.get("value").and_then(|v| v.try_into().ok());
+        let image = hints
+            .get("image-data")
+            .or_else(|| hints.get("image_data"))
+            .or_else(|| hints.get("icon_data"))
+            .and_then(parse_image_hint);
+        let hints_map: HashMap<String, String> = hints
+            .iter()
+            .filter(|(key, _)| {
+                !matches!(key.as_str(), "image-data" | "image_data" | "icon_data")
+            })
+            .map(|(key, value)| (key.clone(), value.to_string()))
+            .collect();
+
         // Convert timeout.
         let expire_timeout = if expire_timeout > 0 {
             Some(Duration::from_millis(expire_timeout as u64))
@@ -103,6 +154,15 @@ impl Notifications {
             is_read: false,
             timestamp,
             actions,
+            deadline: None,
+            repeats: 0,
+            sound,
+            suppress_sound,
+            icon: app_icon,
+            category,
+            value,
+            hints: hints_map,
+            image,
         };
 
         // Send the notification to the main thread for display.
@@ -141,12 +201,29 @@ impl Notifications {
 /// Control interface for managing notifications.
 pub struct NotificationControl {
     sender: Sender<Action>,
+    /// Handle to the notification manager for inspecting the unread buffer.
+    manager: Manager,
+    /// Handle to the notification window for inspecting its layout;
+    /// absent in headless mode.
+    window: Option<std::sync::Arc<crate::x11::X11Window>>,
+    /// Shared runtime configuration, for inspection and runtime tuning.
+    config: std::sync::Arc<std::sync::RwLock<crate::config::Config>>,
 }
 
 impl NotificationControl {
     /// Creates a new notification control handle.
-    pub fn new(sender: Sender<Action>) -> Self {
-        Self { sender }
+    pub fn new(
+        sender: Sender<Action>,
+        manager: Manager,
+        window: Option<std::sync::Arc<crate::x11::X11Window>>,
+        config: std::sync::Arc<std::sync::RwLock<crate::config::Config>>,
+    ) -> Self {
+        Self {
+            sender,
+            manager,
+            window,
+            config,
+        }
     }
 }
 
@@ -175,4 +252,156 @@ impl NotificationControl {
             .map_err(|e| fdo::Error::Failed(e.to_string()))?;
         Ok(())
     }
+
+    /// Restores the most recently dismissed notification (undo-close).
+    async fn pop(&self) -> fdo::Result<()> {
+        self.sender
+            .send(Action::Pop)
+            .map_err(|e| fdo::Error::Failed(e.to_string()))?;
+        Ok(())
+    }
+
+    /// Returns the popup's position, size and per-entry bounds as JSON.
+    async fn layout(&self) -> fdo::Result<String> {
+        let window = self
+            .window
+            .as_ref()
+            .ok_or_else(|| fdo::Error::Failed("no layout: daemon is running headless".to_string()))?;
+        serde_json::to_string_pretty(&window.get_layout())
+            .map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+
+    /// Returns the daemon's effective configuration as JSON.
+    async fn get_config(&self) -> fdo::Result<String> {
+        let config = self
+            .config
+            .read()
+            .map_err(|e| fdo::Error::Failed(format!("Lock poisoned: {}", e)))?;
+        serde_json::to_string_pretty(&*config).map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+
+    /// Changes a runtime-tunable option.
+    ///
+    /// Supported keys: `dnd` (bool), `display_limit` (integer) and
+    /// `origin` (e.g. "top-right"). The change lasts until the next
+    /// configuration reload.
+    async fn set_option(&self, key: String, value: String) -> fdo::Result<()> {
+        use std::str::FromStr;
+        let mut config = self
+            .config
+            .write()
+            .map_err(|e| fdo::Error::Failed(format!("Lock poisoned: {}", e)))?;
+        match key.as_str() {
+            "dnd" => {
+                config.global.dnd = value
+                    .parse()
+                    .map_err(|e| fdo::Error::Failed(format!("Invalid bool: {}", e)))?;
+            }
+            "display_limit" => {
+                config.global.display_limit = value
+                    .parse()
+                    .map_err(|e| fdo::Error::Failed(format!("Invalid limit: {}", e)))?;
+            }
+            "origin" => {
+                config.global.origin = crate::config::Origin::from_str(&value)
+                    .map_err(|e| fdo::Error::Failed(e.to_string()))?;
+            }
+            _ => {
+                return Err(fdo::Error::Failed(format!(
+                    "Unknown option '{}' (supported: dnd, display_limit, origin)",
+                    key
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns displayed/unread counts and DND state as JSON.
+    ///
+    /// DND is reported active when either the manual toggle or the
+    /// quiet-hours schedule currently suppresses popups.
+    async fn stats(&self) -> fdo::Result<String> {
+        let config = self
+            .config
+            .read()
+            .map_err(|e| fdo::Error::Failed(format!("Lock poisoned: {}", e)))?;
+        let unread = self.manager.get_unread_count();
+        let displayed = if config.global.display_limit > 0 {
+            unread.min(config.global.display_limit)
+        } else {
+            unread
+        };
+        let dnd = config.global.dnd || config.global.quiet_hours_active();
+        serde_json::to_string(&serde_json::json!({
+            "displayed": displayed,
+            "unread": unread,
+            "dnd": dnd,
+        }))
+        .map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+
+    /// Exports the unread notification buffer as a versioned JSON document.
+    async fn export_unread(&self) -> fdo::Result<String> {
+        let unread = self.manager.get_unread_buffer(0);
+        crate::schema::to_json(&unread).map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+
+    /// Temporarily mutes applications matching the glob pattern.
+    ///
+    /// The duration is in humantime format; an empty string mutes until
+    /// unmuted. History still records notifications from muted apps.
+    async fn mute(&self, pattern: String, duration: String) -> fdo::Result<()> {
+        let duration = if duration.is_empty() {
+            None
+        } else {
+            Some(
+                humantime::parse_duration(&duration)
+                    .map_err(|e| fdo::Error::Failed(format!("Invalid duration: {}", e)))?,
+            )
+        };
+        self.manager.mute(pattern, duration);
+        Ok(())
+    }
+
+    /// Removes a mute. Returns true if one existed for the pattern.
+    async fn unmute(&self, pattern: String) -> fdo::Result<bool> {
+        Ok(self.manager.unmute(&pattern))
+    }
+
+    /// Returns the active mutes as JSON.
+    async fn mutes(&self) -> fdo::Result<String> {
+        serde_json::to_string_pretty(&self.manager.mutes())
+            .map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+
+    /// Invokes an action on a displayed notification, as if the user had
+    /// clicked it. An empty key falls back to "default".
+    async fn invoke_action(&self, id: u32, action_key: String) -> fdo::Result<()> {
+        let action_key = if action_key.is_empty() {
+            "default".to_string()
+        } else {
+            action_key
+        };
+        self.sender
+            .send(Action::Invoke(id, action_key))
+            .map_err(|e| fdo::Error::Failed(e.to_string()))?;
+        Ok(())
+    }
+
+    /// Signal emitted when a notification is recorded into history,
+    /// carrying the entry as JSON; `runst history --follow` streams it.
+    #[zbus(signal)]
+    async fn history_added(signal_emitter: &SignalEmitter<'_>, entry: String) -> zbus::Result<()>;
+
+    /// Imports unread notifications from JSON, re-posting them for display.
+    async fn import_unread(&self, data: String) -> fdo::Result<()> {
+        let notifications: Vec<Notification> = crate::schema::from_json(&data)
+            .map_err(|e| fdo::Error::Failed(format!("Invalid notification data: {}", e)))?;
+        for notification in notifications {
+            self.sender
+                .send(Action::Show(notification))
+                .map_err(|e| fdo::Error::Failed(format!("Send failed: {}", e)))?;
+        }
+        Ok(())
+    }
 }
@@ -0,0 +1,337 @@
+//! End-to-end tests against a real daemon on a private Xvfb display and
+//! D-Bus session.
+//!
+//! These are heavyweight and environment-dependent (they need the `Xvfb`
+//! and `dbus-daemon` binaries), so they only run when explicitly
+//! requested:
+//!
+//! ```sh
+//! RUNST_XVFB_TESTS=1 cargo test --test xvfb
+//! ```
+//!
+//! Without the environment variable every test is a silent no-op, keeping
+//! plain `cargo test` green on machines without an X stack.
+
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::time::{Duration, Instant};
+use zbus::zvariant;
+
+/// Well-known bus name of the notification daemon.
+const BUS_NAME: &str = "org.freedesktop.Notifications";
+
+/// Object path of the notification interface.
+const NOTIFICATIONS_PATH: &str = "/org/freedesktop/Notifications";
+
+/// Object path of the control interface.
+const CONTROL_PATH: &str = "/org/freedesktop/Notifications/ctl";
+
+/// Name of the control interface.
+const CONTROL_INTERFACE: &str = "org.freedesktop.NotificationControl";
+
+/// Returns true when the Xvfb suite was requested via the environment.
+fn enabled() -> bool {
+    if std::env::var_os("RUNST_XVFB_TESTS").is_some() {
+        true
+    } else {
+        eprintln!("skipping: set RUNST_XVFB_TESTS=1 to run the Xvfb suite");
+        false
+    }
+}
+
+/// A private Xvfb display, D-Bus session and daemon instance.
+///
+/// Everything is torn down (killed) on drop, and all state lives under a
+/// temporary directory so the suite never touches the user's history.
+struct Harness {
+    xvfb: Child,
+    dbus: Child,
+    daemon: Child,
+    /// Address of the private session bus.
+    bus_address: String,
+    /// Temporary home directory of the daemon.
+    _tmp: tempfile::TempDir,
+    /// Path the daemon writes its history to.
+    history_path: PathBuf,
+}
+
+impl Harness {
+    /// Starts Xvfb, a private session bus and the daemon, and waits until
+    /// the daemon has claimed its bus name.
+    fn start() -> Harness {
+        // Each harness gets its own display so tests can run concurrently
+        static NEXT_DISPLAY: std::sync::atomic::AtomicU32 = std::sync::atomic::AtomicU32::new(0);
+        let tmp = tempfile::tempdir().expect("failed to create tempdir");
+        let display = format!(
+            ":{}",
+            90 + std::process::id() % 100
+                + NEXT_DISPLAY.fetch_add(1, std::sync::atomic::Ordering::SeqCst)
+        );
+
+        let xvfb = Command::new("Xvfb")
+            .args([&display, "-screen", "0", "1024x768x24"])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .expect("failed to start Xvfb; is it installed?");
+        wait_for(Duration::from_secs(5), || {
+            Path::new(&format!("/tmp/.X11-unix/X{}", &display[1..])).exists()
+        });
+
+        let mut dbus = Command::new("dbus-daemon")
+            .args(["--session", "--nofork", "--print-address=1"])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .expect("failed to start dbus-daemon; is it installed?");
+        let mut bus_address = String::new();
+        BufReader::new(dbus.stdout.take().expect("dbus-daemon stdout"))
+            .read_line(&mut bus_address)
+            .expect("failed to read dbus-daemon address");
+        let bus_address = bus_address.trim().to_string();
+        assert!(!bus_address.is_empty(), "dbus-daemon printed no address");
+
+        let data_dir = tmp.path().join("data");
+        let daemon = Command::new(env!("CARGO_BIN_EXE_runst"))
+            .env("DISPLAY", &display)
+            .env("DBUS_SESSION_BUS_ADDRESS", &bus_address)
+            .env("HOME", tmp.path())
+            .env("XDG_DATA_HOME", &data_dir)
+            .env("XDG_CONFIG_HOME", tmp.path().join("config"))
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .expect("failed to start the daemon");
+
+        let harness = Harness {
+            xvfb,
+            dbus,
+            daemon,
+            bus_address,
+            _tmp: tmp,
+            history_path: data_dir.join("runst").join("history.json"),
+        };
+
+        // Wait for the daemon to claim its well-known name
+        let connection = harness.connect();
+        wait_for(Duration::from_secs(10), || {
+            connection
+                .call_method(
+                    Some(BUS_NAME),
+                    NOTIFICATIONS_PATH,
+                    Some(BUS_NAME),
+                    "GetServerInformation",
+                    &(),
+                )
+                .is_ok()
+        });
+        harness
+    }
+
+    /// Opens a new client connection to the private session bus.
+    fn connect(&self) -> zbus::blocking::Connection {
+        zbus::blocking::connection::Builder::address(self.bus_address.as_str())
+            .expect("invalid bus address")
+            .build()
+            .expect("failed to connect to the private bus")
+    }
+
+    /// Sends a notification through the daemon's `Notify` method and
+    /// returns the assigned ID.
+    fn notify(
+        &self,
+        connection: &zbus::blocking::Connection,
+        app_name: &str,
+        summary: &str,
+        body: &str,
+    ) -> u32 {
+        let mut hints: HashMap<&str, zvariant::Value> = HashMap::new();
+        hints.insert("urgency", zvariant::Value::from(1u8));
+        connection
+            .call_method(
+                Some(BUS_NAME),
+                NOTIFICATIONS_PATH,
+                Some(BUS_NAME),
+                "Notify",
+                &(
+                    app_name,
+                    0u32,
+                    "",
+                    summary,
+                    body,
+                    Vec::<String>::new(),
+                    hints,
+                    0i32,
+                ),
+            )
+            .expect("Notify failed")
+            .body()
+            .deserialize()
+            .expect("Notify returned no ID")
+    }
+
+    /// Returns the unread buffer as reported by the control interface.
+    fn export_unread(&self, connection: &zbus::blocking::Connection) -> serde_json::Value {
+        let json: String = connection
+            .call_method(
+                Some(BUS_NAME),
+                CONTROL_PATH,
+                Some(CONTROL_INTERFACE),
+                "ExportUnread",
+                &(),
+            )
+            .expect("ExportUnread failed")
+            .body()
+            .deserialize()
+            .expect("ExportUnread returned no body");
+        serde_json::from_str(&json).expect("ExportUnread returned invalid JSON")
+    }
+}
+
+impl Drop for Harness {
+    fn drop(&mut self) {
+        let _ = self.daemon.kill();
+        let _ = self.daemon.wait();
+        let _ = self.dbus.kill();
+        let _ = self.dbus.wait();
+        let _ = self.xvfb.kill();
+        let _ = self.xvfb.wait();
+    }
+}
+
+/// Polls `condition` until it holds or the timeout elapses.
+fn wait_for(timeout: Duration, condition: impl Fn() -> bool) {
+    let start = Instant::now();
+    while !condition() {
+        assert!(
+            start.elapsed() < timeout,
+            "condition not met within {timeout:?}"
+        );
+        std::thread::sleep(Duration::from_millis(100));
+    }
+}
+
+#[test]
+fn daemon_end_to_end() {
+    if !enabled() {
+        return;
+    }
+    let harness = Harness::start();
+    let connection = harness.connect();
+
+    // The daemon identifies itself correctly
+    let info: (String, String, String, String) = connection
+        .call_method(
+            Some(BUS_NAME),
+            NOTIFICATIONS_PATH,
+            Some(BUS_NAME),
+            "GetServerInformation",
+            &(),
+        )
+        .expect("GetServerInformation failed")
+        .body()
+        .deserialize()
+        .expect("invalid GetServerInformation reply");
+    assert_eq!(info.0, "runst");
+
+    // A sent notification shows up in the unread buffer
+    let id = harness.notify(&connection, "xvfb-test", "integration summary", "hello");
+    assert!(id > 0);
+    wait_for(Duration::from_secs(5), || {
+        harness
+            .export_unread(&connection)
+            .as_array()
+            .expect("unread buffer is not an array")
+            .iter()
+            .any(|n| n["summary"] == "integration summary")
+    });
+
+    // ...and in the persisted history
+    wait_for(Duration::from_secs(5), || {
+        std::fs::read_to_string(&harness.history_path)
+            .map(|history| history.contains("integration summary"))
+            .unwrap_or(false)
+    });
+
+    // Closing it removes it from the unread buffer
+    connection
+        .call_method(
+            Some(BUS_NAME),
+            NOTIFICATIONS_PATH,
+            Some(BUS_NAME),
+            "CloseNotification",
+            &(id,),
+        )
+        .expect("CloseNotification failed");
+    wait_for(Duration::from_secs(5), || {
+        !harness
+            .export_unread(&connection)
+            .as_array()
+            .expect("unread buffer is not an array")
+            .iter()
+            .any(|n| n["summary"] == "integration summary")
+    });
+}
+
+#[test]
+fn mutes_suppress_display_but_not_history() {
+    if !enabled() {
+        return;
+    }
+    let harness = Harness::start();
+    let connection = harness.connect();
+
+    connection
+        .call_method(
+            Some(BUS_NAME),
+            CONTROL_PATH,
+            Some(CONTROL_INTERFACE),
+            "Mute",
+            &("muted-app", ""),
+        )
+        .expect("Mute failed");
+
+    harness.notify(&connection, "muted-app", "muted summary", "");
+    harness.notify(&connection, "loud-app", "loud summary", "");
+
+    // Only the unmuted notification reaches the unread buffer
+    wait_for(Duration::from_secs(5), || {
+        harness
+            .export_unread(&connection)
+            .as_array()
+            .expect("unread buffer is not an array")
+            .iter()
+            .any(|n| n["summary"] == "loud summary")
+    });
+    assert!(
+        !harness
+            .export_unread(&connection)
+            .as_array()
+            .expect("unread buffer is not an array")
+            .iter()
+            .any(|n| n["summary"] == "muted summary")
+    );
+
+    // ...but history still records the muted one
+    wait_for(Duration::from_secs(5), || {
+        std::fs::read_to_string(&harness.history_path)
+            .map(|history| history.contains("muted summary"))
+            .unwrap_or(false)
+    });
+
+    let existed: bool = connection
+        .call_method(
+            Some(BUS_NAME),
+            CONTROL_PATH,
+            Some(CONTROL_INTERFACE),
+            "Unmute",
+            &("muted-app",),
+        )
+        .expect("Unmute failed")
+        .body()
+        .deserialize()
+        .expect("invalid Unmute reply");
+    assert!(existed);
+}